@@ -13,33 +13,390 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use crate::der::Tag;
-use crate::{der, signed_data, Error};
+use crate::{
+    der, oid, pin, signed_data, subject_name, verify_cert, DistinguishedName, Error, TrustAnchor,
+    EKU_PRECERT_SIGNING,
+};
+use core::convert::TryFrom;
+use ring::digest;
 
+/// The role of a certificate on a path.
+#[derive(Clone, Copy, Debug)]
 pub enum EndEntityOrCa<'a> {
+    /// The end-entity certificate.
     EndEntity,
+
+    /// A CA certificate, with the certificate it issued on the path.
     Ca(&'a Cert<'a>),
 }
 
+/// A parsed X.509 certificate, as seen during path building.
+///
+/// Only read-only accessors are provided; a `Cert` can only be obtained from
+/// this crate, e.g. with `EndEntityCert::as_cert`.
+#[derive(Clone)]
 pub struct Cert<'a> {
-    pub ee_or_ca: EndEntityOrCa<'a>,
+    pub(crate) ee_or_ca: EndEntityOrCa<'a>,
+    pub(crate) v1: bool,
+
+    pub(crate) der: untrusted::Input<'a>,
+    pub(crate) signed_data: signed_data::SignedData<'a>,
+    pub(crate) tbs: untrusted::Input<'a>,
+    pub(crate) serial: untrusted::Input<'a>,
+    pub(crate) issuer: untrusted::Input<'a>,
+    pub(crate) validity: untrusted::Input<'a>,
+    pub(crate) subject: untrusted::Input<'a>,
+    pub(crate) spki: der::Value<'a>,
+    pub(crate) spki_der: untrusted::Input<'a>,
+
+    pub(crate) basic_constraints: Option<untrusted::Input<'a>>,
+    pub(crate) eku: Option<untrusted::Input<'a>>,
+    pub(crate) eku_critical: bool,
+    pub(crate) name_constraints: Option<untrusted::Input<'a>>,
+    pub(crate) subject_alt_name: Option<untrusted::Input<'a>>,
+    pub(crate) subject_key_id: Option<untrusted::Input<'a>>,
+    pub(crate) key_usage: Option<untrusted::Input<'a>>,
+    pub(crate) sct_list: Option<untrusted::Input<'a>>,
+    pub(crate) ct_poison: bool,
+    #[cfg(feature = "proxy-certs")]
+    pub(crate) proxy_cert_info: Option<untrusted::Input<'a>>,
+}
+
+impl<'a> Cert<'a> {
+    /// Returns the certificate's DER encoding.
+    pub fn der(&self) -> &'a [u8] {
+        self.der.as_slice_less_safe()
+    }
+
+    /// Returns the value (without tag and length) of the certificate's
+    /// `issuer` Name.
+    pub fn issuer(&self) -> &'a [u8] {
+        self.issuer.as_slice_less_safe()
+    }
+
+    /// Returns the value (without tag and length) of the certificate's
+    /// `subject` Name.
+    pub fn subject(&self) -> &'a [u8] {
+        self.subject.as_slice_less_safe()
+    }
+
+    /// Returns the certificate's `issuer` Name, parsed.
+    pub fn issuer_name(&self) -> Result<DistinguishedName<'a>, Error> {
+        DistinguishedName::try_from(self.issuer())
+    }
+
+    /// Returns the certificate's `subject` Name, parsed.
+    pub fn subject_name(&self) -> Result<DistinguishedName<'a>, Error> {
+        DistinguishedName::try_from(self.subject())
+    }
+
+    /// Returns whether the certificate is self-issued: whether its subject
+    /// and issuer match, as names are matched when building paths (RFC 5280
+    /// section 7.1).
+    ///
+    /// A self-issued intermediate certificate, such as one a CA issues to
+    /// certify its new key with its old one, doesn't count toward the path
+    /// length constraints of the certificates above it, and its names aren't
+    /// checked against their name constraints (RFC 5280 section 6.1). Such a
+    /// certificate can therefore make a path longer than its path length
+    /// constraints would otherwise allow.
+    pub fn is_self_issued(&self) -> bool {
+        subject_name::names_match(self.issuer, self.subject)
+    }
+
+    /// Returns whether the certificate is self-signed: whether it is
+    /// self-issued and its signature, made with one of `supported_sig_algs`,
+    /// is valid for its own public key.
+    pub fn is_self_signed(&self, supported_sig_algs: &[&signed_data::SignatureAlgorithm]) -> bool {
+        self.is_self_issued()
+            && signed_data::verify_signed_data(
+                supported_sig_algs,
+                self.spki.value(),
+                &self.signed_data,
+                signed_data::EcdsaSignatures::Der,
+                signed_data::AlgorithmParameters::Strict,
+            )
+            .is_ok()
+    }
+
+    /// Returns whether the certificate is a Certificate Transparency
+    /// Precertificate Signing Certificate: whether its extended key usage
+    /// includes `EKU_PRECERT_SIGNING` (RFC 6962 Section 3.1).
+    ///
+    /// Such a certificate is issued by a CA to issue precertificates in its
+    /// place, and is accepted as their issuer without having the purpose
+    /// they are verified for.
+    pub fn is_precert_signing_cert(&self) -> bool {
+        untrusted::read_all_optional(self.eku, Error::BadDer, |value| match value {
+            Some(value) => verify_cert::check_eku(Some(value), EKU_PRECERT_SIGNING),
+            None => Err(Error::RequiredEkuNotFound),
+        })
+        .is_ok()
+    }
+
+    /// Returns the value (without tag and length) of the certificate's
+    /// `serialNumber` INTEGER.
+    ///
+    /// This is the big-endian two's complement encoding, which may have a
+    /// leading zero byte; negative and overlong serial numbers are not
+    /// rejected.
+    pub fn serial(&self) -> &'a [u8] {
+        self.serial.as_slice_less_safe()
+    }
+
+    /// Returns whether the certificate's serial number conforms to RFC 5280:
+    /// an integer that is not negative and is at most 20 octets long.
+    ///
+    /// Certificates with non-conforming serial numbers are still accepted;
+    /// see `VerificationDiagnostics::warning`.
+    pub fn serial_is_conforming(&self) -> bool {
+        let serial = self.serial();
+        match serial.first() {
+            Some(first) => first & 0x80 == 0 && serial.len() <= 20,
+            None => false,
+        }
+    }
+
+    /// Returns the certificate's serial number formatted as uppercase
+    /// hexadecimal, for logs and revocation databases.
+    pub fn serial_hex(&self) -> SerialHex<'a> {
+        SerialHex(self.serial())
+    }
 
-    pub signed_data: signed_data::SignedData<'a>,
-    pub issuer: untrusted::Input<'a>,
-    pub validity: untrusted::Input<'a>,
-    pub subject: untrusted::Input<'a>,
-    pub spki: der::Value<'a>,
+    /// Returns the DER encoding of the certificate's
+    /// `subjectPublicKeyInfo`, including its tag and length.
+    pub fn spki_der(&self) -> &'a [u8] {
+        self.spki_der.as_slice_less_safe()
+    }
+
+    /// Returns the contents of the certificate's basicConstraints extension.
+    ///
+    /// A certificate without the extension, including a v1 certificate
+    /// accepted as an intermediate, is reported as not being a CA. This
+    /// fails with `Error::BadDer` if the extension is malformed.
+    pub fn basic_constraints(&self) -> Result<BasicConstraints, Error> {
+        untrusted::read_all_optional(self.basic_constraints, Error::BadDer, |input| {
+            let input = match input {
+                Some(input) => input,
+                None => {
+                    return Ok(BasicConstraints {
+                        is_ca: false,
+                        path_len_constraint: None,
+                    })
+                }
+            };
+            let is_ca = der::optional_boolean(input)?;
+
+            // https://bugzilla.mozilla.org/show_bug.cgi?id=985025: RFC 5280
+            // says that a certificate must not have pathLenConstraint unless
+            // it is a CA certificate, but some real-world end-entity
+            // certificates have pathLenConstraint.
+            let path_len_constraint = if !input.at_end() {
+                let value = der::small_nonnegative_integer(input)?;
+                Some(usize::from(value))
+            } else {
+                None
+            };
+
+            Ok(BasicConstraints {
+                is_ca,
+                path_len_constraint,
+            })
+        })
+    }
+
+    /// Returns the value of the certificate's subject key identifier
+    /// extension, if it has one.
+    ///
+    /// Fails if the extension is malformed.
+    pub fn subject_key_id(&self) -> Result<Option<&'a [u8]>, Error> {
+        self.subject_key_id
+            .map(|value| {
+                value.read_all(Error::BadDer, |value| {
+                    der::expect_tag_and_get_value(value, der::Tag::OctetString)
+                        .map(|id| id.as_slice_less_safe())
+                })
+            })
+            .transpose()
+    }
 
-    pub basic_constraints: Option<untrusted::Input<'a>>,
-    pub eku: Option<untrusted::Input<'a>>,
-    pub name_constraints: Option<untrusted::Input<'a>>,
-    pub subject_alt_name: Option<untrusted::Input<'a>>,
+    /// Returns whether the certificate's keyUsage extension asserts the
+    /// keyCertSign bit, or `None` if it has no keyUsage extension.
+    ///
+    /// Fails if the extension is malformed.
+    pub fn key_cert_sign(&self) -> Result<Option<bool>, Error> {
+        const KEY_CERT_SIGN: usize = 5;
+        self.key_usage
+            .map(|value| {
+                let key_usage = value.read_all(Error::BadDer, |value| {
+                    der::expect_tag_and_get_value(value, der::Tag::BitString)
+                })?;
+                key_usage_bit(key_usage, KEY_CERT_SIGN)
+            })
+            .transpose()
+    }
+
+    /// Returns the role of the certificate on the path being built.
+    pub fn ee_or_ca(&self) -> &EndEntityOrCa<'a> {
+        &self.ee_or_ca
+    }
+}
+
+// Returns whether bit `bit` of the keyUsage BIT STRING value `key_usage` is
+// set. Bits past the end of the string are unset.
+fn key_usage_bit(key_usage: untrusted::Input, bit: usize) -> Result<bool, Error> {
+    key_usage.read_all(Error::BadDer, |key_usage| {
+        let unused_bits = key_usage.read_byte().map_err(|_| Error::BadDer)?;
+        let bits = key_usage.read_bytes_to_end().as_slice_less_safe();
+        // An empty BIT STRING has no unused bits, and a non-empty one has at
+        // most 7, which must be zero.
+        match bits.last() {
+            None if unused_bits != 0 => return Err(Error::BadDer),
+            Some(last) if unused_bits > 7 || last & ((1 << unused_bits) - 1) != 0 => {
+                return Err(Error::BadDer)
+            }
+            _ => (),
+        }
+        Ok(bits
+            .get(bit / 8)
+            .map_or(false, |byte| byte & (0x80 >> (bit % 8)) != 0))
+    })
+}
+
+/// The contents of a certificate's basicConstraints extension; see
+/// `Cert::basic_constraints`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BasicConstraints {
+    /// Whether the certificate is a CA certificate.
+    pub is_ca: bool,
+
+    /// The maximum number of non-self-issued intermediate certificates that
+    /// may follow the certificate on a path, if limited.
+    pub path_len_constraint: Option<usize>,
+}
+
+/// A certificate serial number, formatted by `Display` in canonical form:
+/// uppercase hexadecimal, two digits per byte, with no leading zero bytes
+/// other than for a serial number of zero.
+///
+/// For example, a serial number encoded as `00 9A 0B` is formatted as
+/// `9A0B`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerialHex<'a>(&'a [u8]);
+
+impl core::fmt::Display for SerialHex<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let mut serial = self.0;
+        while let [0, rest @ ..] = serial {
+            if rest.is_empty() {
+                break;
+            }
+            serial = rest;
+        }
+        for byte in serial {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// A certificate's identity: the key and name of its issuer and its serial
+/// number, as in a CRL entry or an OCSP request, e.g. as the key of a
+/// revocation status cache; see `RevocationStatusProvider` and
+/// `BlockedCert::CertId`.
+///
+/// Unlike the issuer name and serial number alone, this distinguishes
+/// certificates issued by different CAs with the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CertId<'a> {
+    /// The SHA-256 digest of the issuer's DER-encoded subjectPublicKeyInfo,
+    /// as in `SpkiPin`.
+    pub issuer_spki_sha256: [u8; 32],
+
+    /// The SHA-256 digest of the value of the certificate's `issuer` Name,
+    /// as returned by `Cert::issuer`.
+    pub issuer_dn_sha256: [u8; 32],
+
+    /// The value of the certificate's `serialNumber` INTEGER, as returned by
+    /// `Cert::serial`.
+    pub serial: &'a [u8],
+}
+
+impl<'a> CertId<'a> {
+    /// Returns the identity of `cert`, issued by the holder of the key with
+    /// the DER-encoded subjectPublicKeyInfo `issuer_spki_der`, as returned
+    /// by `Cert::spki_der`.
+    pub fn new(cert: &Cert<'a>, issuer_spki_der: &[u8]) -> Self {
+        Self {
+            issuer_spki_sha256: sha256(issuer_spki_der),
+            issuer_dn_sha256: sha256(cert.issuer()),
+            serial: cert.serial(),
+        }
+    }
+
+    /// Returns the identity of `cert`, issued by `issuer`.
+    pub fn issued_by(cert: &Cert<'a>, issuer: &Cert) -> Self {
+        Self::new(cert, issuer.spki_der())
+    }
+
+    /// Returns the identity of `cert`, issued by the trust anchor `anchor`.
+    pub fn issued_by_anchor(cert: &Cert<'a>, anchor: &TrustAnchor) -> Result<Self, Error> {
+        Self::from_issuer_spki(cert, untrusted::Input::from(anchor.spki))
+    }
+
+    // `issuer_spki` is the value of the issuer's subjectPublicKeyInfo,
+    // without tag and length, as during path building.
+    pub(crate) fn from_issuer_spki(
+        cert: &Cert<'a>,
+        issuer_spki: untrusted::Input,
+    ) -> Result<Self, Error> {
+        let issuer_spki_sha256 = pin::spki_sha256(issuer_spki).ok_or(Error::BadDer)?;
+        Ok(Self {
+            issuer_spki_sha256: to_array(issuer_spki_sha256),
+            issuer_dn_sha256: sha256(cert.issuer()),
+            serial: cert.serial(),
+        })
+    }
+}
+
+fn sha256(input: &[u8]) -> [u8; 32] {
+    to_array(digest::digest(&digest::SHA256, input))
+}
+
+fn to_array(sha256: digest::Digest) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(sha256.as_ref());
+    out
+}
+
+impl core::fmt::Debug for Cert<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Cert")
+            .field("der", &self.der.as_slice_less_safe())
+            .finish()
+    }
 }
 
 pub(crate) fn parse_cert<'a>(
     cert_der: untrusted::Input<'a>,
     ee_or_ca: EndEntityOrCa<'a>,
 ) -> Result<Cert<'a>, Error> {
-    let (tbs, signed_data) = cert_der.read_all(Error::BadDer, |cert_der| {
+    parse_cert_internal(cert_der, ee_or_ca, V1Certs::Reject)
+}
+
+/// Whether X.509 v1 certificates, which have no extensions, are accepted.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum V1Certs {
+    Reject,
+    Accept,
+}
+
+pub(crate) fn parse_cert_internal<'a>(
+    cert_der: untrusted::Input<'a>,
+    ee_or_ca: EndEntityOrCa<'a>,
+    v1_certs: V1Certs,
+) -> Result<Cert<'a>, Error> {
+    let (tbs_value, signed_data) = cert_der.read_all(Error::BadDer, |cert_der| {
         der::nested(
             cert_der,
             der::Tag::Sequence,
@@ -48,9 +405,14 @@ pub(crate) fn parse_cert<'a>(
         )
     })?;
 
-    tbs.read_all(Error::BadDer, |tbs| {
-        version3(tbs)?;
-        lenient_certificate_serial_number(tbs)?;
+    tbs_value.read_all(Error::BadDer, |tbs| {
+        // The version field is omitted, being DEFAULT v1, in v1 certificates.
+        let v1 = v1_certs == V1Certs::Accept
+            && !tbs.peek(u8::from(der::Tag::ContextSpecificConstructed0));
+        if !v1 {
+            version3(tbs)?;
+        }
+        let serial = lenient_certificate_serial_number(tbs)?;
 
         let signature = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
         // TODO: In mozilla::pkix, the comparison is done based on the
@@ -63,7 +425,7 @@ pub(crate) fn parse_cert<'a>(
         let issuer = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
         let validity = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
         let subject = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
-        let spki = der::expect_tag(tbs, der::Tag::Sequence)?;
+        let (spki_der, spki) = tbs.read_partial(|tbs| der::expect_tag(tbs, der::Tag::Sequence))?;
 
         // In theory there could be fields [1] issuerUniqueID and [2]
         // subjectUniqueID, but in practice there never are, and to keep the
@@ -72,20 +434,35 @@ pub(crate) fn parse_cert<'a>(
 
         let mut cert = Cert {
             ee_or_ca,
+            v1,
 
+            der: cert_der,
             signed_data,
+            tbs: tbs_value,
+            serial,
             issuer,
             validity,
             subject,
             spki,
+            spki_der,
 
             basic_constraints: None,
             eku: None,
+            eku_critical: false,
             name_constraints: None,
             subject_alt_name: None,
+            subject_key_id: None,
+            key_usage: None,
+            sct_list: None,
+            ct_poison: false,
+            #[cfg(feature = "proxy-certs")]
+            proxy_cert_info: None,
         };
 
         if !tbs.at_end() {
+            if v1 {
+                return Err(Error::BadDer);
+            }
             der::nested(
                 tbs,
                 der::Tag::ContextSpecificConstructed3,
@@ -101,7 +478,7 @@ pub(crate) fn parse_cert<'a>(
                             let critical = der::optional_boolean(extension)?;
                             let extn_value =
                                 der::expect_tag_and_get_value(extension, der::Tag::OctetString)?;
-                            match remember_extension(&mut cert, extn_id, extn_value)? {
+                            match remember_extension(&mut cert, extn_id, critical, extn_value)? {
                                 Understood::No if critical => {
                                     Err(Error::UnsupportedCriticalExtension)
                                 }
@@ -135,9 +512,9 @@ fn version3(input: &mut untrusted::Reader) -> Result<(), Error> {
     )
 }
 
-pub(crate) fn lenient_certificate_serial_number(
-    input: &mut untrusted::Reader,
-) -> Result<(), Error> {
+pub(crate) fn lenient_certificate_serial_number<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<untrusted::Input<'a>, Error> {
     // https://tools.ietf.org/html/rfc5280#section-4.1.2.2:
     // * Conforming CAs MUST NOT use serialNumber values longer than 20 octets."
     // * "The serial number MUST be a positive integer [...]"
@@ -148,8 +525,9 @@ pub(crate) fn lenient_certificate_serial_number(
     //   Note: Non-conforming CAs may issue certificates with serial numbers
     //   that are negative or zero.  Certificate users SHOULD be prepared to
     //   gracefully handle such certificates.
-    der::expect_tag_and_get_value(input, Tag::Integer)?;
-    Ok(())
+    //
+    // Path building reports them with `Warning::NonConformingSerial` instead.
+    der::expect_tag_and_get_value(input, Tag::Integer)
 }
 
 enum Understood {
@@ -157,9 +535,21 @@ enum Understood {
     No,
 }
 
+// Remembers the value of an extension that is only parsed when it is used,
+// so that a malformed or duplicate one is only rejected where it matters,
+// e.g. for CA certificates. A duplicate is remembered as an empty value,
+// which never parses.
+fn remember_unparsed<'a>(out: &mut Option<untrusted::Input<'a>>, value: untrusted::Input<'a>) {
+    *out = Some(match out {
+        None => value,
+        Some(_) => untrusted::Input::from(&[]),
+    });
+}
+
 fn remember_extension<'a>(
     cert: &mut Cert<'a>,
     extn_id: untrusted::Input,
+    critical: bool,
     value: untrusted::Input<'a>,
 ) -> Result<Understood, Error> {
     // We don't do anything with certificate policies so we can safely ignore
@@ -169,17 +559,59 @@ fn remember_extension<'a>(
     // id-ce 2.5.29
     static ID_CE: [u8; 2] = oid![2, 5, 29];
 
+    // The precertificate poison's value is an ASN.1 NULL.
+    if oid::CT_POISON.matches(extn_id) {
+        if cert.ct_poison || value.as_slice_less_safe() != [0x05, 0x00] {
+            return Err(Error::ExtensionValueInvalid);
+        }
+        cert.ct_poison = true;
+        return Ok(Understood::Yes);
+    }
+
+    #[cfg(feature = "proxy-certs")]
+    {
+        if oid::PROXY_CERT_INFO.matches(extn_id) {
+            if cert.proxy_cert_info.is_some() {
+                return Err(Error::ExtensionValueInvalid);
+            }
+            cert.proxy_cert_info = Some(value.read_all(Error::BadDer, |value| {
+                der::expect_tag_and_get_value(value, der::Tag::Sequence)
+            })?);
+            return Ok(Understood::Yes);
+        }
+    }
+
+    // Unlike the id-ce extensions below, the value of the SCT list is an
+    // OCTET STRING wrapping a TLS-encoded structure, so it is remembered
+    // as-is and only parsed on demand.
+    if oid::SCT_LIST.matches(extn_id) {
+        if cert.sct_list.is_some() {
+            return Err(Error::ExtensionValueInvalid);
+        }
+        cert.sct_list = Some(value);
+        return Ok(Understood::Yes);
+    }
+
     if extn_id.len() != ID_CE.len() + 1 || !extn_id.as_slice_less_safe().starts_with(&ID_CE) {
         return Ok(Understood::No);
     }
 
     let out = match *extn_id.as_slice_less_safe().last().unwrap() {
-        // id-ce-keyUsage 2.5.29.15. We ignore the KeyUsage extension. For CA
-        // certificates, BasicConstraints.cA makes KeyUsage redundant. Firefox
-        // and other common browsers do not check KeyUsage for end-entities,
-        // though it would be kind of nice to ensure that a KeyUsage without
-        // the keyEncipherment bit could not be used for RSA key exchange.
+        // id-ce-keyUsage 2.5.29.15. Only the keyCertSign bit of CA
+        // certificates is checked, so the value is only parsed then, by
+        // `Cert::key_cert_sign`. Firefox and other common browsers do not
+        // check KeyUsage for end-entities, though it would be kind of nice
+        // to ensure that a KeyUsage without the keyEncipherment bit could not
+        // be used for RSA key exchange.
         15 => {
+            remember_unparsed(&mut cert.key_usage, value);
+            return Ok(Understood::Yes);
+        }
+
+        // id-ce-subjectKeyIdentifier 2.5.29.14. As with KeyUsage, the value
+        // is only parsed when it is used, by `Cert::subject_key_id`.
+        14 => {
+            remember_unparsed(&mut cert.subject_key_id, value);
             return Ok(Understood::Yes);
         }
 
@@ -193,7 +625,10 @@ fn remember_extension<'a>(
         30 => &mut cert.name_constraints,
 
         // id-ce-extKeyUsage 2.5.29.37
-        37 => &mut cert.eku,
+        37 => {
+            cert.eku_critical = critical;
+            &mut cert.eku
+        }
 
         _ => {
             return Ok(Understood::No);
@@ -217,3 +652,18 @@ fn remember_extension<'a>(
 
     Ok(Understood::Yes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn serial_hex() {
+        let hex = |serial: &[u8]| SerialHex(serial).to_string();
+        assert_eq!(hex(&[0x00, 0x9a, 0x0b]), "9A0B");
+        assert_eq!(hex(&[0x00, 0x00, 0x01]), "01");
+        assert_eq!(hex(&[0x7f, 0x00]), "7F00");
+        assert_eq!(hex(&[0x00]), "00");
+    }
+}
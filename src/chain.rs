@@ -0,0 +1,398 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use crate::{
+    cert::{self, Cert, CertId, EndEntityOrCa},
+    der,
+    limits::MAX_SUB_CA_COUNT,
+    pin, signed_data, Error, KeyPurposeId, RevocationStatusProvider, SpkiPin, Time, TrustAnchor,
+    VerificationOptions,
+};
+use core::convert::TryFrom;
+use ring::digest;
+
+/// The DER-encoded certificates of a verified path, in the order of a TLS
+/// `Certificate` message: the end-entity certificate first, followed by
+/// each intermediate certificate, each one issuing the one before it.
+///
+/// The trust anchor is not included.
+#[derive(Clone, Copy, Debug)]
+pub struct CertChain<'a> {
+    certs: [&'a [u8]; 1 + MAX_SUB_CA_COUNT],
+    len: usize,
+}
+
+impl<'a> CertChain<'a> {
+    /// Returns all the certificates in the chain, end-entity certificate
+    /// first.
+    pub fn certs(&self) -> &[&'a [u8]] {
+        &self.certs[..self.len]
+    }
+
+    /// Returns the end-entity certificate.
+    pub fn end_entity(&self) -> &'a [u8] {
+        self.certs[0]
+    }
+
+    /// Returns the intermediate certificates, starting with the issuer of
+    /// the end-entity certificate.
+    pub fn intermediates(&self) -> &[&'a [u8]] {
+        &self.certs[1..self.len]
+    }
+}
+
+/// A verified path: the certificates of a `CertChain`, and the trust anchor
+/// they chain to.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifiedChain<'a> {
+    chain: CertChain<'a>,
+    anchor: TrustAnchor<'a>,
+}
+
+impl<'a> VerifiedChain<'a> {
+    /// Returns the trust anchor the chain ends at.
+    pub fn anchor(&self) -> &TrustAnchor<'a> {
+        &self.anchor
+    }
+
+    /// Returns the intermediate certificates, starting with the issuer of
+    /// the end-entity certificate.
+    pub fn intermediates(&self) -> &[&'a [u8]] {
+        self.chain.intermediates()
+    }
+
+    /// Returns the end-entity certificate.
+    pub fn leaf(&self) -> &'a [u8] {
+        self.chain.end_entity()
+    }
+
+    /// Returns the certificates of the chain, without the trust anchor.
+    pub fn chain(&self) -> &CertChain<'a> {
+        &self.chain
+    }
+
+    /// Returns true if the subjectPublicKeyInfo of the trust anchor or of any
+    /// certificate in the chain matches `pin`.
+    pub fn contains_spki(&self, pin: &SpkiPin) -> bool {
+        let matches = |spki_sha256: &[u8]| pin.as_ref() == spki_sha256;
+        self.parsed_certs().any(|cert| {
+            matches(digest::digest(&digest::SHA256, cert.spki_der.as_slice_less_safe()).as_ref())
+        }) || pin::spki_sha256(untrusted::Input::from(self.anchor.spki))
+            .map_or(false, |spki_sha256| matches(spki_sha256.as_ref()))
+    }
+
+    /// Returns the earliest notAfter time of the leaf and intermediate
+    /// certificates: the last time at which the chain is valid, e.g. to
+    /// schedule renewal of the leaf or reloading of the chain before then.
+    ///
+    /// Trust anchors have no validity period, so the anchor is not taken
+    /// into account.
+    pub fn expires_at(&self) -> Time {
+        let mut expires_at = Time::from_seconds_since_unix_epoch(u64::MAX);
+        for cert in self.parsed_certs() {
+            let not_after = cert.validity.read_all(Error::BadDer, |value| {
+                let _not_before = der::time_choice(value)?;
+                der::time_choice(value)
+            });
+            match not_after {
+                Ok(not_after) if not_after < expires_at => expires_at = not_after,
+                Ok(_) => (),
+                Err(_) => unreachable!(),
+            }
+        }
+        expires_at
+    }
+
+    /// Returns the earliest time at which `provider` says the revocation
+    /// data of a certificate in the chain must be replaced, e.g. to schedule
+    /// fetching fresh CRLs or OCSP responses before then, so that a
+    /// revocation status provider used with `UnknownStatusPolicy::Deny`
+    /// keeps accepting the chain.
+    ///
+    /// Certificates for which `provider` doesn't know a nextUpdate time are
+    /// not taken into account; returns `None` if it knows none.
+    pub fn revocation_next_update(&self, provider: &dyn RevocationStatusProvider) -> Option<Time> {
+        let mut next_update: Option<Time> = None;
+        for cert_id in self.cert_ids() {
+            if let Some(cert_next_update) = provider.next_update(&cert_id) {
+                next_update = Some(match next_update {
+                    Some(earliest) if earliest <= cert_next_update => earliest,
+                    _ => cert_next_update,
+                });
+            }
+        }
+        next_update
+    }
+
+    /// Returns the identities of the certificates in the chain, starting
+    /// with the end-entity certificate, e.g. to keep alongside the chain's
+    /// `VerificationToken` and find the sessions to evict with
+    /// `revoked_cert_ids` when new revocation data is loaded.
+    pub fn cert_ids(&self) -> impl Iterator<Item = CertId<'a>> + '_ {
+        let mut certs = self.parsed_certs().peekable();
+        core::iter::from_fn(move || {
+            let cert = certs.next()?;
+            match certs.peek() {
+                Some(issuer) => Some(CertId::issued_by(&cert, issuer)),
+                None => CertId::issued_by_anchor(&cert, &self.anchor).ok(),
+            }
+        })
+    }
+
+    /// Checks the revocation status of every certificate in the chain, as
+    /// `VerificationOptions::with_revocation_status_provider` does during
+    /// path building, with the provider and at the time of `options`.
+    ///
+    /// This lets a chain be verified first, and checked for revocation as a
+    /// second step, e.g. once the CRLs or OCSP responses for its
+    /// certificates have been fetched. Fails with `Error::CertRevoked` or
+    /// `Error::UnknownRevocationStatus` for the first certificate, starting
+    /// with the end-entity certificate, that is revoked or whose unknown
+    /// status isn't accepted. Succeeds if `options` has no provider.
+    pub fn check_revocation(&self, options: &VerificationOptions) -> Result<(), Error> {
+        let mut certs = self.parsed_certs().peekable();
+        while let Some(cert) = certs.next() {
+            let issuer_spki = match certs.peek() {
+                Some(issuer) => issuer.spki.value(),
+                None => untrusted::Input::from(self.anchor.spki),
+            };
+            options.check_revocation(&cert, issuer_spki)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the size in bits of the smallest public key in the chain,
+    /// including the trust anchor's.
+    ///
+    /// RSA keys are measured by the size of their modulus, and elliptic
+    /// curve keys by the size of their curve. Returns `None` if the size of
+    /// any of the keys is unknown, because its algorithm isn't supported.
+    pub fn weakest_key_bits(&self) -> Option<usize> {
+        let mut weakest = signed_data::public_key_bits(untrusted::Input::from(self.anchor.spki))?;
+        for cert in self.parsed_certs() {
+            weakest = weakest.min(signed_data::public_key_bits(cert.spki.value())?);
+        }
+        Some(weakest)
+    }
+
+    /// Returns a token recording that the chain was verified for the
+    /// extended key usage `eku` with `options`, as by
+    /// `EndEntityCert::build_path`, to revalidate it cheaply later, e.g.
+    /// when a TLS session is resumed; see `VerificationToken`.
+    pub fn verification_token(
+        &self,
+        options: &VerificationOptions,
+        eku: KeyPurposeId,
+    ) -> VerificationToken {
+        let mut not_before = Time::from_seconds_since_unix_epoch(0);
+        for cert in self.parsed_certs() {
+            let cert_not_before = cert.validity.read_all(Error::BadDer, |value| {
+                let not_before = der::time_choice(value)?;
+                let _not_after = der::time_choice(value)?;
+                Ok(not_before)
+            });
+            match cert_not_before {
+                Ok(cert_not_before) if cert_not_before > not_before => not_before = cert_not_before,
+                Ok(_) => (),
+                Err(_) => unreachable!(),
+            }
+        }
+        VerificationToken {
+            digest: token_digest(self.chain.certs(), &self.anchor, options, eku),
+            not_before,
+            not_after: self.expires_at(),
+        }
+    }
+
+    // Every certificate in the chain was parsed while verifying it, so this
+    // cannot fail.
+    fn parsed_certs(&self) -> impl Iterator<Item = Cert<'a>> + '_ {
+        self.chain.certs().iter().map(|der| {
+            match cert::parse_cert_internal(
+                untrusted::Input::from(*der),
+                EndEntityOrCa::EndEntity,
+                cert::V1Certs::Accept,
+            ) {
+                Ok(cert) => cert,
+                Err(_) => unreachable!(),
+            }
+        })
+    }
+}
+
+/// A compact record of a successful verification: a digest of the verified
+/// path, the trust anchor, the extended key usage and the options it was
+/// verified with, and the time window in which all the certificates on the
+/// path are valid.
+///
+/// `is_still_valid` checks, without parsing certificates or verifying
+/// signatures, that the same path would be verified with the same options
+/// at the time of the given options. It doesn't account for anything that
+/// isn't an input of the verification, such as a revocation that isn't on
+/// the options' blocklist; to find the tokens that a newly published
+/// revocation invalidates, keep the chain's `VerifiedChain::cert_ids`
+/// alongside the token, and look them up with `revoked_cert_ids`.
+///
+/// Anyone can compute a token for any inputs, so a token only attests to a
+/// verification if it was stored where the peer cannot modify it, e.g. in
+/// an encrypted session ticket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerificationToken {
+    digest: [u8; 32],
+    not_before: Time,
+    not_after: Time,
+}
+
+impl VerificationToken {
+    /// Returns the 48-byte serialization of the token.
+    pub fn to_bytes(&self) -> [u8; 48] {
+        let mut bytes = [0; 48];
+        bytes[..32].copy_from_slice(&self.digest);
+        bytes[32..40].copy_from_slice(&self.not_before.as_seconds_since_unix_epoch().to_be_bytes());
+        bytes[40..].copy_from_slice(&self.not_after.as_seconds_since_unix_epoch().to_be_bytes());
+        bytes
+    }
+
+    /// Parses a token serialized with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8; 48]) -> Self {
+        let mut digest = [0; 32];
+        digest.copy_from_slice(&bytes[..32]);
+        let time = |bytes: &[u8]| match <[u8; 8]>::try_from(bytes) {
+            Ok(secs) => Time::from_seconds_since_unix_epoch(u64::from_be_bytes(secs)),
+            Err(_) => unreachable!(),
+        };
+        Self {
+            digest,
+            not_before: time(&bytes[32..40]),
+            not_after: time(&bytes[40..]),
+        }
+    }
+
+    /// Returns true if the path of DER-encoded certificates `certs`, in the
+    /// order of `CertChain::certs`, ending at `anchor`, is the path that the
+    /// token was made for, `options` and `eku` are the same as when it was
+    /// verified except for the time, and all the certificates are valid at
+    /// the time of `options`.
+    ///
+    /// Code can't be compared, so for the certificate and chain checks, the
+    /// revocation status provider and the verification functions of custom
+    /// signature algorithms, only whether they are set, and the names and
+    /// identifiers of the algorithms, are. The caller must make sure that
+    /// these behave as they did when the token was made, e.g. by not
+    /// accepting tokens across a change to them; otherwise this may return
+    /// true for a path they would now reject.
+    pub fn is_still_valid(
+        &self,
+        certs: &[&[u8]],
+        anchor: &TrustAnchor,
+        options: &VerificationOptions,
+        eku: KeyPurposeId,
+    ) -> bool {
+        self.not_before <= options.time
+            && options.time <= self.not_after
+            && self.digest == token_digest(certs, anchor, options, eku)
+    }
+}
+
+// Every variable-length input is prefixed with its length, so that distinct
+// inputs cannot hash the same.
+fn token_digest(
+    certs: &[&[u8]],
+    anchor: &TrustAnchor,
+    options: &VerificationOptions,
+    eku: KeyPurposeId,
+) -> [u8; 32] {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    let mut update = |bytes: &[u8]| {
+        #[allow(clippy::as_conversions)] // usize is at most 64 bits.
+        ctx.update(&(bytes.len() as u64).to_be_bytes());
+        ctx.update(bytes);
+    };
+    #[allow(clippy::as_conversions)] // usize is at most 64 bits.
+    update(&(certs.len() as u64).to_be_bytes());
+    for cert in certs {
+        update(cert);
+    }
+    update(anchor.subject);
+    update(anchor.spki);
+    update(&[u8::from(anchor.name_constraints.is_some())]);
+    update(anchor.name_constraints.unwrap_or(&[]));
+    update(eku.oid().value());
+    options.update_policy_digest(&mut update);
+
+    let mut digest = [0; 32];
+    digest.copy_from_slice(ctx.finish().as_ref());
+    digest
+}
+
+/// The positions in `intermediate_certs` of the intermediate certificates
+/// of a verified path, starting with the issuer of the end-entity
+/// certificate.
+#[derive(Clone, Copy)]
+pub(crate) struct PathIndices {
+    indices: [usize; MAX_SUB_CA_COUNT],
+    len: usize,
+}
+
+impl PathIndices {
+    /// Finds the intermediate certificates of `path` in `intermediate_certs`.
+    ///
+    /// Returns `None` if the path is longer than `MAX_SUB_CA_COUNT`
+    /// intermediates, or a certificate on it did not come from
+    /// `intermediate_certs`; `build_chain` never produces such paths.
+    pub(crate) fn new(path: &Cert, intermediate_certs: &[&[u8]]) -> Option<Self> {
+        let mut indices = [0; MAX_SUB_CA_COUNT];
+        let mut len = 0;
+        let mut cert = path;
+        while let EndEntityOrCa::Ca(child_cert) = &cert.ee_or_ca {
+            let der = cert.der.as_slice_less_safe();
+            *indices.get_mut(len)? = intermediate_certs
+                .iter()
+                .position(|intermediate| *intermediate == der)?;
+            len += 1;
+            cert = child_cert;
+        }
+
+        // `path` starts with the certificate issued by the trust anchor.
+        indices[..len].reverse();
+        Some(Self { indices, len })
+    }
+
+    pub(crate) fn into_chain<'a>(
+        self,
+        end_entity: &'a [u8],
+        intermediate_certs: &[&'a [u8]],
+    ) -> CertChain<'a> {
+        let mut certs = [end_entity; 1 + MAX_SUB_CA_COUNT];
+        for (cert, index) in certs[1..].iter_mut().zip(&self.indices[..self.len]) {
+            *cert = intermediate_certs[*index];
+        }
+        CertChain {
+            certs,
+            len: 1 + self.len,
+        }
+    }
+
+    pub(crate) fn into_verified_chain<'a>(
+        self,
+        anchor: TrustAnchor<'a>,
+        end_entity: &'a [u8],
+        intermediate_certs: &[&'a [u8]],
+    ) -> VerifiedChain<'a> {
+        VerifiedChain {
+            chain: self.into_chain(end_entity, intermediate_certs),
+            anchor,
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! DNS-Based Authentication of Named Entities (DANE) TLSA record matching.
+//!
+//! See [RFC 6698] and [RFC 7671].
+//!
+//! [RFC 6698]: https://tools.ietf.org/html/rfc6698
+//! [RFC 7671]: https://tools.ietf.org/html/rfc7671
+
+use crate::cert::Cert;
+use ring::digest;
+
+#[cfg(feature = "tls-server-verify")]
+use crate::{
+    cert::{self, EndEntityOrCa},
+    der, verify_cert, EndEntityCert, Error, SignatureAlgorithm, Time, TlsServerTrustAnchors,
+    TrustAnchor, VerificationOptions,
+};
+#[cfg(feature = "tls-server-verify")]
+use core::cell::Cell;
+
+/// The TLSA certificate usage field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsaCertUsage {
+    /// PKIX-TA(0): a CA certificate on a PKIX-validated path must match.
+    PkixTa,
+    /// PKIX-EE(1): the end-entity certificate must match, and its path must
+    /// be PKIX-validated.
+    PkixEe,
+    /// DANE-TA(2): a certificate presented by the peer is the trust anchor.
+    DaneTa,
+    /// DANE-EE(3): the end-entity certificate must match; no path is needed.
+    DaneEe,
+}
+
+/// The TLSA selector field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsaSelector {
+    /// Cert(0): the full DER-encoded certificate.
+    FullCert,
+    /// SPKI(1): the DER-encoded subjectPublicKeyInfo.
+    Spki,
+}
+
+/// The TLSA matching type field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsaMatchingType {
+    /// Full(0): the selected content itself.
+    Full,
+    /// SHA2-256(1): the SHA-256 digest of the selected content.
+    Sha256,
+    /// SHA2-512(2): the SHA-512 digest of the selected content.
+    Sha512,
+}
+
+/// A TLSA resource record.
+#[derive(Clone, Copy, Debug)]
+pub struct TlsaRecord<'a> {
+    /// The certificate usage.
+    pub usage: TlsaCertUsage,
+    /// Which part of the certificate is matched.
+    pub selector: TlsaSelector,
+    /// How the selected content is presented in `data`.
+    pub matching_type: TlsaMatchingType,
+    /// The certificate association data.
+    pub data: &'a [u8],
+}
+
+impl<'a> TlsaRecord<'a> {
+    /// Constructs a `TlsaRecord` from the fields of its RDATA.
+    ///
+    /// Returns `None` if any of the fields has a value this crate does not
+    /// know. RFC 6698 requires such records to be treated as unusable, so
+    /// callers should skip them.
+    pub fn from_rdata(usage: u8, selector: u8, matching_type: u8, data: &'a [u8]) -> Option<Self> {
+        let usage = match usage {
+            0 => TlsaCertUsage::PkixTa,
+            1 => TlsaCertUsage::PkixEe,
+            2 => TlsaCertUsage::DaneTa,
+            3 => TlsaCertUsage::DaneEe,
+            _ => return None,
+        };
+        let selector = match selector {
+            0 => TlsaSelector::FullCert,
+            1 => TlsaSelector::Spki,
+            _ => return None,
+        };
+        let matching_type = match matching_type {
+            0 => TlsaMatchingType::Full,
+            1 => TlsaMatchingType::Sha256,
+            2 => TlsaMatchingType::Sha512,
+            _ => return None,
+        };
+        Some(Self {
+            usage,
+            selector,
+            matching_type,
+            data,
+        })
+    }
+}
+
+/// Certificate association data computed from a certificate.
+pub struct TlsaAssociationData<'a>(AssociationData<'a>);
+
+enum AssociationData<'a> {
+    Full(&'a [u8]),
+    Digest(digest::Digest),
+}
+
+impl AsRef<[u8]> for TlsaAssociationData<'_> {
+    fn as_ref(&self) -> &[u8] {
+        match &self.0 {
+            AssociationData::Full(data) => data,
+            AssociationData::Digest(digest) => digest.as_ref(),
+        }
+    }
+}
+
+pub(crate) fn association_data<'a>(
+    cert: &Cert<'a>,
+    selector: TlsaSelector,
+    matching_type: TlsaMatchingType,
+) -> TlsaAssociationData<'a> {
+    let selected = match selector {
+        TlsaSelector::FullCert => cert.der,
+        TlsaSelector::Spki => cert.spki_der,
+    };
+    let algorithm = match matching_type {
+        TlsaMatchingType::Full => {
+            return TlsaAssociationData(AssociationData::Full(selected.as_slice_less_safe()));
+        }
+        TlsaMatchingType::Sha256 => &digest::SHA256,
+        TlsaMatchingType::Sha512 => &digest::SHA512,
+    };
+    TlsaAssociationData(AssociationData::Digest(digest::digest(
+        algorithm,
+        selected.as_slice_less_safe(),
+    )))
+}
+
+/// Verifies `cert` against the TLSA records `records`.
+///
+/// Records with usage DANE-EE are matched against `cert` alone, without path
+/// validation. Records with usage DANE-TA are matched against the
+/// certificates in `intermediate_certs`; a matching certificate is used as
+/// the only trust anchor when building a path. Records with usage PKIX-TA or
+/// PKIX-EE additionally require a path to one of `pkix_trust_anchors`; for
+/// PKIX-TA, trust anchors can only be matched using the SPKI selector since
+/// the `TrustAnchor` representation does not retain the full certificate.
+///
+/// The DANE-TA and PKIX usages build paths for a TLS server as
+/// `EndEntityCert::verify_for_usage` does with `Usage::TlsServer`. As with that
+/// function, this does not check that `cert` is valid for the peer's name.
+///
+/// Fails with `Error::NoMatchingTlsaRecord` if no record matches `cert` or a
+/// certificate on a valid path. If a record does match but no valid path
+/// goes through the certificate it matches, this fails with the error from
+/// building the path instead, e.g. `Error::UnknownIssuer` or
+/// `Error::CertExpired`.
+///
+/// Requires the `tls-server-verify` feature.
+#[cfg(feature = "tls-server-verify")]
+pub fn verify_tlsa_records(
+    cert: &EndEntityCert,
+    records: &[TlsaRecord],
+    supported_sig_algs: &[&SignatureAlgorithm],
+    pkix_trust_anchors: &TlsServerTrustAnchors,
+    intermediate_certs: &[&[u8]],
+    time: Time,
+) -> Result<(), Error> {
+    verify_tlsa_records_with_options(
+        cert,
+        records,
+        pkix_trust_anchors,
+        intermediate_certs,
+        &VerificationOptions::new(supported_sig_algs, time),
+    )
+}
+
+/// Verifies `cert` against the TLSA records `records`, building paths as
+/// configured by `options`.
+///
+/// This is `verify_tlsa_records` with `options` in place of the signature
+/// algorithms and time.
+///
+/// Requires the `tls-server-verify` feature.
+#[cfg(feature = "tls-server-verify")]
+pub fn verify_tlsa_records_with_options(
+    cert: &EndEntityCert,
+    records: &[TlsaRecord],
+    &TlsServerTrustAnchors(pkix_trust_anchors): &TlsServerTrustAnchors,
+    intermediate_certs: &[&[u8]],
+    options: &VerificationOptions,
+) -> Result<(), Error> {
+    let leaf = cert.inner();
+
+    // DANE-EE(3) is the cheapest and most common usage, so try it first.
+    if records
+        .iter()
+        .any(|record| record.usage == TlsaCertUsage::DaneEe && cert_matches(record, leaf))
+    {
+        return Ok(());
+    }
+
+    // The error from building a path to the last DANE-TA certificate
+    // matched, if any was.
+    let mut dane_ta_error = None;
+    for record in records
+        .iter()
+        .filter(|record| record.usage == TlsaCertUsage::DaneTa)
+    {
+        for cert_der in intermediate_certs {
+            let candidate = match cert::parse_cert(
+                untrusted::Input::from(cert_der),
+                EndEntityOrCa::EndEntity,
+            ) {
+                Ok(candidate) => candidate,
+                Err(_) => continue,
+            };
+            if !cert_matches(record, &candidate) {
+                continue;
+            }
+            let trust_anchor = TrustAnchor::from(candidate);
+            let result = verify_cert::build_chain(
+                verify_cert::EKU_SERVER_AUTH,
+                verify_cert::Precertificates::Reject,
+                options,
+                core::slice::from_ref(&trust_anchor),
+                intermediate_certs,
+                leaf,
+                0,
+                &|_, _| Ok(()),
+            );
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) => dane_ta_error = Some(err),
+            }
+        }
+    }
+
+    let has_pkix_records = records.iter().any(|record| {
+        record.usage == TlsaCertUsage::PkixEe || record.usage == TlsaCertUsage::PkixTa
+    });
+    if !has_pkix_records {
+        return Err(dane_ta_error.unwrap_or(Error::NoMatchingTlsaRecord));
+    }
+
+    let found_unmatched_path = Cell::new(false);
+    let result = verify_cert::build_chain(
+        verify_cert::EKU_SERVER_AUTH,
+        verify_cert::Precertificates::Reject,
+        options,
+        pkix_trust_anchors,
+        intermediate_certs,
+        leaf,
+        0,
+        &|trust_anchor, path| match records
+            .iter()
+            .any(|record| path_matches(record, trust_anchor, path))
+        {
+            true => Ok(()),
+            false => {
+                found_unmatched_path.set(true);
+                Err(Error::NoMatchingTlsaRecord)
+            }
+        },
+    );
+    match result {
+        Err(Error::UnknownIssuer) | Err(Error::PathLoopDetected) if found_unmatched_path.get() => {
+            Err(Error::NoMatchingTlsaRecord)
+        }
+        result => result,
+    }
+}
+
+#[cfg(feature = "tls-server-verify")]
+fn path_matches(record: &TlsaRecord, trust_anchor: &TrustAnchor, path: &Cert) -> bool {
+    let mut cert = path;
+    loop {
+        match (&cert.ee_or_ca, record.usage) {
+            (EndEntityOrCa::EndEntity, TlsaCertUsage::PkixEe)
+            | (EndEntityOrCa::Ca(..), TlsaCertUsage::PkixTa)
+                if cert_matches(record, cert) =>
+            {
+                return true;
+            }
+            _ => (),
+        }
+        match &cert.ee_or_ca {
+            EndEntityOrCa::Ca(child_cert) => {
+                cert = child_cert;
+            }
+            EndEntityOrCa::EndEntity => {
+                break;
+            }
+        }
+    }
+
+    record.usage == TlsaCertUsage::PkixTa
+        && record.selector == TlsaSelector::Spki
+        && spki_matches(record, trust_anchor.spki)
+}
+
+#[cfg(feature = "tls-server-verify")]
+fn cert_matches(record: &TlsaRecord, cert: &Cert) -> bool {
+    association_data(cert, record.selector, record.matching_type).as_ref() == record.data
+}
+
+// `spki` is the value of a subjectPublicKeyInfo, without the outer SEQUENCE
+// tag and length, as stored in `TrustAnchor`.
+#[cfg(feature = "tls-server-verify")]
+fn spki_matches(record: &TlsaRecord, spki: &[u8]) -> bool {
+    let header = match der::Header::new(der::Tag::Sequence, spki.len()) {
+        Some(header) => header,
+        None => return false,
+    };
+    let header = header.as_ref();
+
+    let algorithm = match record.matching_type {
+        TlsaMatchingType::Full => {
+            return record.data.len() == header.len() + spki.len()
+                && record.data.starts_with(header)
+                && record.data.ends_with(spki);
+        }
+        TlsaMatchingType::Sha256 => &digest::SHA256,
+        TlsaMatchingType::Sha512 => &digest::SHA512,
+    };
+    let mut ctx = digest::Context::new(algorithm);
+    ctx.update(header);
+    ctx.update(spki);
+    ctx.finish().as_ref() == record.data
+}
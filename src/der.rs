@@ -26,6 +26,7 @@ pub(crate) enum Tag {
     OctetString = 0x04,
     OID = 0x06,
     UTF8String = 0x0C,
+    PrintableString = 0x13,
     Sequence = CONSTRUCTED | 0x10, // 0x30
     Set = CONSTRUCTED | 0x11,      // 0x31
     UTCTime = 0x17,
@@ -78,6 +79,47 @@ where
     inner.read_all(error, decoder)
 }
 
+/// The tag and length octets of a DER-encoded item, for reconstructing the
+/// encoding of items that are stored without them, such as
+/// `TrustAnchor::spki`.
+pub(crate) struct Header {
+    bytes: [u8; 4],
+    len: usize,
+}
+
+impl Header {
+    /// Returns `None` if `value_len` needs more than two length octets.
+    pub(crate) fn new(tag: Tag, value_len: usize) -> Option<Self> {
+        let mut bytes = [u8::from(tag), 0, 0, 0];
+        #[allow(clippy::as_conversions)] // Each value is checked to fit in a u8.
+        let len = match value_len {
+            len if len < 0x80 => {
+                bytes[1] = len as u8;
+                2
+            }
+            len if len <= 0xff => {
+                bytes[1] = 0x81;
+                bytes[2] = len as u8;
+                3
+            }
+            len if len <= 0xffff => {
+                bytes[1] = 0x82;
+                bytes[2] = (len >> 8) as u8;
+                bytes[3] = len as u8;
+                4
+            }
+            _ => return None,
+        };
+        Some(Self { bytes, len })
+    }
+}
+
+impl AsRef<[u8]> for Header {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
 pub struct Value<'a> {
     value: untrusted::Input<'a>,
 }
@@ -169,6 +211,23 @@ pub(crate) fn time_choice(input: &mut untrusted::Reader) -> Result<time::Time, E
         Tag::GeneralizedTime
     };
 
+    nested(input, expected_tag, Error::BadDer, |value| {
+        time_value(value, is_utc_time, false)
+    })
+}
+
+/// Parses the value of a GeneralizedTime that may have fractional seconds,
+/// as allowed for the `genTime` of an RFC 3161 time-stamp token. The
+/// fraction is ignored.
+pub(crate) fn generalized_time_with_fraction(value: untrusted::Input) -> Result<time::Time, Error> {
+    value.read_all(Error::BadDerTime, |value| time_value(value, false, true))
+}
+
+fn time_value(
+    value: &mut untrusted::Reader,
+    is_utc_time: bool,
+    allow_fraction: bool,
+) -> Result<time::Time, Error> {
     fn read_digit(inner: &mut untrusted::Reader) -> Result<u64, Error> {
         const DIGIT: core::ops::RangeInclusive<u8> = b'0'..=b'9';
         let b = inner.read_byte().map_err(|_| Error::BadDerTime)?;
@@ -188,32 +247,44 @@ pub(crate) fn time_choice(input: &mut untrusted::Reader) -> Result<time::Time, E
         Ok(value)
     }
 
-    nested(input, expected_tag, Error::BadDer, |value| {
-        let (year_hi, year_lo) = if is_utc_time {
-            let lo = read_two_digits(value, 0, 99)?;
-            let hi = if lo >= 50 { 19 } else { 20 };
-            (hi, lo)
-        } else {
-            let hi = read_two_digits(value, 0, 99)?;
-            let lo = read_two_digits(value, 0, 99)?;
-            (hi, lo)
-        };
+    let (year_hi, year_lo) = if is_utc_time {
+        let lo = read_two_digits(value, 0, 99)?;
+        let hi = if lo >= 50 { 19 } else { 20 };
+        (hi, lo)
+    } else {
+        let hi = read_two_digits(value, 0, 99)?;
+        let lo = read_two_digits(value, 0, 99)?;
+        (hi, lo)
+    };
 
-        let year = (year_hi * 100) + year_lo;
-        let month = read_two_digits(value, 1, 12)?;
-        let days_in_month = calendar::days_in_month(year, month);
-        let day_of_month = read_two_digits(value, 1, days_in_month)?;
-        let hours = read_two_digits(value, 0, 23)?;
-        let minutes = read_two_digits(value, 0, 59)?;
-        let seconds = read_two_digits(value, 0, 59)?;
+    let year = (year_hi * 100) + year_lo;
+    let month = read_two_digits(value, 1, 12)?;
+    let days_in_month = calendar::days_in_month(year, month);
+    let day_of_month = read_two_digits(value, 1, days_in_month)?;
+    let hours = read_two_digits(value, 0, 23)?;
+    let minutes = read_two_digits(value, 0, 59)?;
+    let seconds = read_two_digits(value, 0, 59)?;
 
-        let time_zone = value.read_byte().map_err(|_| Error::BadDerTime)?;
-        if time_zone != b'Z' {
+    let mut time_zone = value.read_byte().map_err(|_| Error::BadDerTime)?;
+    if allow_fraction && time_zone == b'.' {
+        // DER requires at least one digit, and no trailing zeros.
+        let mut last_digit = read_digit(value)?;
+        loop {
+            time_zone = value.read_byte().map_err(|_| Error::BadDerTime)?;
+            match time_zone {
+                b'0'..=b'9' => last_digit = u64::from(time_zone - b'0'),
+                _ => break,
+            }
+        }
+        if last_digit == 0 {
             return Err(Error::BadDerTime);
         }
+    }
+    if time_zone != b'Z' {
+        return Err(Error::BadDerTime);
+    }
 
-        calendar::time_from_ymdhms_utc(year, month, day_of_month, hours, minutes, seconds)
-    })
+    calendar::time_from_ymdhms_utc(year, month, day_of_month, hours, minutes, seconds)
 }
 
 macro_rules! oid {
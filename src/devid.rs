@@ -0,0 +1,110 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Support for IEEE 802.1AR secure device identifiers (DevIDs).
+//!
+//! DevID certificates identify the device by a hardwareModuleName
+//! ([RFC 4108]) in the subjectAltName extension, and initial DevIDs are
+//! typically issued with no well-defined expiration ([RFC 5280 Section
+//! 4.1.2.5]).
+//!
+//! [RFC 4108]: https://tools.ietf.org/html/rfc4108#section-5
+//! [RFC 5280 Section 4.1.2.5]: https://tools.ietf.org/html/rfc5280#section-4.1.2.5
+
+use crate::{calendar, cert::Cert, der, limits, oid, time, Error};
+
+/// The hardwareModuleName of a device, from an otherName in the
+/// subjectAltName extension.
+#[derive(Clone, Copy, Debug)]
+pub struct HardwareModuleName<'a> {
+    /// The encoded value (without tag and length) of the `hwType` object
+    /// identifier, identifying the kind of hardware module.
+    pub hw_type: &'a [u8],
+
+    /// The serial number of the hardware module.
+    pub hw_serial_num: &'a [u8],
+}
+
+pub(crate) fn hardware_module_name<'a>(
+    cert: &Cert<'a>,
+) -> Result<Option<HardwareModuleName<'a>>, Error> {
+    let subject_alt_name = match cert.subject_alt_name {
+        Some(subject_alt_name) => subject_alt_name,
+        None => return Ok(None),
+    };
+
+    subject_alt_name.read_all(Error::BadDer, |names| {
+        let mut count = 0;
+        while !names.at_end() {
+            count += 1;
+            if count > limits::MAX_SUBJECT_ALT_NAMES {
+                return Err(Error::MaximumSubjectAltNamesExceeded);
+            }
+
+            let (tag, value) = der::read_tag_and_get_value(names)?;
+            if tag != u8::from(der::Tag::ContextSpecificConstructed0) {
+                continue;
+            }
+
+            // OtherName ::= SEQUENCE {
+            //      type-id    OBJECT IDENTIFIER,
+            //      value      [0] EXPLICIT ANY DEFINED BY type-id }
+            let name = value.read_all(Error::BadDer, |other_name| {
+                let type_id = der::expect_tag_and_get_value(other_name, der::Tag::OID)?;
+                if !oid::HARDWARE_MODULE_NAME.matches(type_id) {
+                    other_name.skip_to_end();
+                    return Ok(None);
+                }
+                der::nested(
+                    other_name,
+                    der::Tag::ContextSpecificConstructed0,
+                    Error::BadDer,
+                    |value| {
+                        der::nested(value, der::Tag::Sequence, Error::BadDer, |value| {
+                            let hw_type = der::expect_tag_and_get_value(value, der::Tag::OID)?;
+                            let hw_serial_num =
+                                der::expect_tag_and_get_value(value, der::Tag::OctetString)?;
+                            Ok(Some(HardwareModuleName {
+                                hw_type: hw_type.as_slice_less_safe(),
+                                hw_serial_num: hw_serial_num.as_slice_less_safe(),
+                            }))
+                        })
+                    },
+                )
+            })?;
+            if name.is_some() {
+                names.skip_to_end();
+                return Ok(name);
+            }
+        }
+        Ok(None)
+    })
+}
+
+/// Returns true if the certificate's notAfter is the GeneralizedTime
+/// 99991231235959Z, meaning it has no well-defined expiration date.
+pub(crate) fn has_no_well_defined_expiration(cert: &Cert) -> bool {
+    let not_after = cert.validity.read_all(Error::BadDer, |validity| {
+        let _not_before = der::time_choice(validity)?;
+        der::time_choice(validity)
+    });
+    match (not_after, no_well_defined_expiration()) {
+        (Ok(not_after), Ok(unbounded)) => not_after == unbounded,
+        _ => false,
+    }
+}
+
+fn no_well_defined_expiration() -> Result<time::Time, Error> {
+    calendar::time_from_ymdhms_utc(9999, 12, 31, 23, 59, 59)
+}
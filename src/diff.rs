@@ -0,0 +1,217 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use crate::{
+    cert::{self, EndEntityOrCa, V1Certs},
+    der, signed_data, subject_name, Error, KeyKind, PresentedName, Time, TrustAnchor,
+};
+use alloc::vec::Vec;
+
+/// The differences between two certificate chains for the same subject,
+/// e.g. before and after a reissuance, for reviewing the change.
+///
+/// Requires the `alloc` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChainDiff<'a> {
+    /// The subjects of the end-entity certificates differ.
+    pub subject_changed: bool,
+
+    /// The issuers of the end-entity certificates differ.
+    pub issuer_changed: bool,
+
+    /// The subjectPublicKeyInfos of the end-entity certificates differ.
+    pub key_rotated: bool,
+
+    /// The intermediate certificates differ, in content or in order.
+    pub intermediates_changed: bool,
+
+    /// The DNS names and IP addresses in the new end-entity certificate's
+    /// subjectAltName extension that aren't in the old one's.
+    pub names_added: Vec<PresentedName<'a>>,
+
+    /// The DNS names and IP addresses in the old end-entity certificate's
+    /// subjectAltName extension that aren't in the new one's.
+    pub names_removed: Vec<PresentedName<'a>>,
+}
+
+impl<'a> ChainDiff<'a> {
+    /// Compares the chains of DER-encoded certificates `old` and `new`, each
+    /// in the order of `CertChain::certs`: the end-entity certificate first,
+    /// followed by the intermediate certificates.
+    ///
+    /// The chains are only parsed, not verified. Fails with `Error::BadDer`
+    /// if a chain is empty, or with the error from parsing an end-entity
+    /// certificate.
+    pub fn new(old: &[&'a [u8]], new: &[&'a [u8]]) -> Result<Self, Error> {
+        let (old_leaf, old_intermediates) = old.split_first().ok_or(Error::BadDer)?;
+        let (new_leaf, new_intermediates) = new.split_first().ok_or(Error::BadDer)?;
+        let old_leaf =
+            cert::parse_cert(untrusted::Input::from(*old_leaf), EndEntityOrCa::EndEntity)?;
+        let new_leaf =
+            cert::parse_cert(untrusted::Input::from(*new_leaf), EndEntityOrCa::EndEntity)?;
+
+        let old_names = subject_name::presented_names(&old_leaf)?;
+        let new_names = subject_name::presented_names(&new_leaf)?;
+        let difference = |names: &[PresentedName<'a>], others: &[PresentedName<'a>]| -> Vec<_> {
+            names
+                .iter()
+                .filter(|name| !others.contains(name))
+                .copied()
+                .collect()
+        };
+
+        Ok(Self {
+            subject_changed: old_leaf.subject() != new_leaf.subject(),
+            issuer_changed: old_leaf.issuer() != new_leaf.issuer(),
+            key_rotated: old_leaf.spki_der() != new_leaf.spki_der(),
+            intermediates_changed: old_intermediates != new_intermediates,
+            names_added: difference(&new_names, &old_names),
+            names_removed: difference(&old_names, &new_names),
+        })
+    }
+}
+
+/// The differences between two sets of trust anchors, e.g. two releases of
+/// a root program, for reviewing the change.
+///
+/// Anchors are identified by their `subject` and `spki`, compared byte for
+/// byte; their name constraints aren't compared.
+///
+/// Requires the `alloc` feature.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AnchorDiff<'a> {
+    /// The new anchors whose subject isn't that of any old anchor, in their
+    /// order in the new set.
+    pub added: Vec<TrustAnchor<'a>>,
+
+    /// The old anchors whose subject isn't that of any new anchor, in their
+    /// order in the old set.
+    pub removed: Vec<TrustAnchor<'a>>,
+
+    /// The old and new anchors with the same subject but different keys,
+    /// e.g. a root CA that was rekeyed without being renamed, in the order
+    /// of the old set.
+    pub rekeyed: Vec<(TrustAnchor<'a>, TrustAnchor<'a>)>,
+}
+
+impl<'a> AnchorDiff<'a> {
+    /// Compares the sets of trust anchors `old` and `new`.
+    ///
+    /// An old and a new anchor with the same subject but different keys are
+    /// reported in `rekeyed`, each paired with at most one other anchor,
+    /// rather than in both `removed` and `added`.
+    pub fn new(old: &[TrustAnchor<'a>], new: &[TrustAnchor<'a>]) -> Self {
+        let same = |a: &TrustAnchor, b: &TrustAnchor| a.subject == b.subject && a.spki == b.spki;
+        let difference = |anchors: &[TrustAnchor<'a>], others: &[TrustAnchor<'a>]| -> Vec<_> {
+            anchors
+                .iter()
+                .filter(|anchor| !others.iter().any(|other| same(anchor, other)))
+                .copied()
+                .collect()
+        };
+
+        let mut added = difference(new, old);
+        let mut removed = difference(old, new);
+        let mut rekeyed = Vec::new();
+        removed.retain(|old_anchor| {
+            match added
+                .iter()
+                .position(|new_anchor| new_anchor.subject == old_anchor.subject)
+            {
+                Some(index) => {
+                    rekeyed.push((*old_anchor, added.remove(index)));
+                    false
+                }
+                None => true,
+            }
+        });
+
+        Self {
+            added,
+            removed,
+            rekeyed,
+        }
+    }
+}
+
+/// A summary of a set of trust anchors, for auditing it.
+///
+/// Requires the `alloc` feature.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AnchorStoreSummary<'a> {
+    /// The number of anchors with each kind of public key, in the order in
+    /// which each kind first appears.
+    pub key_kinds: Vec<(KeyKind, usize)>,
+
+    /// The anchors that have expired or will expire soon, with the notAfter
+    /// time of their certificates, soonest first.
+    pub expiring: Vec<(TrustAnchor<'a>, Time)>,
+}
+
+impl<'a> AnchorStoreSummary<'a> {
+    /// Summarizes the trust anchors whose DER-encoded certificates are
+    /// `anchor_certs`, as for `TrustAnchor::try_from_cert_der`.
+    ///
+    /// An anchor expires soon if its certificate's notAfter time is at most
+    /// `expiring_within_secs` seconds after `time`. Although a trust
+    /// anchor's validity period isn't checked when verifying a path, an
+    /// expiring root certificate is usually due to be replaced.
+    ///
+    /// v1 certificates are accepted, as they are by
+    /// `TrustAnchor::try_from_cert_der`. Fails with the error from parsing a
+    /// certificate.
+    pub fn new(
+        anchor_certs: &[&'a [u8]],
+        time: Time,
+        expiring_within_secs: u64,
+    ) -> Result<Self, Error> {
+        let expiring_before = Time::from_seconds_since_unix_epoch(
+            time.as_seconds_since_unix_epoch()
+                .saturating_add(expiring_within_secs),
+        );
+
+        let mut key_kinds: Vec<(KeyKind, usize)> = Vec::new();
+        let mut expiring = Vec::new();
+        for cert_der in anchor_certs {
+            let cert = cert::parse_cert_internal(
+                untrusted::Input::from(*cert_der),
+                EndEntityOrCa::EndEntity,
+                V1Certs::Accept,
+            )?;
+
+            let key_kind = signed_data::public_key_kind(cert.spki.value());
+            match key_kinds.iter_mut().find(|(kind, _)| *kind == key_kind) {
+                Some((_, count)) => *count += 1,
+                None => key_kinds.push((key_kind, 1)),
+            }
+
+            let not_after = cert.validity.read_all(Error::BadDer, |value| {
+                let _not_before = der::time_choice(value)?;
+                der::time_choice(value)
+            })?;
+            if not_after <= expiring_before {
+                expiring.push((TrustAnchor::from(cert), not_after));
+            }
+        }
+        expiring.sort_by_key(|(_, not_after)| not_after.as_seconds_since_unix_epoch());
+
+        Ok(Self {
+            key_kinds,
+            expiring,
+        })
+    }
+}
@@ -13,18 +13,34 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use crate::{
-    cert, signed_data, subject_name, verify_cert, Error, SignatureAlgorithm, SubjectNameRef, Time,
-    TlsClientTrustAnchors, TlsServerTrustAnchors,
+    cert, chain, dane, der, devid, matter, sct, signed_data, subject_name, verify_cert, DnsNameRef,
+    Error, IpAddressInDnsName, KeyKind, KeyPurposeId, MatterIdentity, SignatureAlgorithm,
+    SubjectNameRef, Time, TrustAnchor, TsaTrustAnchors, Usage, VerificationDiagnostics,
+    VerificationOptions,
+};
+use core::{cell::Cell, convert::TryFrom};
+use ring::digest;
+
+#[cfg(feature = "tls-server-verify")]
+use crate::{pin, CtPolicy, SpkiPin, TlsServerTrustAnchors};
+
+#[cfg(feature = "tls-client-verify")]
+use crate::TlsClientTrustAnchors;
+
+#[cfg(feature = "alloc")]
+use {
+    crate::{fallible, trace, verify_cert::PathError},
+    alloc::vec::Vec,
+    core::cell::RefCell,
 };
-use core::convert::TryFrom;
 
 /// An end-entity certificate.
 ///
 /// Server certificate processing in a TLS connection consists of several
 /// steps. All of these steps are necessary:
 ///
-/// * `EndEntityCert.verify_is_valid_tls_server_cert`: Verify that the server's
-///   certificate is currently valid *for use by a TLS server*.
+/// * `EndEntityCert.verify_for_usage` with `Usage::TlsServer`: Verify that
+///   the server's certificate is currently valid *for use by a TLS server*.
 /// * `EndEntityCert.verify_is_valid_for_subject_name`: Verify that the server's
 ///   certificate is valid for the host or IP address that is being connected to.
 ///
@@ -34,8 +50,8 @@ use core::convert::TryFrom;
 /// Client certificate processing in a TLS connection consists of analogous
 /// steps. All of these steps are necessary:
 ///
-/// * `EndEntityCert.verify_is_valid_tls_client_cert`: Verify that the client's
-///   certificate is currently valid *for use by a TLS client*.
+/// * `EndEntityCert.verify_for_usage` with `Usage::TlsClient`: Verify that
+///   the client's certificate is currently valid *for use by a TLS client*.
 /// * `EndEntityCert.verify_signature`: Verify that the client's signature in
 ///   its `CertificateVerify` message is valid using the public key from the
 ///   client's certificate.
@@ -49,6 +65,7 @@ use core::convert::TryFrom;
 /// deterministic, so if these tasks are done in multiple threads, it is
 /// probably best to just call `EndEntityCert::from` multiple times (before each
 /// operation) for the same DER-encoded ASN.1 certificate bytes.
+#[derive(Clone, Debug)]
 pub struct EndEntityCert<'a> {
     inner: cert::Cert<'a>,
 }
@@ -68,21 +85,273 @@ impl<'a> TryFrom<&'a [u8]> for EndEntityCert<'a> {
     }
 }
 
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+impl<'a> TryFrom<&'a Vec<u8>> for EndEntityCert<'a> {
+    type Error = Error;
+
+    /// Parse the ASN.1 DER-encoded X.509 encoding of the certificate
+    /// `cert_der`.
+    fn try_from(cert_der: &'a Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(cert_der.as_slice())
+    }
+}
+
+/// The results of verifying a certificate under the options in force and
+/// under a proposed policy; see
+/// `EndEntityCert::verify_for_usage_with_shadow_options`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShadowResult {
+    /// The result under the options in force, which decides whether the
+    /// certificate is accepted.
+    pub result: Result<(), Error>,
+
+    /// The result under the shadow options.
+    pub shadow_result: Result<(), Error>,
+}
+
+impl ShadowResult {
+    /// Returns whether the results differ: whether the shadow options accept
+    /// a certificate the options in force reject, or the other way around,
+    /// or both reject it with different errors.
+    pub fn diverged(&self) -> bool {
+        self.result != self.shadow_result
+    }
+}
+
+/// An end-entity certificate that owns its DER encoding.
+///
+/// Unlike `EndEntityCert`, this does not borrow its input, so it can be
+/// stored in session state or moved between threads or tasks. Use
+/// `as_ref` to verify it.
+///
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OwnedEndEntityCert(Vec<u8>);
+
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+impl TryFrom<Vec<u8>> for OwnedEndEntityCert {
+    type Error = Error;
+
+    /// Parse the ASN.1 DER-encoded X.509 encoding of the certificate
+    /// `cert_der`, taking ownership of it.
+    fn try_from(cert_der: Vec<u8>) -> Result<Self, Self::Error> {
+        EndEntityCert::try_from(cert_der.as_slice())?;
+        Ok(Self(cert_der))
+    }
+}
+
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+impl<'a> From<&'a OwnedEndEntityCert> for EndEntityCert<'a> {
+    fn from(cert: &'a OwnedEndEntityCert) -> Self {
+        cert.as_ref()
+    }
+}
+
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+impl OwnedEndEntityCert {
+    /// Returns an `EndEntityCert` that refers to this certificate.
+    ///
+    /// The parsed certificate would borrow from `self`, so it isn't stored;
+    /// instead, each call parses the certificate again, at the cost of
+    /// `EndEntityCert::try_from`: a single pass over the encoding, without
+    /// allocating or verifying any signature. To verify the certificate
+    /// several times, call this once and keep the result.
+    pub fn as_ref(&self) -> EndEntityCert {
+        match EndEntityCert::try_from(self.0.as_slice()) {
+            Ok(cert) => cert,
+            // The encoding was parsed successfully when `self` was
+            // constructed, it can't have changed since, and parsing is
+            // deterministic.
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Returns the certificate's DER encoding.
+    pub fn der(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the certificate's DER encoding, consuming `self`.
+    pub fn into_der(self) -> Vec<u8> {
+        self.0
+    }
+}
+
 impl<'a> EndEntityCert<'a> {
     pub(super) fn inner(&self) -> &cert::Cert {
         &self.inner
     }
 
-    /// Verifies that the end-entity certificate is valid for use by a TLS
-    /// server.
+    /// Returns the parsed certificate.
+    pub fn as_cert(&self) -> &cert::Cert<'a> {
+        &self.inner
+    }
+
+    /// Returns the certificate's DER encoding, as it was parsed.
+    pub fn as_der(&self) -> &'a [u8] {
+        self.inner.der.as_slice_less_safe()
+    }
+
+    /// Constructs an `OwnedEndEntityCert` from a copy of this certificate's
+    /// DER encoding.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn to_owned_cert(&self) -> OwnedEndEntityCert {
+        OwnedEndEntityCert(self.inner.der.as_slice_less_safe().to_vec())
+    }
+
+    /// Verifies that the end-entity certificate is valid for `usage`.
     ///
     /// `supported_sig_algs` is the list of signature algorithms that are
     /// trusted for use in certificate signatures; the end-entity certificate's
     /// public key is not validated against this list. `trust_anchors` is the
-    /// list of root CAs to trust. `intermediate_certs` is the sequence of
-    /// intermediate certificates that the server sent in the TLS handshake.
-    /// `time` is the time for which the validation is effective (usually the
-    /// current time).
+    /// list of root CAs to trust for `usage`. `intermediate_certs` is the
+    /// sequence of intermediate certificates that the peer sent, e.g. in the
+    /// TLS handshake. `time` is the time for which the validation is effective
+    /// (usually the current time).
+    pub fn verify_for_usage(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        trust_anchors: &[TrustAnchor],
+        intermediate_certs: &[&[u8]],
+        time: Time,
+        usage: Usage,
+    ) -> Result<(), Error> {
+        self.verify_for_usage_with_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(supported_sig_algs, time),
+            usage,
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for `usage`, as
+    /// configured by `options`.
+    ///
+    /// `trust_anchors` and `intermediate_certs` are as for
+    /// `verify_for_usage`.
+    pub fn verify_for_usage_with_options(
+        &self,
+        trust_anchors: &[TrustAnchor],
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+        usage: Usage,
+    ) -> Result<(), Error> {
+        verify_cert::build_chain(
+            usage.eku(),
+            verify_cert::Precertificates::Reject,
+            options,
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
+            0,
+            &|_, _| Ok(()),
+        )
+    }
+
+    /// Verifies the end-entity certificate for `usage` as configured by
+    /// `options`, as `verify_for_usage_with_options` does, and also as
+    /// configured by `shadow_options`, e.g. a stricter policy being rolled
+    /// out, reporting both results so that divergences can be logged before
+    /// the new policy is enforced.
+    ///
+    /// The path found under `options` is tried first under `shadow_options`
+    /// on its own, so that when it is valid under both, as is usual, only its
+    /// certificates are parsed and checked again. Otherwise all of
+    /// `trust_anchors` and `intermediate_certs` are searched, so the shadow
+    /// result is what `verify_for_usage_with_options` returns under
+    /// `shadow_options`, except that a path may be found where a full search
+    /// would exceed one of the limits of `shadow_options` first. If
+    /// `shadow_options` has diagnostics, they are told about a full search
+    /// only, as by `verify_for_usage_with_options`.
+    pub fn verify_for_usage_with_shadow_options(
+        &self,
+        trust_anchors: &[TrustAnchor],
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+        shadow_options: &VerificationOptions,
+        usage: Usage,
+    ) -> ShadowResult {
+        let path = self.find_path(usage.eku(), trust_anchors, intermediate_certs, options);
+        let same_path_result = match path {
+            Ok((anchor, indices)) if shadow_options.diagnostics.is_none() => {
+                let chain =
+                    indices.into_chain(self.inner.der.as_slice_less_safe(), intermediate_certs);
+                self.verify_for_usage_with_options(
+                    &trust_anchors[anchor..=anchor],
+                    chain.intermediates(),
+                    shadow_options,
+                    usage,
+                )
+            }
+            _ => Err(Error::UnknownIssuer),
+        };
+        ShadowResult {
+            result: path.map(|_| ()),
+            shadow_result: same_path_result.or_else(|_| {
+                self.verify_for_usage_with_options(
+                    trust_anchors,
+                    intermediate_certs,
+                    shadow_options,
+                    usage,
+                )
+            }),
+        }
+    }
+
+    /// Verifies the end-entity certificate for `usage` as configured by
+    /// `options`, as `verify_for_usage_with_options` does, also returning a
+    /// trace of the decisions made, to reproduce the verification later; see
+    /// `VerificationTrace`.
+    ///
+    /// Any `VerificationDiagnostics` in `options` is told about the
+    /// verification as usual.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn verify_for_usage_with_trace(
+        &self,
+        trust_anchors: &[TrustAnchor],
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+        usage: Usage,
+    ) -> (Result<(), Error>, trace::VerificationTrace) {
+        trace::record(self, trust_anchors, intermediate_certs, options, usage)
+    }
+
+    /// Verifies each of `certs` as by `verify_for_usage_with_options`, with
+    /// the same trust anchors, intermediates and options, e.g. to revalidate
+    /// cached client certificates after the trust configuration changes.
+    ///
+    /// The results are yielded in the order of `certs`, as each certificate
+    /// is verified; an error for one certificate doesn't stop the others
+    /// from being verified.
+    pub fn verify_all_for_usage_with_options<'v>(
+        certs: &'v [EndEntityCert<'v>],
+        trust_anchors: &'v [TrustAnchor<'v>],
+        intermediate_certs: &'v [&'v [u8]],
+        options: &'v VerificationOptions<'v>,
+        usage: Usage,
+    ) -> impl Iterator<Item = Result<(), Error>> + 'v {
+        certs.iter().map(move |cert| {
+            cert.verify_for_usage_with_options(trust_anchors, intermediate_certs, options, usage)
+        })
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server.
+    ///
+    /// Equivalent to `verify_for_usage` with `Usage::TlsServer`.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    #[deprecated(note = "Use `verify_for_usage` with `Usage::TlsServer`")]
     pub fn verify_is_valid_tls_server_cert(
         &self,
         supported_sig_algs: &[&SignatureAlgorithm],
@@ -90,31 +359,502 @@ impl<'a> EndEntityCert<'a> {
         intermediate_certs: &[&[u8]],
         time: Time,
     ) -> Result<(), Error> {
+        self.verify_for_usage(
+            supported_sig_algs,
+            trust_anchors,
+            intermediate_certs,
+            time,
+            Usage::TlsServer,
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server, returning the verified chain.
+    ///
+    /// The arguments are the same as for `verify_for_usage`, without the
+    /// usage. The returned chain holds this certificate followed by the
+    /// intermediate certificates actually used, in order, which is what a
+    /// TLS `Certificate` message or an EST `/cacerts` response needs;
+    /// unused or duplicate entries of `intermediate_certs` are left out.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_tls_server_cert_chain<'i>(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        trust_anchors: &TlsServerTrustAnchors,
+        intermediate_certs: &[&'i [u8]],
+        time: Time,
+    ) -> Result<chain::CertChain<'i>, Error>
+    where
+        'a: 'i,
+    {
+        self.verify_tls_server_cert_chain_with_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(supported_sig_algs, time),
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server, as configured by `options`, returning the verified chain.
+    ///
+    /// This is `verify_tls_server_cert_chain` with `options` in place of the
+    /// signature algorithms and time.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_tls_server_cert_chain_with_options<'i>(
+        &self,
+        &TlsServerTrustAnchors(trust_anchors): &TlsServerTrustAnchors,
+        intermediate_certs: &[&'i [u8]],
+        options: &VerificationOptions,
+    ) -> Result<chain::CertChain<'i>, Error>
+    where
+        'a: 'i,
+    {
+        let (_, path) = self.find_path(
+            verify_cert::EKU_SERVER_AUTH,
+            trust_anchors,
+            intermediate_certs,
+            options,
+        )?;
+        Ok(path.into_chain(self.inner.der.as_slice_less_safe(), intermediate_certs))
+    }
+
+    /// Builds a path from the end-entity certificate to one of
+    /// `trust_anchors` that is valid for `eku`, returning it.
+    ///
+    /// This is the path building stage of `verify_for_usage`, for composing
+    /// custom verifiers: each certificate on the path is checked for
+    /// validity at `options.time`, basic constraints, the extended key usage
+    /// `eku`, name constraints and signatures. Names and any other policy
+    /// are left to the caller, who can inspect the returned chain and the
+    /// trust anchor it ends at.
+    pub fn build_path<'i>(
+        &self,
+        eku: KeyPurposeId,
+        trust_anchors: &[TrustAnchor<'i>],
+        intermediate_certs: &[&'i [u8]],
+        options: &VerificationOptions,
+    ) -> Result<chain::VerifiedChain<'i>, Error>
+    where
+        'a: 'i,
+    {
+        let (anchor, path) = self.find_path(eku, trust_anchors, intermediate_certs, options)?;
+        Ok(path.into_verified_chain(
+            trust_anchors[anchor],
+            self.inner.der.as_slice_less_safe(),
+            intermediate_certs,
+        ))
+    }
+
+    /// Builds every path from the end-entity certificate to one of
+    /// `trust_anchors` that is valid for `eku`, as `build_path` builds the
+    /// first one, stopping after `max_paths` of them.
+    ///
+    /// This is for PKI analysis rather than verification, e.g. to find out
+    /// which cross-signed intermediates a root is needed for before
+    /// distrusting it. Paths are returned in the order they are found, which
+    /// is the order of `trust_anchors` and `intermediate_certs`, with each
+    /// certificate's issuers among the trust anchors tried before those among
+    /// the intermediates. If there is no valid path, this fails with the
+    /// error `build_path` would. Going on to the next path after one is found
+    /// isn't reported to `VerificationDiagnostics::issuer_rejected`.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn enumerate_valid_paths<'i>(
+        &self,
+        eku: KeyPurposeId,
+        trust_anchors: &[TrustAnchor<'i>],
+        intermediate_certs: &[&'i [u8]],
+        options: &VerificationOptions,
+        max_paths: usize,
+    ) -> Result<Vec<chain::VerifiedChain<'i>>, Error>
+    where
+        'a: 'i,
+    {
+        if max_paths == 0 {
+            return Ok(Vec::new());
+        }
+        let paths = RefCell::new(Vec::new());
+        let result = verify_cert::build_chains(
+            eku,
+            verify_cert::Precertificates::Reject,
+            options,
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
+            0,
+            &|trust_anchor, cert_path| {
+                let anchor = trust_anchors
+                    .iter()
+                    .position(|candidate| core::ptr::eq(candidate, trust_anchor))
+                    .ok_or(Error::UnknownIssuer)?;
+                let indices = chain::PathIndices::new(cert_path, intermediate_certs)
+                    .ok_or(Error::UnknownIssuer)?;
+                let mut paths = paths.borrow_mut();
+                fallible::push(
+                    &mut paths,
+                    indices.into_verified_chain(
+                        trust_anchors[anchor],
+                        self.inner.der.as_slice_less_safe(),
+                        intermediate_certs,
+                    ),
+                )?;
+                // Pass over the path, so that path building goes on to the
+                // next one, until there are enough.
+                if paths.len() < max_paths {
+                    return Err(PathError::Continue);
+                }
+                Ok(())
+            },
+        );
+        let paths = paths.into_inner();
+        match result {
+            Err(PathError::Invalid(Error::OutOfMemory)) => Err(Error::OutOfMemory),
+            Err(PathError::Invalid(err)) if paths.is_empty() => Err(err),
+            _ => Ok(paths),
+        }
+    }
+
+    // Returns the position in `trust_anchors` of the trust anchor that the
+    // path found ends at, with the path.
+    fn find_path(
+        &self,
+        eku: KeyPurposeId,
+        trust_anchors: &[TrustAnchor],
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+    ) -> Result<(usize, chain::PathIndices), Error> {
+        let path = Cell::new(None);
         verify_cert::build_chain(
+            eku,
+            verify_cert::Precertificates::Reject,
+            options,
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
+            0,
+            &|trust_anchor, cert_path| {
+                let anchor = trust_anchors
+                    .iter()
+                    .position(|candidate| core::ptr::eq(candidate, trust_anchor))
+                    .ok_or(Error::UnknownIssuer)?;
+                let indices = chain::PathIndices::new(cert_path, intermediate_certs)
+                    .ok_or(Error::UnknownIssuer)?;
+                path.set(Some((anchor, indices)));
+                Ok(())
+            },
+        )?;
+        path.get().ok_or(Error::UnknownIssuer)
+    }
+
+    /// Checks that `time` is within the end-entity certificate's validity
+    /// period.
+    ///
+    /// This fails with `Error::CertNotValidYet` or `Error::CertExpired` if it
+    /// is not. Only this certificate is checked; see `build_path`.
+    pub fn check_validity(&self, time: Time) -> Result<(), Error> {
+        self.inner.validity.read_all(Error::BadDer, |value| {
+            verify_cert::check_validity(value, time)
+        })
+    }
+
+    /// Checks that the end-entity certificate's extended key usage extension,
+    /// if it has one, includes `eku`.
+    ///
+    /// This fails with `Error::RequiredEkuNotFound` if it does not. Only this
+    /// certificate is checked; see `build_path`.
+    pub fn check_eku(&self, eku: KeyPurposeId) -> Result<(), Error> {
+        untrusted::read_all_optional(self.inner.eku, Error::BadDer, |value| {
+            verify_cert::check_eku(value, eku)
+        })
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server, and that it satisfies the Certificate Transparency policy
+    /// `ct_policy`.
+    ///
+    /// The arguments are otherwise the same as for `verify_for_usage`, with
+    /// `Usage::TlsServer`. `ct_policy` is consulted for each
+    /// candidate path once the path has been found to chain to one of
+    /// `trust_anchors`. If valid paths exist but `ct_policy` rejects all of
+    /// them, this fails with the error it last returned, usually
+    /// `Error::CtPolicyNotSatisfied`.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_is_valid_tls_server_cert_with_ct_policy(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        trust_anchors: &TlsServerTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        time: Time,
+        ct_policy: &dyn CtPolicy,
+    ) -> Result<(), Error> {
+        self.verify_is_valid_tls_server_cert_with_ct_policy_and_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(supported_sig_algs, time),
+            ct_policy,
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server, as configured by `options`, and that it satisfies the
+    /// Certificate Transparency policy `ct_policy`.
+    ///
+    /// This is `verify_is_valid_tls_server_cert_with_ct_policy` with
+    /// `options` in place of the signature algorithms and time; `ct_policy`
+    /// is given `options`' time.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_is_valid_tls_server_cert_with_ct_policy_and_options(
+        &self,
+        &TlsServerTrustAnchors(trust_anchors): &TlsServerTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+        ct_policy: &dyn CtPolicy,
+    ) -> Result<(), Error> {
+        let ct_policy_error = Cell::new(None);
+        let result = verify_cert::build_chain(
             verify_cert::EKU_SERVER_AUTH,
-            supported_sig_algs,
+            verify_cert::Precertificates::Reject,
+            options,
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
+            0,
+            &|trust_anchor, path| {
+                let issuer_spki = verify_cert::leaf_issuer_spki(trust_anchor, path);
+                ct_policy
+                    .check(self, issuer_spki.as_slice_less_safe(), options.time)
+                    .map_err(|err| {
+                        ct_policy_error.set(Some(err));
+                        err
+                    })
+            },
+        );
+        match (result, ct_policy_error.get()) {
+            (Err(Error::UnknownIssuer), Some(err)) | (Err(Error::PathLoopDetected), Some(err)) => {
+                Err(err)
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server, and that at least one certificate on the path to the trust
+    /// anchor, or the trust anchor itself, matches one of `pins`.
+    ///
+    /// The arguments are otherwise the same as for `verify_for_usage`, with
+    /// `Usage::TlsServer`. Pins are checked against each
+    /// candidate path, so a pinned path is found even if an unpinned one is
+    /// also valid. If valid paths exist but none matches a pin, this fails
+    /// with `Error::PinMismatch`.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_is_valid_tls_server_cert_with_pins(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        trust_anchors: &TlsServerTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        time: Time,
+        pins: &[SpkiPin],
+    ) -> Result<(), Error> {
+        self.verify_is_valid_tls_server_cert_with_pins_and_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(supported_sig_algs, time),
+            pins,
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by a TLS
+    /// server, as configured by `options`, and that its path matches one of
+    /// `pins`.
+    ///
+    /// This is `verify_is_valid_tls_server_cert_with_pins` with `options` in
+    /// place of the signature algorithms and time.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_is_valid_tls_server_cert_with_pins_and_options(
+        &self,
+        &TlsServerTrustAnchors(trust_anchors): &TlsServerTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+        pins: &[SpkiPin],
+    ) -> Result<(), Error> {
+        let found_unpinned_path = Cell::new(false);
+        let result = verify_cert::build_chain(
+            verify_cert::EKU_SERVER_AUTH,
+            verify_cert::Precertificates::Reject,
+            options,
             trust_anchors,
             intermediate_certs,
             &self.inner,
-            time,
             0,
+            &|trust_anchor, path| match pin::path_matches_pins(pins, trust_anchor, path) {
+                true => Ok(()),
+                false => {
+                    found_unpinned_path.set(true);
+                    Err(Error::PinMismatch)
+                }
+            },
+        );
+        match result {
+            Err(Error::UnknownIssuer) | Err(Error::PathLoopDetected)
+                if found_unpinned_path.get() =>
+            {
+                Err(Error::PinMismatch)
+            }
+            result => result,
+        }
+    }
+
+    /// Verifies a Certificate Transparency precertificate as if it were a
+    /// certificate for use by a TLS server.
+    ///
+    /// This is for CT monitors and logs, which need to check that a
+    /// precertificate was issued by a trusted CA. Unlike the other
+    /// verification functions, this accepts an end-entity certificate
+    /// containing the CT poison extension; certificates without it are
+    /// verified as usual. The arguments are the same as for
+    /// `verify_for_usage`, with `Usage::TlsServer`.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_is_valid_tls_server_precert(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        trust_anchors: &TlsServerTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        time: Time,
+    ) -> Result<(), Error> {
+        self.verify_is_valid_tls_server_precert_with_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(supported_sig_algs, time),
         )
     }
 
+    /// Verifies a Certificate Transparency precertificate as if it were a
+    /// certificate for use by a TLS server, as configured by `options`.
+    ///
+    /// This is `verify_is_valid_tls_server_precert` with `options` in place
+    /// of the signature algorithms and time.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn verify_is_valid_tls_server_precert_with_options(
+        &self,
+        &TlsServerTrustAnchors(trust_anchors): &TlsServerTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+    ) -> Result<(), Error> {
+        verify_cert::build_chain(
+            verify_cert::EKU_SERVER_AUTH,
+            verify_cert::Precertificates::Accept,
+            options,
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
+            0,
+            &|_, _| Ok(()),
+        )
+    }
+
+    /// Verifies that the end-entity certificate is a valid Matter node
+    /// operational certificate (NOC), returning its Matter identity.
+    ///
+    /// This verifies the path as `verify_for_usage` does for
+    /// `Usage::TlsClient`, using ECDSA P-256 with SHA-256, the only signature
+    /// algorithm Matter allows. In addition, the NOC must have exactly one
+    /// node ID and fabric ID, the intermediate CA certificate (if any) an
+    /// ICAC ID, and the trust anchor an RCAC ID; the fabric IDs of the CAs,
+    /// if present, must match that of the NOC. Certificates that do not follow this profile fail
+    /// with `Error::InvalidMatterCert`.
+    ///
+    /// Requires the `tls-client-verify` feature.
+    #[cfg(feature = "tls-client-verify")]
+    pub fn verify_is_valid_matter_noc(
+        &self,
+        trust_anchors: &TlsClientTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        time: Time,
+    ) -> Result<MatterIdentity, Error> {
+        self.verify_is_valid_matter_noc_with_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(matter::SIGNATURE_ALGORITHMS, time),
+        )
+    }
+
+    /// Verifies that the end-entity certificate is a valid Matter node
+    /// operational certificate (NOC), as configured by `options`, returning
+    /// its Matter identity.
+    ///
+    /// This is `verify_is_valid_matter_noc` with `options` in place of the
+    /// time. The signature algorithms of `options` are ignored: only ECDSA
+    /// P-256 with SHA-256 is supported, as Matter requires.
+    ///
+    /// Requires the `tls-client-verify` feature.
+    #[cfg(feature = "tls-client-verify")]
+    pub fn verify_is_valid_matter_noc_with_options(
+        &self,
+        &TlsClientTrustAnchors(trust_anchors): &TlsClientTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+    ) -> Result<MatterIdentity, Error> {
+        let noc = matter::check_noc(&self.inner)?;
+        let found_invalid_path = Cell::new(false);
+        let result = verify_cert::build_chain(
+            verify_cert::EKU_CLIENT_AUTH,
+            verify_cert::Precertificates::Reject,
+            &VerificationOptions {
+                supported_sig_algs: matter::SIGNATURE_ALGORITHMS,
+                ..*options
+            },
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
+            0,
+            &|trust_anchor, path| {
+                matter::check_issuers(&noc, trust_anchor, path).map_err(|err| {
+                    found_invalid_path.set(true);
+                    err
+                })
+            },
+        );
+        match result {
+            Ok(()) => Ok(noc),
+            Err(Error::UnknownIssuer) | Err(Error::PathLoopDetected)
+                if found_invalid_path.get() =>
+            {
+                Err(Error::InvalidMatterCert)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the Matter-specific attributes of the certificate's subject.
+    pub fn matter_identity(&self) -> Result<MatterIdentity, Error> {
+        matter::matter_identity(self.inner.subject)
+    }
+
     /// Verifies that the end-entity certificate is valid for use by a TLS
     /// client.
     ///
-    /// If the certificate is not valid for any of the given names then this
-    /// fails with `Error::CertNotValidForName`.
+    /// Equivalent to `verify_for_usage` with `Usage::TlsClient`.
     ///
-    /// `supported_sig_algs` is the list of signature algorithms that are
-    /// trusted for use in certificate signatures; the end-entity certificate's
-    /// public key is not validated against this list. `trust_anchors` is the
-    /// list of root CAs to trust. `intermediate_certs` is the sequence of
-    /// intermediate certificates that the client sent in the TLS handshake.
-    /// `cert` is the purported end-entity certificate of the client. `time` is
-    /// the time for which the validation is effective (usually the current
-    /// time).
+    /// Requires the `tls-client-verify` feature.
+    #[cfg(feature = "tls-client-verify")]
+    #[deprecated(note = "Use `verify_for_usage` with `Usage::TlsClient`")]
     pub fn verify_is_valid_tls_client_cert(
         &self,
         supported_sig_algs: &[&SignatureAlgorithm],
@@ -122,23 +862,308 @@ impl<'a> EndEntityCert<'a> {
         intermediate_certs: &[&[u8]],
         time: Time,
     ) -> Result<(), Error> {
-        verify_cert::build_chain(
+        self.verify_for_usage(
+            supported_sig_algs,
+            trust_anchors,
+            intermediate_certs,
+            time,
+            Usage::TlsClient,
+        )
+    }
+
+    /// Returns the value (without tag and length) of the certificate's
+    /// `serialNumber` INTEGER; see `Cert::serial`.
+    pub fn serial(&self) -> &'a [u8] {
+        self.inner.serial()
+    }
+
+    /// Returns the certificate's serial number formatted as uppercase
+    /// hexadecimal; see `SerialHex`.
+    pub fn serial_hex(&self) -> cert::SerialHex<'a> {
+        self.inner.serial_hex()
+    }
+
+    /// Returns the SHA-256 digest of the certificate's DER encoding.
+    pub fn sha256_fingerprint(&self) -> [u8; 32] {
+        sha256(self.inner.der)
+    }
+
+    /// Returns the SHA-256 digest of the certificate's DER-encoded
+    /// subjectPublicKeyInfo, as used for public key pinning.
+    pub fn spki_sha256(&self) -> [u8; 32] {
+        sha256(self.inner.spki_der)
+    }
+
+    /// Returns the certificate's identity, given the DER-encoded
+    /// subjectPublicKeyInfo of its issuer; see `CertId::new`.
+    pub fn cert_id(&self, issuer_spki_der: &[u8]) -> cert::CertId<'a> {
+        cert::CertId::new(&self.inner, issuer_spki_der)
+    }
+
+    /// Returns the kind of the certificate's public key, e.g. for choosing
+    /// the signature schemes it can produce a TLS CertificateVerify with.
+    ///
+    /// Keys of algorithms this crate doesn't support are `KeyKind::Other`.
+    pub fn key_kind(&self) -> KeyKind {
+        signed_data::public_key_kind(self.inner.spki.value())
+    }
+
+    /// Returns the size in bits of the certificate's public key: the size of
+    /// the modulus of an RSA key, or of the curve of an elliptic curve key.
+    /// Returns `None` if the key's algorithm isn't supported.
+    pub fn key_bits(&self) -> Option<usize> {
+        signed_data::public_key_bits(self.inner.spki.value())
+    }
+
+    /// Verifies that the end-entity certificate is a proxy certificate
+    /// ([RFC 3820]) valid for use by a TLS client.
+    ///
+    /// `proxy_issuers` is the proxy chain above this certificate: its issuer,
+    /// then that certificate's issuer, and so on, ending with the end-entity
+    /// certificate the proxies were derived from. That certificate is then
+    /// verified as by `verify_for_usage` with `Usage::TlsClient`, using the
+    /// remaining arguments. If `proxy_issuers` is empty, this certificate must not be a
+    /// proxy certificate, and is verified as usual.
+    ///
+    /// Requires the `proxy-certs` and `tls-client-verify` features.
+    ///
+    /// [RFC 3820]: https://tools.ietf.org/html/rfc3820
+    #[cfg(all(feature = "proxy-certs", feature = "tls-client-verify"))]
+    pub fn verify_is_valid_tls_client_proxy_cert(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        &TlsClientTrustAnchors(trust_anchors): &TlsClientTrustAnchors,
+        proxy_issuers: &[&[u8]],
+        intermediate_certs: &[&[u8]],
+        time: Time,
+    ) -> Result<(), Error> {
+        crate::proxy::verify_proxy_chain(
             verify_cert::EKU_CLIENT_AUTH,
             supported_sig_algs,
             trust_anchors,
+            proxy_issuers,
             intermediate_certs,
             &self.inner,
             time,
+        )
+    }
+
+    /// Returns the IEEE 802.1AR hardwareModuleName from the certificate's
+    /// subjectAltName extension, if it has one.
+    pub fn hardware_module_name(&self) -> Result<Option<devid::HardwareModuleName<'a>>, Error> {
+        devid::hardware_module_name(&self.inner)
+    }
+
+    /// Returns true if the certificate has no well-defined expiration date,
+    /// as is usual for IEEE 802.1AR initial DevID certificates.
+    ///
+    /// Such certificates are still verified against their notAfter time of
+    /// 99991231235959Z like any other.
+    pub fn has_no_well_defined_expiration(&self) -> bool {
+        devid::has_no_well_defined_expiration(&self.inner)
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by an
+    /// RFC 3161 time-stamping authority (TSA).
+    ///
+    /// The extended key usage extension must be critical and contain only
+    /// id-kp-timeStamping; otherwise this fails with `Error::InvalidTsaCert`.
+    /// The other arguments are as for `verify_for_usage`.
+    ///
+    /// To verify a TSA certificate, or the certificate of a signer, as of the
+    /// time a trusted time-stamp token was issued, pass its `genTime`, parsed
+    /// with `Time::from_generalized_time`, as `time`.
+    pub fn verify_is_valid_tsa_cert(
+        &self,
+        supported_sig_algs: &[&SignatureAlgorithm],
+        trust_anchors: &TsaTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        time: Time,
+    ) -> Result<(), Error> {
+        self.verify_is_valid_tsa_cert_with_options(
+            trust_anchors,
+            intermediate_certs,
+            &VerificationOptions::new(supported_sig_algs, time),
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for use by an
+    /// RFC 3161 time-stamping authority (TSA), as configured by `options`.
+    ///
+    /// This is `verify_is_valid_tsa_cert` with `options` in place of the
+    /// signature algorithms and time.
+    pub fn verify_is_valid_tsa_cert_with_options(
+        &self,
+        &TsaTrustAnchors(trust_anchors): &TsaTrustAnchors,
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+    ) -> Result<(), Error> {
+        verify_cert::check_tsa_eku(&self.inner)?;
+        verify_cert::build_chain(
+            verify_cert::EKU_TIME_STAMPING,
+            verify_cert::Precertificates::Reject,
+            options,
+            trust_anchors,
+            intermediate_certs,
+            &self.inner,
             0,
+            &|_, _| Ok(()),
         )
     }
 
+    /// Computes the TLSA certificate association data for this certificate
+    /// using the given selector and matching type.
+    pub fn tlsa_association_data(
+        &self,
+        selector: dane::TlsaSelector,
+        matching_type: dane::TlsaMatchingType,
+    ) -> dane::TlsaAssociationData<'a> {
+        dane::association_data(&self.inner, selector, matching_type)
+    }
+
+    /// Returns the Signed Certificate Timestamps embedded in the certificate.
+    ///
+    /// The SCTs are parsed but not verified; see `CtPolicy`.
+    pub fn signed_certificate_timestamps(&self) -> sct::Scts<'a> {
+        sct::scts(self.inner.sct_list)
+    }
+
+    /// Returns the `TBSCertificate` with the CT poison extension removed, if
+    /// this is a precertificate.
+    ///
+    /// This is the `tbs_certificate` of the `PreCert` entry that a log signs,
+    /// needed to compute the Merkle tree leaf hash of a precertificate.
+    pub fn precert_tbs_certificate(&self) -> Option<sct::PrecertTbsCertificate<'a>> {
+        sct::precert_tbs_certificate(&self.inner, None)
+    }
+
+    /// Returns the `TBSCertificate` of the `PreCert` entry that a log signs,
+    /// given the DER-encoded certificate `issuer_cert_der` of the
+    /// precertificate's issuer, if this is a precertificate.
+    ///
+    /// If the issuer is a Precertificate Signing Certificate (see
+    /// `Cert::is_precert_signing_cert`), the logged `TBSCertificate` is that
+    /// of the final certificate, issued by the signing certificate's own
+    /// issuer: its issuer and authorityKeyIdentifier extension are replaced
+    /// with the signing certificate's issuer and authorityKeyIdentifier
+    /// extension, or the authorityKeyIdentifier extension is removed if the
+    /// signing certificate has none (RFC 6962 Section 3.2). The
+    /// `issuer_key_hash` of the entry is then that of the signing
+    /// certificate's issuer's key, too. Otherwise, this is the same as
+    /// `precert_tbs_certificate`.
+    ///
+    /// The issuer certificate is only parsed, not verified. Fails with the
+    /// error from parsing it.
+    pub fn precert_tbs_certificate_for_issuer(
+        &self,
+        issuer_cert_der: &'a [u8],
+    ) -> Result<Option<sct::PrecertTbsCertificate<'a>>, Error> {
+        // As for `TrustAnchor::try_from_cert_der`, the issuer is parsed as an
+        // end-entity certificate for lack of a reference to its child that
+        // lives long enough; it doesn't matter here.
+        let issuer = cert::parse_cert(
+            untrusted::Input::from(issuer_cert_der),
+            cert::EndEntityOrCa::EndEntity,
+        )?;
+        let precert_signing_cert = if issuer.is_precert_signing_cert() {
+            Some(&issuer)
+        } else {
+            None
+        };
+        Ok(sct::precert_tbs_certificate(
+            &self.inner,
+            precert_signing_cert,
+        ))
+    }
+
     /// Verifies that the certificate is valid for the given Subject Name.
+    ///
+    /// dNSNames that are IP addresses are ignored; see
+    /// `verify_is_valid_for_subject_name_with_policy`.
     pub fn verify_is_valid_for_subject_name(
         &self,
         subject_name: SubjectNameRef,
     ) -> Result<(), Error> {
-        subject_name::verify_cert_subject_name(self, subject_name)
+        self.verify_is_valid_for_subject_name_with_policy(
+            subject_name,
+            IpAddressInDnsName::default(),
+        )
+    }
+
+    /// Verifies that the certificate is valid for the given Subject Name,
+    /// like `verify_is_valid_for_subject_name`, handling IP addresses in
+    /// dNSNames as `ip_address_in_dns_name` says.
+    pub fn verify_is_valid_for_subject_name_with_policy(
+        &self,
+        subject_name: SubjectNameRef,
+        ip_address_in_dns_name: IpAddressInDnsName,
+    ) -> Result<(), Error> {
+        subject_name::verify_cert_subject_name(self, subject_name, ip_address_in_dns_name)
+    }
+
+    /// Returns whether the certificate is valid for `dns_name`, exactly as
+    /// `verify_is_valid_for_subject_name` decides it.
+    ///
+    /// Nothing else is checked; not the certificate's validity period, nor
+    /// its path to a trust anchor. This is meant for servers selecting which
+    /// of their own certificates to present for a ClientHello's server name
+    /// indication.
+    pub fn covers_dns_name(&self, dns_name: DnsNameRef) -> bool {
+        self.verify_is_valid_for_subject_name(dns_name.into())
+            .is_ok()
+    }
+
+    /// Returns a `DnsNameMatcher` for the DNS names the certificate is valid
+    /// for, which answers `covers_dns_name` without parsing the certificate's
+    /// subjectAltName extension again for every name.
+    ///
+    /// Fails if the subjectAltName extension is malformed, or if it has a
+    /// dNSName that `verify_is_valid_for_subject_name` would reject the
+    /// certificate for; see `Error::InvalidDnsNameInSubjectAltName`.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn dns_name_matcher(&self) -> Result<subject_name::DnsNameMatcher<'a>, Error> {
+        subject_name::DnsNameMatcher::new(&self.inner)
+    }
+
+    /// Verifies that the certificate is valid for the given Subject Name,
+    /// like `verify_is_valid_for_subject_name`.
+    ///
+    /// If it isn't, the DNS names and IP addresses that the certificate is
+    /// valid for are reported to `diagnostics`, with
+    /// `VerificationDiagnostics::name_presented`, to help explain the
+    /// failure.
+    pub fn verify_is_valid_for_subject_name_with_diagnostics(
+        &self,
+        subject_name: SubjectNameRef,
+        diagnostics: &dyn VerificationDiagnostics,
+    ) -> Result<(), Error> {
+        let result = self.verify_is_valid_for_subject_name(subject_name);
+        if result == Err(Error::CertNotValidForName) {
+            subject_name::report_presented_names(&self.inner, diagnostics)?;
+        }
+        result
+    }
+
+    /// Verifies that the certificate's subject is `expected_subject`, the
+    /// DER encoding of an X.509 `Name`.
+    ///
+    /// This is for servers that authorize TLS clients by the subject of their
+    /// certificates. Names are compared byte for byte; unlike when matching
+    /// a certificate to its issuer, no RFC 5280 name normalization is done
+    /// (see `DistinguishedName::eq_rfc5280`). The certificate must be
+    /// verified separately, e.g. with `verify_for_usage`.
+    pub fn verify_is_valid_for_subject_dn(&self, expected_subject: &[u8]) -> Result<(), Error> {
+        let expected_subject = untrusted::Input::from(expected_subject)
+            .read_all(Error::BadDer, |input| {
+                der::expect_tag_and_get_value(input, der::Tag::Sequence)
+            })?;
+        if self.inner.subject == expected_subject {
+            Ok(())
+        } else {
+            Err(Error::CertNotValidForName)
+        }
     }
 
     /// Verifies the signature `signature` of message `msg` using the
@@ -172,6 +1197,14 @@ impl<'a> EndEntityCert<'a> {
             self.inner.spki.value(),
             untrusted::Input::from(msg),
             untrusted::Input::from(signature),
+            signed_data::EcdsaSignatures::Der,
+            signed_data::AlgorithmParameters::Strict,
         )
     }
 }
+
+fn sha256(input: untrusted::Input) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(digest::digest(&digest::SHA256, input.as_slice_less_safe()).as_ref());
+    out
+}
@@ -12,21 +12,35 @@
 // ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
+//! The error type.
+
 use core::fmt;
 
 /// An error that occurs during certificate validation or name validation.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Error {
+    /// A valid path to a trust anchor was found, but only to trust anchors
+    /// that `VerificationOptions::with_anchor_dns_suffixes` restricts to DNS
+    /// suffixes the name being verified isn't under.
+    AnchorNotTrustedForName,
+
     /// The encoding of some ASN.1 DER-encoded item is invalid.
     BadDer,
 
     /// The encoding of an ASN.1 DER-encoded time is invalid.
     BadDerTime,
 
+    /// The embedded Signed Certificate Timestamp list is malformed.
+    BadSctList,
+
     /// A CA certificate is being used as an end-entity certificate.
     CaUsedAsEndEntity,
 
+    /// The certificate, or one on its path, matches an entry of the
+    /// verification's blocklist.
+    CertBlocked,
+
     /// The certificate is expired; i.e. the time it is being validated for is
     /// later than the certificate's notAfter time.
     CertExpired,
@@ -34,40 +48,181 @@ pub enum Error {
     /// The certificate is not valid for the name it is being validated for.
     CertNotValidForName,
 
+    /// The certificate was revoked, as of the time it is being validated
+    /// for, according to the `RevocationStatusProvider` of the verification.
+    CertRevoked,
+
     /// The certificate is not valid yet; i.e. the time it is being validated
     /// for is earlier than the certificate's notBefore time.
     CertNotValidYet,
 
+    /// The validity period of the end-entity certificate is longer than the
+    /// `WebPkiProfile` of the verification allows for when it was issued.
+    CertValidityTooLong,
+
+    /// The certificate's Signed Certificate Timestamps do not satisfy the
+    /// Certificate Transparency policy.
+    CtPolicyNotSatisfied,
+
     /// An end-entity certificate is being used as a CA certificate.
     EndEntityUsedAsCa,
 
     /// An X.509 extension is invalid.
     ExtensionValueInvalid,
 
+    /// A certificate, or the list of intermediate certificates, is larger
+    /// than the limit set with `VerificationOptions`.
+    InputTooLarge,
+
     /// The certificate validity period (notBefore, notAfter) is invalid; e.g.
     /// the notAfter time is earlier than the notBefore time.
     InvalidCertValidity,
 
+    /// A dNSName in the certificate's subjectAltName extension contains a
+    /// NUL or another byte that can't appear in a DNS name. The certificate
+    /// is rejected whichever names it is otherwise valid for, since software
+    /// that handles names as C strings could see a different name than this
+    /// crate does, as in a null-prefix attack.
+    InvalidDnsNameInSubjectAltName {
+        /// The position of the name among the entries of the
+        /// subjectAltName extension, starting at 0.
+        index: usize,
+
+        /// What is wrong with the name: `NonAscii`, `EmbeddedNul` or
+        /// `InvalidCharacter`.
+        reason: crate::InvalidPresentedDnsNameError,
+    },
+
+    /// A certificate does not conform to the Matter operational certificate
+    /// profile.
+    InvalidMatterCert,
+
+    /// A proxy certificate, or the certificate issuing it, violates the
+    /// requirements of RFC 3820.
+    InvalidProxyCert,
+
     /// The signature is invalid for the given public key.
     InvalidSignatureForPublicKey,
 
+    /// A time-stamping authority's certificate does not meet the requirements
+    /// of RFC 3161: its extended key usage extension must be critical, with
+    /// id-kp-timeStamping as its only purpose.
+    InvalidTsaCert,
+
+    /// A dNSName in the certificate's subjectAltName extension is an IP
+    /// address, which `IpAddressInDnsName::Reject` forbids.
+    IpAddressInDnsName,
+
+    /// An intermediate certificate has a keyUsage extension that doesn't
+    /// assert keyCertSign, so its key may not be used to sign certificates.
+    KeyCertSignNotAsserted,
+
+    /// The public key is an X25519 or X448 key, which can only be used for
+    /// key agreement, not for verifying signatures.
+    KeyTypeNotUsableForSignatures,
+
+    /// The serial number of the end-entity certificate is too short to have
+    /// the entropy that `LeafSerialPolicy::Reject` requires of it.
+    LowEntropySerial,
+
+    /// The validity period of the end-entity certificate is longer than
+    /// `VerificationOptions::with_max_leaf_validity` allows.
+    LeafValidityTooLong {
+        /// The length of the validity period, in seconds, counting both its
+        /// notBefore and its notAfter second.
+        validity_secs: u64,
+
+        /// The maximum length allowed, in seconds.
+        max_validity_secs: u64,
+    },
+
+    /// A name constraints extension contains more permitted or excluded
+    /// subtrees than `MAX_NAME_CONSTRAINT_SUBTREES`.
+    MaximumNameConstraintSubtreesExceeded,
+
+    /// The subjectAltName extension contains more names than
+    /// `MAX_SUBJECT_ALT_NAMES`.
+    MaximumSubjectAltNamesExceeded,
+
+    /// An intermediate certificate has no keyUsage extension, which
+    /// `VerificationOptions::require_ca_key_usage` requires.
+    MissingKeyUsage,
+
+    /// An intermediate certificate has no subject key identifier extension,
+    /// which `VerificationOptions::require_ca_subject_key_id` requires.
+    MissingSubjectKeyId,
+
     /// The certificate violates one or more name constraints.
     NameConstraintViolation,
 
+    /// None of the given TLSA records is satisfied by the certificate and
+    /// its path.
+    NoMatchingTlsaRecord,
+
+    /// A heap allocation failed. Only returned with the `fallible-alloc`
+    /// feature; without it, the process is aborted instead.
+    OutOfMemory,
+
     /// The certificate violates one or more path length constraints.
     PathLenConstraintViolated,
 
+    /// No path to a trust anchor was found, and at least one candidate issuer
+    /// was skipped because it would have formed a loop, e.g. through
+    /// mutually cross-certified CAs.
+    PathLoopDetected,
+
+    /// A valid path to a trust anchor was found, but no certificate on it
+    /// matches any of the required public key pins.
+    PinMismatch,
+
+    /// The certificate is a Certificate Transparency precertificate, which
+    /// carries a critical poison extension precisely so that it cannot be
+    /// used in place of the real certificate.
+    PoisonedPrecertificate,
+
+    /// A proxy certificate violates the path length constraint of a proxy
+    /// certificate issuing it.
+    ProxyPathLenConstraintViolated,
+
+    /// A certificate is signed with SHA-1, which the `WebPkiProfile` of the
+    /// verification doesn't allow for when it was issued.
+    Sha1SignatureNotAllowed,
+
     /// The algorithm in the TBSCertificate "signature" field of a certificate
     /// does not match the algorithm in the signature of the certificate.
+    ///
+    /// RFC 5280 Section 4.1.1.2 requires the two to be identical, and they are
+    /// always compared byte-for-byte, parameters included.
     SignatureAlgorithmMismatch,
 
     /// The certificate is not valid for the Extended Key Usage for which it is
     /// being validated.
+    ///
+    /// `VerificationDiagnostics::required_eku_not_found` reports the purpose
+    /// that was required and those the certificate asserts.
     RequiredEkuNotFound,
 
+    /// The public exponent of an RSA key is not allowed by
+    /// `VerificationOptions::with_rsa_exponent_policy`.
+    RsaExponentNotAllowed,
+
     /// A valid issuer for the certificate could not be found.
     UnknownIssuer,
 
+    /// A verification replayed from a `VerificationTrace` was given other
+    /// inputs or options than those it was recorded with, or didn't make the
+    /// same decisions.
+    TraceMismatch,
+
+    /// The trust anchor has expired, and
+    /// `VerificationOptions::reject_expired_anchors` doesn't allow that.
+    TrustAnchorExpired,
+
+    /// The `RevocationStatusProvider` of the verification doesn't know the
+    /// revocation status of the certificate, and `UnknownStatusPolicy::Deny`
+    /// requires it to be known.
+    UnknownRevocationStatus,
+
     /// The certificate is not a v3 X.509 certificate.
     ///
     /// This error may be also reported if the certificate version field
@@ -97,6 +252,9 @@ pub enum Error {
     /// signature algorithms given.
     UnsupportedSignatureAlgorithm,
 
+    /// A Signed Certificate Timestamp has a version other than v1.
+    UnsupportedSctVersion,
+
     /// A iPAddress name constraint was invalid:
     /// - it had a sparse network mask (ie, cannot be written in CIDR form).
     /// - it was too long or short
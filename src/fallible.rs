@@ -0,0 +1,33 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Heap allocation that, with the `fallible-alloc` feature, fails with
+//! `Error::OutOfMemory` instead of aborting the process.
+
+use crate::Error;
+use alloc::vec::Vec;
+
+// Appends `value` to `vec`.
+#[cfg(feature = "fallible-alloc")]
+pub(crate) fn push<T>(vec: &mut Vec<T>, value: T) -> Result<(), Error> {
+    vec.try_reserve(1).map_err(|_| Error::OutOfMemory)?;
+    vec.push(value);
+    Ok(())
+}
+
+#[cfg(not(feature = "fallible-alloc"))]
+pub(crate) fn push<T>(vec: &mut Vec<T>, value: T) -> Result<(), Error> {
+    vec.push(value);
+    Ok(())
+}
@@ -17,17 +17,46 @@
 //! See `EndEntityCert`'s documentation for a description of the certificate
 //! processing steps necessary for a TLS connection.
 //!
+//! Everything public is available from the crate root. The same items are
+//! also grouped by topic in the [`anchor`], [`certificate`], [`name`],
+//! [`alg`] and [`error`] modules, and the most commonly used ones in
+//! [`prelude`]. The object identifiers the crate knows about are in [`oid`].
+//!
 //! # Features
 //!
 //! | Feature | Description |
 //! | ------- | ----------- |
 //! | `alloc` | Enable features that require use of the heap. Currently all RSA signature algorithms require this feature. |
 //! | `std` | Enable features that require libstd. Implies `alloc`. |
+//! | `fallible-alloc` | Report a failed heap allocation in the functions that allocate, such as `EndEntityCert::enumerate_valid_paths`, as `Error::OutOfMemory` instead of aborting the process. Implies `alloc`, and requires Rust 1.57. |
+//! | `idna` | Enable conversion of `DnsName`s to and from Unicode, with the [`idna`](https://docs.rs/idna) crate. Implies `alloc`. |
+//! | `tls-client-verify` | Enable verification of TLS client certificates, including Matter operational certificates. Enabled by default. |
+//! | `tls-server-verify` | Enable verification of TLS server certificates, including with Certificate Transparency policies, pins and DANE. Enabled by default. |
+//! | `test-utils` | Enable the [`testing`] module, building certificates for tests at runtime. Implies `alloc`. |
+//! | `tracing` | Emit [`tracing`](https://docs.rs/tracing) spans and debug-level events for each step of path building. |
+//! | `lint` | Enable the [`lint`] module, checking certificates against issuance profiles. Implies `alloc`. |
+//! | `proxy-certs` | Enable verification of RFC 3820 proxy certificate chains. Without it, certificates with the critical proxyCertInfo extension are rejected. |
+//!
+//! # Memory and computation bounds
+//!
+//! Verification borrows everything from its inputs and never allocates, so
+//! the stack is its only memory. The exceptions are the functions returning
+//! owned values, such as `EndEntityCert::enumerate_valid_paths`, and, with
+//! the `alloc` feature, candidate issuers reordered by
+//! `VerificationOptions::with_issuer_key_order` or `with_acceptable_cas`,
+//! which are kept on the heap while they are tried. Recursion depth and the number of names and
+//! name constraints examined per certificate are bounded by the constants
+//! [`MAX_SUB_CA_COUNT`], [`MAX_SUBJECT_ALT_NAMES`] and
+//! [`MAX_NAME_CONSTRAINT_SUBTREES`]; inputs exceeding them are rejected with an
+//! error.
 
 #![doc(html_root_url = "https://briansmith.org/rustdoc/")]
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(unreachable_pub)]
 
+#[cfg(feature = "idna")]
+extern crate idna_crate as idna;
+
 #[cfg(any(test, feature = "alloc"))]
 #[cfg_attr(test, macro_use)]
 extern crate alloc;
@@ -37,36 +66,172 @@ mod der;
 
 mod calendar;
 mod cert;
+mod chain;
+mod dane;
+mod devid;
+#[cfg(feature = "alloc")]
+mod diff;
 mod end_entity;
-mod error;
+pub mod error;
+#[cfg(feature = "alloc")]
+mod fallible;
+mod limits;
+#[cfg(feature = "lint")]
+pub mod lint;
+mod matter;
+pub mod oid;
+mod options;
+mod pin;
+#[cfg(feature = "alloc")]
+mod prevalidate;
+#[cfg(feature = "proxy-certs")]
+mod proxy;
+mod revocation;
+mod sct;
 mod signed_data;
 mod subject_name;
+#[cfg(feature = "test-utils")]
+pub mod testing;
 mod time;
+#[cfg(feature = "alloc")]
+mod trace;
 mod trust_anchor;
 
 mod verify_cert;
+mod web_pki;
 
 pub use {
-    end_entity::EndEntityCert,
+    cert::{BasicConstraints, Cert, CertId, EndEntityOrCa, SerialHex},
+    chain::{CertChain, VerificationToken, VerifiedChain},
+    dane::{TlsaAssociationData, TlsaCertUsage, TlsaMatchingType, TlsaRecord, TlsaSelector},
+    devid::HardwareModuleName,
+    end_entity::{EndEntityCert, ShadowResult},
     error::Error,
+    limits::{MAX_NAME_CONSTRAINT_SUBTREES, MAX_SUBJECT_ALT_NAMES, MAX_SUB_CA_COUNT},
+    matter::MatterIdentity,
+    options::{
+        BlockedCert, CaHintPolicy, Candidate, Counter, EkuMismatch, LeafSerialPolicy,
+        RsaExponentPolicy, VerificationCounters, VerificationDiagnostics, VerificationOptions,
+        Warning,
+    },
+    pin::{InvalidPinError, SpkiPin},
+    revocation::{
+        revoked_cert_ids, BloomFilter, FilterCascade, RevocationReason, RevocationStatus,
+        RevocationStatusProvider, UnknownStatusPolicy,
+    },
+    sct::{CtPolicy, PrecertTbsCertificate, Scts, SignedCertificateTimestamp},
     signed_data::{
-        SignatureAlgorithm, ECDSA_P256_SHA256, ECDSA_P256_SHA384, ECDSA_P384_SHA256,
-        ECDSA_P384_SHA384, ED25519,
+        Digest, KeyKind, SignatureAlgorithm, ALL_SIGNATURE_ALGORITHMS, ECDSA_P256_SHA256,
+        ECDSA_P256_SHA384, ECDSA_P384_SHA256, ECDSA_P384_SHA384, ED25519,
     },
     subject_name::{
-        AddrParseError, DnsNameRef, InvalidDnsNameError, InvalidSubjectNameError, IpAddrRef,
-        SubjectNameRef,
+        validate_presented_dns_name, verify_dns_names_match, AddrParseError, AttributeTypeAndValue,
+        Attributes, DistinguishedName, DnsNameRef, InvalidDnsNameError,
+        InvalidPresentedDnsNameError, InvalidSubjectNameError, IpAddrRef, IpAddressInDnsName,
+        PresentedName, Rdns, RelativeDistinguishedName, SubjectNameRef,
     },
     time::Time,
-    trust_anchor::{TlsClientTrustAnchors, TlsServerTrustAnchors, TrustAnchor},
+    trust_anchor::{
+        AnchorDnsSuffixes, AnchorExpiry, TlsClientTrustAnchors, TlsServerTrustAnchors, TrustAnchor,
+        TsaTrustAnchors,
+    },
+    verify_cert::{
+        KeyPurposeId, Usage, EKU_CLIENT_AUTH, EKU_OCSP_SIGNING, EKU_PRECERT_SIGNING,
+        EKU_SERVER_AUTH, EKU_TIME_STAMPING,
+    },
+    web_pki::WebPkiProfile,
 };
 
+#[cfg(feature = "tls-server-verify")]
+pub use dane::{verify_tlsa_records, verify_tlsa_records_with_options};
+
+/// Trust anchors, and the sets of them trusted for each kind of verification.
+///
+/// Public key pins and DANE TLSA records, which further restrict which trust
+/// anchors a path may end at, are here too.
+pub mod anchor {
+    pub use crate::{
+        AnchorDnsSuffixes, AnchorExpiry, InvalidPinError, SpkiPin, TlsClientTrustAnchors,
+        TlsServerTrustAnchors, TlsaAssociationData, TlsaCertUsage, TlsaMatchingType, TlsaRecord,
+        TlsaSelector, TrustAnchor, TsaTrustAnchors,
+    };
+
+    #[cfg(feature = "tls-server-verify")]
+    pub use crate::{verify_tlsa_records, verify_tlsa_records_with_options};
+
+    #[cfg(feature = "alloc")]
+    pub use crate::{AnchorDiff, AnchorStoreSummary};
+}
+
+/// Signature algorithms.
+pub mod alg {
+    pub use crate::{
+        Digest, KeyKind, SignatureAlgorithm, ALL_SIGNATURE_ALGORITHMS, ECDSA_P256_SHA256,
+        ECDSA_P256_SHA384, ECDSA_P384_SHA256, ECDSA_P384_SHA384, ED25519,
+    };
+
+    #[cfg(feature = "alloc")]
+    pub use crate::{
+        RSA_PKCS1_2048_8192_SHA256, RSA_PKCS1_2048_8192_SHA384, RSA_PKCS1_2048_8192_SHA512,
+        RSA_PKCS1_3072_8192_SHA384, RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+        RSA_PSS_2048_8192_SHA384_LEGACY_KEY, RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    };
+}
+
+/// End-entity certificates, their verification, and what can be read from
+/// them.
+pub mod certificate {
+    pub use crate::{
+        BasicConstraints, BlockedCert, Candidate, Cert, CertChain, CertId, Counter, CtPolicy,
+        EndEntityCert, EndEntityOrCa, HardwareModuleName, KeyPurposeId, MatterIdentity,
+        PrecertTbsCertificate, Scts, SerialHex, ShadowResult, SignedCertificateTimestamp, Time,
+        Usage, VerificationCounters, VerificationDiagnostics, VerificationOptions, VerifiedChain,
+        Warning, EKU_CLIENT_AUTH, EKU_OCSP_SIGNING, EKU_PRECERT_SIGNING, EKU_SERVER_AUTH,
+        EKU_TIME_STAMPING, MAX_NAME_CONSTRAINT_SUBTREES, MAX_SUBJECT_ALT_NAMES, MAX_SUB_CA_COUNT,
+    };
+
+    #[cfg(feature = "alloc")]
+    pub use crate::{
+        prevalidate_intermediates, OwnedEndEntityCert, ValidatedIntermediates, VerificationTrace,
+    };
+}
+
+/// DNS names and IP addresses, to verify certificates against, and the
+/// distinguished names of certificates' subjects and issuers.
+pub mod name {
+    pub use crate::{
+        validate_presented_dns_name, verify_dns_names_match, AddrParseError, AttributeTypeAndValue,
+        Attributes, DistinguishedName, DnsNameRef, InvalidDnsNameError,
+        InvalidPresentedDnsNameError, InvalidSubjectNameError, IpAddrRef, IpAddressInDnsName,
+        PresentedName, Rdns, RelativeDistinguishedName, SubjectNameRef,
+    };
+
+    #[cfg(feature = "alloc")]
+    pub use crate::{DnsName, DnsNameMatcher, IpAddr};
+}
+
+/// The types needed for most uses of the crate.
+///
+/// ```
+/// use webpki::prelude::*;
+/// ```
+pub mod prelude {
+    pub use crate::{
+        DnsNameRef, EndEntityCert, Error, SignatureAlgorithm, SubjectNameRef, Time,
+        TlsClientTrustAnchors, TlsServerTrustAnchors, TrustAnchor, Usage, VerificationOptions,
+    };
+}
+
 #[cfg(feature = "alloc")]
 pub use {
+    diff::{AnchorDiff, AnchorStoreSummary, ChainDiff},
+    end_entity::OwnedEndEntityCert,
+    prevalidate::{prevalidate_intermediates, ValidatedIntermediates},
     signed_data::{
         RSA_PKCS1_2048_8192_SHA256, RSA_PKCS1_2048_8192_SHA384, RSA_PKCS1_2048_8192_SHA512,
         RSA_PKCS1_3072_8192_SHA384, RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
         RSA_PSS_2048_8192_SHA384_LEGACY_KEY, RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
     },
-    subject_name::{DnsName, IpAddr},
+    subject_name::{DnsName, DnsNameMatcher, IpAddr},
+    trace::VerificationTrace,
 };
@@ -0,0 +1,44 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Fixed upper bounds on the work done while verifying a certificate.
+//!
+//! Certificate verification never allocates, unless candidate issuers are
+//! reordered (see the crate documentation): every parsed item borrows from
+//! the caller's input, and each candidate path is represented on the stack.
+//! The bounds below make the amount of stack used and the number of loop
+//! iterations performed independent of the contents of attacker-supplied
+//! certificates. Inputs exceeding a bound are rejected with an error; they
+//! are never truncated.
+
+/// The maximum number of intermediate certificates between the end-entity
+/// certificate and the trust anchor.
+///
+/// Path building recurses once per intermediate, so this also bounds the
+/// recursion depth of verification.
+pub const MAX_SUB_CA_COUNT: usize = 6;
+
+/// The maximum number of `GeneralName` entries accepted in a certificate's
+/// subjectAltName extension.
+///
+/// Certificates with more entries fail with
+/// `Error::MaximumSubjectAltNamesExceeded`.
+pub const MAX_SUBJECT_ALT_NAMES: usize = 1024;
+
+/// The maximum number of `GeneralSubtree` entries accepted in each of the
+/// permittedSubtrees and excludedSubtrees of a name constraints extension.
+///
+/// Name constraints with more entries fail with
+/// `Error::MaximumNameConstraintSubtreesExceeded`.
+pub const MAX_NAME_CONSTRAINT_SUBTREES: usize = 1024;
@@ -0,0 +1,218 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Checks of certificates against issuance profiles, for CA operations.
+//!
+//! Linting reports every deviation from the profile it finds, and is
+//! independent of verification: a certificate with findings may still
+//! verify, and one without any may not.
+//!
+//! Requires the `lint` feature.
+
+use crate::{
+    cert::{self, Cert, EndEntityOrCa},
+    der, signed_data, Error, KeyKind, EKU_SERVER_AUTH,
+};
+use alloc::vec::Vec;
+
+/// A certificate profile to lint against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Profile {
+    /// The requirements of RFC 5280 alone.
+    Rfc5280,
+
+    /// A TLS server certificate under the CA/Browser Forum Baseline
+    /// Requirements, which include those of RFC 5280.
+    CabfServerLeaf,
+
+    /// A subordinate CA certificate under the CA/Browser Forum Baseline
+    /// Requirements, which include those of RFC 5280.
+    CabfIntermediate,
+}
+
+/// A deviation from a `Profile`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Finding {
+    /// The serial number is negative, zero length or longer than 20 octets.
+    /// RFC 5280 Section 4.1.2.2.
+    NonConformingSerial,
+
+    /// The validity period is malformed, or its notBefore time is later
+    /// than its notAfter time. RFC 5280 Section 4.1.2.5.
+    InvalidValidity,
+
+    /// The basicConstraints extension is malformed. RFC 5280 Section
+    /// 4.2.1.9.
+    MalformedBasicConstraints,
+
+    /// A CA certificate has no subject key identifier extension. RFC 5280
+    /// Section 4.2.1.2.
+    MissingSubjectKeyId,
+
+    /// An end-entity certificate has a pathLenConstraint. RFC 5280 Section
+    /// 4.2.1.9.
+    PathLenConstraintOnEndEntity,
+
+    /// A TLS server certificate is a CA certificate.
+    CaCertAsLeaf,
+
+    /// A subordinate CA certificate is not a CA certificate.
+    NotCaCert,
+
+    /// A TLS server certificate has no subjectAltName extension.
+    MissingSubjectAltName,
+
+    /// A TLS server certificate has no extended key usage extension.
+    MissingExtendedKeyUsage,
+
+    /// The extended key usage extension of a TLS server certificate doesn't
+    /// include id-kp-serverAuth.
+    MissingServerAuthPurpose,
+
+    /// The validity period of a TLS server certificate is longer than 398
+    /// days.
+    ValidityTooLong,
+
+    /// The public key is an RSA key with a modulus shorter than 2048 bits.
+    RsaKeyTooSmall,
+}
+
+/// Returns the findings of linting `cert` against `profile`, in the order
+/// of the `Finding` variants.
+pub fn check(cert: &Cert, profile: Profile) -> Vec<Finding> {
+    const MAX_LEAF_VALIDITY_SECS: u64 = 398 * 24 * 60 * 60;
+
+    let mut findings = Vec::new();
+    let mut find = |finding| findings.push(finding);
+
+    if !cert.serial_is_conforming() {
+        find(Finding::NonConformingSerial);
+    }
+
+    let validity = cert.validity.read_all(Error::BadDer, |value| {
+        let not_before = der::time_choice(value)?;
+        let not_after = der::time_choice(value)?;
+        Ok((not_before, not_after))
+    });
+    let validity_secs = match validity {
+        Ok((not_before, not_after)) if not_before <= not_after => {
+            Some(not_after.as_seconds_since_unix_epoch() - not_before.as_seconds_since_unix_epoch())
+        }
+        _ => {
+            find(Finding::InvalidValidity);
+            None
+        }
+    };
+
+    let basic_constraints = cert.basic_constraints();
+    match basic_constraints {
+        Ok(basic_constraints) => {
+            if basic_constraints.is_ca && cert.subject_key_id.is_none() {
+                find(Finding::MissingSubjectKeyId);
+            }
+            if !basic_constraints.is_ca && basic_constraints.path_len_constraint.is_some() {
+                find(Finding::PathLenConstraintOnEndEntity);
+            }
+        }
+        Err(_) => find(Finding::MalformedBasicConstraints),
+    }
+    let is_ca = basic_constraints.map(|basic_constraints| basic_constraints.is_ca);
+
+    match profile {
+        Profile::Rfc5280 => (),
+        Profile::CabfServerLeaf => {
+            if is_ca == Ok(true) {
+                find(Finding::CaCertAsLeaf);
+            }
+            if cert.subject_alt_name.is_none() {
+                find(Finding::MissingSubjectAltName);
+            }
+            match cert.eku {
+                None => find(Finding::MissingExtendedKeyUsage),
+                Some(eku) if !has_purpose(eku, EKU_SERVER_AUTH.oid().value()) => {
+                    find(Finding::MissingServerAuthPurpose)
+                }
+                Some(_) => (),
+            }
+            // The validity period includes both the notBefore and the
+            // notAfter second.
+            if matches!(validity_secs, Some(secs) if secs >= MAX_LEAF_VALIDITY_SECS) {
+                find(Finding::ValidityTooLong);
+            }
+        }
+        Profile::CabfIntermediate => {
+            if is_ca == Ok(false) {
+                find(Finding::NotCaCert);
+            }
+        }
+    }
+
+    if profile != Profile::Rfc5280
+        && signed_data::public_key_kind(cert.spki.value()) == KeyKind::Rsa
+        && matches!(signed_data::public_key_bits(cert.spki.value()), Some(bits) if bits < 2048)
+    {
+        find(Finding::RsaKeyTooSmall);
+    }
+
+    findings
+}
+
+/// Lints every certificate of a path at once, so that all of its findings
+/// can be fixed in one go rather than one verification failure at a time.
+///
+/// `certs` is the DER-encoded end-entity certificate followed by the
+/// intermediate certificates of the path, in order, as in
+/// `CertChain::certs`. The end-entity certificate is linted against
+/// `leaf_profile` and the intermediates against `intermediate_profile`.
+///
+/// Returns each finding with the index in `certs` of the certificate it is
+/// about, in the order of `certs`. Fails only if a certificate can't be
+/// parsed.
+pub fn check_chain(
+    certs: &[&[u8]],
+    leaf_profile: Profile,
+    intermediate_profile: Profile,
+) -> Result<Vec<(usize, Finding)>, Error> {
+    let mut findings = Vec::new();
+    for (index, cert_der) in certs.iter().enumerate() {
+        let cert = cert::parse_cert(untrusted::Input::from(*cert_der), EndEntityOrCa::EndEntity)?;
+        let profile = if index == 0 {
+            leaf_profile
+        } else {
+            intermediate_profile
+        };
+        findings.extend(
+            check(&cert, profile)
+                .into_iter()
+                .map(|finding| (index, finding)),
+        );
+    }
+    Ok(findings)
+}
+
+// Whether the value of an extended key usage extension includes the purpose
+// with the object identifier `purpose`. A malformed extension includes none.
+fn has_purpose(eku: untrusted::Input, purpose: &[u8]) -> bool {
+    let mut eku = untrusted::Reader::new(eku);
+    while !eku.at_end() {
+        match der::expect_tag_and_get_value(&mut eku, der::Tag::OID) {
+            Ok(oid) if oid.as_slice_less_safe() == purpose => return true,
+            Ok(_) => (),
+            Err(_) => return false,
+        }
+    }
+    false
+}
@@ -0,0 +1,211 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Matter operational certificates.
+//!
+//! Matter identifies nodes, fabrics and CAs by attributes in the subject
+//! name, under the arc 1.3.6.1.4.1.37244.1, each encoded as a UTF8String
+//! of fixed-width uppercase hexadecimal. A node operational certificate
+//! (NOC) is issued by an optional intermediate CA certificate (ICAC), which
+//! is issued by the root CA certificate (RCAC).
+
+use crate::{der, Error};
+
+#[cfg(feature = "tls-client-verify")]
+use crate::{
+    cert::{Cert, EndEntityOrCa},
+    SignatureAlgorithm, TrustAnchor,
+};
+
+/// The Matter-specific attributes of a certificate's subject name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatterIdentity {
+    /// The matter-node-id attribute of a NOC.
+    pub node_id: Option<u64>,
+
+    /// The matter-firmware-signing-id attribute.
+    pub firmware_signing_id: Option<u64>,
+
+    /// The matter-icac-id attribute of an ICAC.
+    pub icac_id: Option<u64>,
+
+    /// The matter-rcac-id attribute of an RCAC.
+    pub rcac_id: Option<u64>,
+
+    /// The matter-fabric-id attribute.
+    pub fabric_id: Option<u64>,
+
+    /// The matter-noc-cat (CASE Authenticated Tag) attributes of a NOC, in
+    /// order; a NOC has at most three.
+    pub case_authenticated_tags: [Option<u32>; 3],
+}
+
+// 1.3.6.1.4.1.37244.1
+static MATTER_DN_ARC: [u8; 9] = oid![1, 3, 6, 1, 4, 1, 0x82, 0xa2, 0x7c, 1];
+
+const NODE_ID: u8 = 1;
+const FIRMWARE_SIGNING_ID: u8 = 2;
+const ICAC_ID: u8 = 3;
+const RCAC_ID: u8 = 4;
+const FABRIC_ID: u8 = 5;
+const NOC_CAT: u8 = 6;
+
+// ECDSA P-256 with SHA-256 is the only signature algorithm Matter allows.
+#[cfg(feature = "tls-client-verify")]
+pub(crate) static SIGNATURE_ALGORITHMS: &[&SignatureAlgorithm] = &[&crate::ECDSA_P256_SHA256];
+
+/// Parses the Matter attributes of `subject`, the value of a certificate's
+/// subject name.
+///
+/// Fails with `Error::InvalidMatterCert` if an attribute is repeated or
+/// malformed.
+pub(crate) fn matter_identity(subject: untrusted::Input) -> Result<MatterIdentity, Error> {
+    let mut identity = MatterIdentity::default();
+    subject.read_all(Error::BadDer, |rdns| {
+        while !rdns.at_end() {
+            der::nested(rdns, der::Tag::Set, Error::BadDer, |rdn| {
+                while !rdn.at_end() {
+                    der::nested(rdn, der::Tag::Sequence, Error::BadDer, |attribute| {
+                        let attribute_type =
+                            der::expect_tag_and_get_value(attribute, der::Tag::OID)?;
+                        let (tag, value) = der::read_tag_and_get_value(attribute)?;
+                        remember_attribute(&mut identity, attribute_type, tag, value)
+                    })?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(identity)
+}
+
+fn remember_attribute(
+    identity: &mut MatterIdentity,
+    attribute_type: untrusted::Input,
+    tag: u8,
+    value: untrusted::Input,
+) -> Result<(), Error> {
+    let attribute_type = attribute_type.as_slice_less_safe();
+    let id = match attribute_type {
+        [arc @ .., id] if arc == &MATTER_DN_ARC[..] => *id,
+        _ => return Ok(()),
+    };
+    if tag != u8::from(der::Tag::UTF8String) {
+        return Err(Error::InvalidMatterCert);
+    }
+
+    let out = match id {
+        NODE_ID => &mut identity.node_id,
+        FIRMWARE_SIGNING_ID => &mut identity.firmware_signing_id,
+        ICAC_ID => &mut identity.icac_id,
+        RCAC_ID => &mut identity.rcac_id,
+        FABRIC_ID => &mut identity.fabric_id,
+        NOC_CAT => {
+            let tag = parse_hex(value, 8)?;
+            #[allow(clippy::as_conversions)] // Eight hex digits fit in a u32.
+            let tag = Some(tag as u32);
+            let slot = identity
+                .case_authenticated_tags
+                .iter_mut()
+                .find(|slot| slot.is_none())
+                .ok_or(Error::InvalidMatterCert)?;
+            *slot = tag;
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+    if out.is_some() {
+        return Err(Error::InvalidMatterCert);
+    }
+    *out = Some(parse_hex(value, 16)?);
+    Ok(())
+}
+
+fn parse_hex(value: untrusted::Input, digits: usize) -> Result<u64, Error> {
+    let value = value.as_slice_less_safe();
+    if value.len() != digits {
+        return Err(Error::InvalidMatterCert);
+    }
+    value.iter().try_fold(0u64, |acc, c| {
+        let digit = match c {
+            b'0'..=b'9' => c - b'0',
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => return Err(Error::InvalidMatterCert),
+        };
+        Ok((acc << 4) | u64::from(digit))
+    })
+}
+
+/// Checks the subject of a NOC.
+#[cfg(feature = "tls-client-verify")]
+pub(crate) fn check_noc(noc: &Cert) -> Result<MatterIdentity, Error> {
+    let identity = matter_identity(noc.subject)?;
+    match identity {
+        MatterIdentity {
+            node_id: Some(node_id),
+            fabric_id: Some(fabric_id),
+            icac_id: None,
+            rcac_id: None,
+            firmware_signing_id: None,
+            ..
+        } if is_operational_node_id(node_id) && fabric_id != 0 => Ok(identity),
+        _ => Err(Error::InvalidMatterCert),
+    }
+}
+
+// Operational node IDs are 0x0000_0000_0000_0001 to 0xFFFF_FFEF_FFFF_FFFF.
+#[cfg(feature = "tls-client-verify")]
+fn is_operational_node_id(node_id: u64) -> bool {
+    node_id != 0 && node_id <= 0xffff_ffef_ffff_ffff
+}
+
+/// Checks the subjects of the ICAC, if any, and RCAC on the path to `noc`.
+#[cfg(feature = "tls-client-verify")]
+pub(crate) fn check_issuers(
+    noc: &MatterIdentity,
+    trust_anchor: &TrustAnchor,
+    path: &Cert,
+) -> Result<(), Error> {
+    let mut cert = path;
+    while let EndEntityOrCa::Ca(child_cert) = &cert.ee_or_ca {
+        let icac = matter_identity(cert.subject)?;
+        check_ca(noc, &icac, icac.icac_id, icac.rcac_id)?;
+        cert = child_cert;
+    }
+
+    let rcac = matter_identity(untrusted::Input::from(trust_anchor.subject))?;
+    check_ca(noc, &rcac, rcac.rcac_id, rcac.icac_id)
+}
+
+#[cfg(feature = "tls-client-verify")]
+fn check_ca(
+    noc: &MatterIdentity,
+    ca: &MatterIdentity,
+    expected_id: Option<u64>,
+    unexpected_id: Option<u64>,
+) -> Result<(), Error> {
+    let fabric_id_matches = match ca.fabric_id {
+        Some(fabric_id) => Some(fabric_id) == noc.fabric_id,
+        None => true,
+    };
+    if expected_id.is_none()
+        || unexpected_id.is_some()
+        || ca.node_id.is_some()
+        || !fabric_id_matches
+    {
+        return Err(Error::InvalidMatterCert);
+    }
+    Ok(())
+}
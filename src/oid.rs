@@ -0,0 +1,213 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The object identifiers this crate knows about.
+//!
+//! Extended key usage purposes are `KeyPurposeId`s and certificate
+//! extensions are `ExtensionId`s; everything else is a plain `Oid`.
+
+use core::fmt;
+
+pub use crate::verify_cert::{
+    KeyPurposeId, EKU_CLIENT_AUTH, EKU_OCSP_SIGNING, EKU_PRECERT_SIGNING, EKU_SERVER_AUTH,
+    EKU_TIME_STAMPING,
+};
+
+/// An object identifier, as the value (without tag and length) of its DER
+/// encoding.
+///
+/// The `Debug` output is in dotted decimal form, e.g. `2.5.29.17`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Oid(&'static [u8]);
+
+impl Oid {
+    /// Constructs an object identifier from the value (without tag and
+    /// length) of its DER encoding.
+    pub const fn new(value: &'static [u8]) -> Self {
+        Self(value)
+    }
+
+    /// The value (without tag and length) of the DER encoding.
+    pub fn value(&self) -> &'static [u8] {
+        self.0
+    }
+
+    pub(crate) fn matches(&self, value: untrusted::Input) -> bool {
+        value.as_slice_less_safe() == self.0
+    }
+}
+
+impl fmt::Debug for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_dotted_decimal(f, self.0)
+    }
+}
+
+// Writes the object identifier with the DER-encoded value `value` in dotted
+// decimal form.
+pub(crate) fn write_dotted_decimal(f: &mut fmt::Formatter, value: &[u8]) -> fmt::Result {
+    let mut arc: u64 = 0;
+    let mut first = true;
+    for &byte in value {
+        arc = (arc << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 != 0 {
+            continue;
+        }
+        if first {
+            // The first two arcs are encoded together as 40 * x + y.
+            let (x, y) = match arc {
+                0..=39 => (0, arc),
+                40..=79 => (1, arc - 40),
+                _ => (2, arc - 80),
+            };
+            write!(f, "{}.{}", x, y)?;
+            first = false;
+        } else {
+            write!(f, ".{}", arc)?;
+        }
+        arc = 0;
+    }
+    Ok(())
+}
+
+/// The object identifier of a certificate extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtensionId(Oid);
+
+impl ExtensionId {
+    /// Constructs an extension identifier from the value (without tag and
+    /// length) of the DER encoding of its object identifier.
+    pub const fn new(value: &'static [u8]) -> Self {
+        Self(Oid::new(value))
+    }
+
+    /// The object identifier.
+    pub fn oid(&self) -> Oid {
+        self.0
+    }
+
+    pub(crate) fn matches(&self, extn_id: untrusted::Input) -> bool {
+        self.0.matches(extn_id)
+    }
+}
+
+// Extensions.
+
+/// id-ce-subjectKeyIdentifier 2.5.29.14
+pub const SUBJECT_KEY_IDENTIFIER: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 14]);
+
+/// id-ce-keyUsage 2.5.29.15
+pub const KEY_USAGE: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 15]);
+
+/// id-ce-subjectAltName 2.5.29.17
+pub const SUBJECT_ALT_NAME: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 17]);
+
+/// id-ce-basicConstraints 2.5.29.19
+pub const BASIC_CONSTRAINTS: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 19]);
+
+/// id-ce-nameConstraints 2.5.29.30
+pub const NAME_CONSTRAINTS: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 30]);
+
+/// id-ce-authorityKeyIdentifier 2.5.29.35
+pub const AUTHORITY_KEY_IDENTIFIER: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 35]);
+
+/// id-ce-extKeyUsage 2.5.29.37
+pub const EXT_KEY_USAGE: ExtensionId = ExtensionId::new(&oid![2, 5, 29, 37]);
+
+/// The SignedCertificateTimestampList extension 1.3.6.1.4.1.11129.2.4.2, RFC
+/// 6962 Section 3.3.
+pub const SCT_LIST: ExtensionId = ExtensionId::new(&oid![1, 3, 6, 1, 4, 1, 0xd6, 0x79, 2, 4, 2]);
+
+/// The precertificate poison extension 1.3.6.1.4.1.11129.2.4.3, RFC 6962
+/// Section 3.1.
+pub const CT_POISON: ExtensionId = ExtensionId::new(&oid![1, 3, 6, 1, 4, 1, 0xd6, 0x79, 2, 4, 3]);
+
+/// id-pe-proxyCertInfo 1.3.6.1.5.5.7.1.14, RFC 3820 Section 3.8.
+pub const PROXY_CERT_INFO: ExtensionId = ExtensionId::new(&oid![1, 3, 6, 1, 5, 5, 7, 1, 14]);
+
+// Names.
+
+/// id-at-commonName 2.5.4.3
+pub const COMMON_NAME: Oid = Oid::new(&oid![2, 5, 4, 3]);
+
+/// id-on-hardwareModuleName 1.3.6.1.5.5.7.8.4, the otherName type of a
+/// hardware module name; see RFC 4108.
+pub const HARDWARE_MODULE_NAME: Oid = Oid::new(&oid![1, 3, 6, 1, 5, 5, 7, 8, 4]);
+
+// Public key and signature algorithms.
+
+/// rsaEncryption 1.2.840.113549.1.1.1
+pub const RSA_ENCRYPTION: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0x86, 0xf7, 0x0d, 1, 1, 1]);
+
+/// sha1WithRSAEncryption 1.2.840.113549.1.1.5
+pub const SHA1_WITH_RSA_ENCRYPTION: Oid =
+    Oid::new(&oid![1, 2, 0x86, 0x48, 0x86, 0xf7, 0x0d, 1, 1, 5]);
+
+/// id-RSASSA-PSS 1.2.840.113549.1.1.10
+pub const RSASSA_PSS: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0x86, 0xf7, 0x0d, 1, 1, 10]);
+
+/// sha256WithRSAEncryption 1.2.840.113549.1.1.11
+pub const SHA256_WITH_RSA_ENCRYPTION: Oid =
+    Oid::new(&oid![1, 2, 0x86, 0x48, 0x86, 0xf7, 0x0d, 1, 1, 11]);
+
+/// sha384WithRSAEncryption 1.2.840.113549.1.1.12
+pub const SHA384_WITH_RSA_ENCRYPTION: Oid =
+    Oid::new(&oid![1, 2, 0x86, 0x48, 0x86, 0xf7, 0x0d, 1, 1, 12]);
+
+/// sha512WithRSAEncryption 1.2.840.113549.1.1.13
+pub const SHA512_WITH_RSA_ENCRYPTION: Oid =
+    Oid::new(&oid![1, 2, 0x86, 0x48, 0x86, 0xf7, 0x0d, 1, 1, 13]);
+
+/// id-ecPublicKey 1.2.840.10045.2.1
+pub const EC_PUBLIC_KEY: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0xce, 0x3d, 2, 1]);
+
+/// secp256r1 1.2.840.10045.3.1.7, the P-256 curve.
+pub const SECP256R1: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0xce, 0x3d, 3, 1, 7]);
+
+/// secp384r1 1.3.132.0.34, the P-384 curve.
+pub const SECP384R1: Oid = Oid::new(&oid![1, 3, 0x81, 0x04, 0, 34]);
+
+/// ecdsa-with-SHA1 1.2.840.10045.4.1
+pub const ECDSA_WITH_SHA1: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0xce, 0x3d, 4, 1]);
+
+/// ecdsa-with-SHA256 1.2.840.10045.4.3.2
+pub const ECDSA_WITH_SHA256: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0xce, 0x3d, 4, 3, 2]);
+
+/// ecdsa-with-SHA384 1.2.840.10045.4.3.3
+pub const ECDSA_WITH_SHA384: Oid = Oid::new(&oid![1, 2, 0x86, 0x48, 0xce, 0x3d, 4, 3, 3]);
+
+/// id-X25519 1.3.101.110
+pub const X25519: Oid = Oid::new(&oid![1, 3, 101, 110]);
+
+/// id-X448 1.3.101.111
+pub const X448: Oid = Oid::new(&oid![1, 3, 101, 111]);
+
+/// id-Ed25519 1.3.101.112
+pub const ED25519: Oid = Oid::new(&oid![1, 3, 101, 112]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_test() {
+        assert_eq!(format!("{:?}", SUBJECT_ALT_NAME.oid()), "2.5.29.17");
+        assert_eq!(format!("{:?}", SUBJECT_ALT_NAME), "ExtensionId(2.5.29.17)");
+        assert_eq!(format!("{:?}", RSA_ENCRYPTION), "1.2.840.113549.1.1.1");
+        assert_eq!(format!("{:?}", SECP384R1), "1.3.132.0.34");
+        assert_eq!(format!("{:?}", SCT_LIST.oid()), "1.3.6.1.4.1.11129.2.4.2");
+        assert_eq!(format!("{:?}", EKU_SERVER_AUTH.oid()), "1.3.6.1.5.5.7.3.1");
+        assert_eq!(format!("{:?}", Oid::new(&[0x88, 0x37, 3])), "2.999.3");
+    }
+}
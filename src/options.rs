@@ -0,0 +1,1313 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use crate::{
+    cert::{Cert, CertId, EndEntityOrCa, V1Certs},
+    chain::{PathIndices, VerifiedChain},
+    der,
+    limits::MAX_SUB_CA_COUNT,
+    signed_data::{self, AlgorithmParameters, EcdsaSignatures},
+    subject_name,
+    verify_cert::PathError,
+    web_pki, AnchorExpiry, Error, KeyKind, KeyPurposeId, PresentedName, RevocationStatus,
+    RevocationStatusProvider, SignatureAlgorithm, Time, TrustAnchor, UnknownStatusPolicy,
+    WebPkiProfile,
+};
+use core::cell::Cell;
+
+#[cfg(feature = "alloc")]
+use crate::ValidatedIntermediates;
+
+#[cfg(feature = "tls-server-verify")]
+use crate::{AnchorDnsSuffixes, SubjectNameRef};
+use ring::digest;
+
+/// The parameters of a certificate path verification.
+///
+/// Construct one with `VerificationOptions::new`, then adjust it with the
+/// `with_*` and `require_*` methods.
+#[derive(Clone, Copy)]
+pub struct VerificationOptions<'a> {
+    pub(crate) supported_sig_algs: &'a [&'a SignatureAlgorithm],
+    pub(crate) time: Time,
+    pub(crate) max_sub_ca_count: usize,
+    pub(crate) prefer_shortest_path: bool,
+    pub(crate) require_eku_extension: bool,
+    pub(crate) require_ca_subject_key_id: bool,
+    pub(crate) require_ca_key_usage: bool,
+    pub(crate) diagnostics: Option<&'a dyn VerificationDiagnostics>,
+    pub(crate) blocklist: &'a [BlockedCert<'a>],
+    pub(crate) max_cert_len: usize,
+    pub(crate) max_leaf_validity_secs: u64,
+    pub(crate) max_intermediates: usize,
+    pub(crate) v1_intermediates: V1Certs,
+    pub(crate) ecdsa_signatures: EcdsaSignatures,
+    pub(crate) algorithm_parameters: AlgorithmParameters,
+    pub(crate) rsa_exponent_policy: RsaExponentPolicy,
+    pub(crate) cert_check: Option<&'a dyn Fn(&Cert) -> Result<(), Error>>,
+    pub(crate) chain_check: Option<&'a dyn Fn(&VerifiedChain) -> Result<(), Error>>,
+    pub(crate) revocation: Option<(&'a dyn RevocationStatusProvider, UnknownStatusPolicy)>,
+    pub(crate) acceptable_cas: Option<(&'a [&'a [u8]], CaHintPolicy)>,
+    pub(crate) issuer_key_order: &'a [KeyKind],
+    #[cfg(feature = "alloc")]
+    pub(crate) validated_intermediates: Option<&'a ValidatedIntermediates<'a>>,
+    pub(crate) web_pki_profile: Option<WebPkiProfile>,
+    pub(crate) leaf_serial_policy: Option<LeafSerialPolicy>,
+    pub(crate) anchor_expiries: &'a [AnchorExpiry<'a>],
+    pub(crate) reject_expired_anchors: bool,
+    #[cfg(feature = "tls-server-verify")]
+    pub(crate) anchor_dns_suffixes: Option<(SubjectNameRef<'a>, &'a [AnchorDnsSuffixes<'a>])>,
+}
+
+impl<'a> VerificationOptions<'a> {
+    /// Options for verifying a path at `time`, using `supported_sig_algs`
+    /// to check certificate signatures.
+    ///
+    /// `supported_sig_algs` is the list of signature algorithms that are
+    /// trusted for use in certificate signatures; the end-entity
+    /// certificate's public key is not validated against this list. `time`
+    /// is the time for which the validation is effective (usually the
+    /// current time).
+    pub fn new(supported_sig_algs: &'a [&'a SignatureAlgorithm], time: Time) -> Self {
+        Self {
+            supported_sig_algs,
+            time,
+            max_sub_ca_count: MAX_SUB_CA_COUNT,
+            prefer_shortest_path: false,
+            require_eku_extension: false,
+            require_ca_subject_key_id: false,
+            require_ca_key_usage: false,
+            diagnostics: None,
+            blocklist: &[],
+            max_cert_len: usize::MAX,
+            max_leaf_validity_secs: u64::MAX,
+            max_intermediates: usize::MAX,
+            v1_intermediates: V1Certs::Reject,
+            ecdsa_signatures: EcdsaSignatures::Der,
+            algorithm_parameters: AlgorithmParameters::Strict,
+            rsa_exponent_policy: RsaExponentPolicy::Any,
+            cert_check: None,
+            chain_check: None,
+            revocation: None,
+            acceptable_cas: None,
+            issuer_key_order: &[],
+            #[cfg(feature = "alloc")]
+            validated_intermediates: None,
+            web_pki_profile: None,
+            leaf_serial_policy: None,
+            anchor_expiries: &[],
+            reject_expired_anchors: false,
+            #[cfg(feature = "tls-server-verify")]
+            anchor_dns_suffixes: None,
+        }
+    }
+
+    /// Limits the number of intermediate certificates between the end-entity
+    /// certificate and the trust anchor to `max_sub_ca_count`.
+    ///
+    /// The limit can only be lowered: values above `MAX_SUB_CA_COUNT`, the
+    /// default, are treated as `MAX_SUB_CA_COUNT`.
+    pub fn with_max_sub_ca_count(mut self, max_sub_ca_count: usize) -> Self {
+        self.max_sub_ca_count = max_sub_ca_count.min(MAX_SUB_CA_COUNT);
+        self
+    }
+
+    /// Looks for a path with as few intermediate certificates as possible.
+    ///
+    /// By default path building goes depth-first through the candidate
+    /// issuers in the order they are given, and the first path found is used
+    /// even when a shorter one exists, e.g. when an issuing CA is certified
+    /// both by a trust anchor and by a newer root cross-signed by that trust
+    /// anchor. With this option, paths are looked for with no intermediate
+    /// certificates first, then with one more at a time up to the
+    /// `with_max_sub_ca_count` limit. The candidates that only lead to
+    /// longer paths are considered, and reported to
+    /// `VerificationDiagnostics`, again in each round.
+    pub fn prefer_shortest_path(mut self) -> Self {
+        self.prefer_shortest_path = true;
+        self
+    }
+
+    /// Rejects end-entity and intermediate certificates longer than
+    /// `max_cert_len` bytes with `Error::InputTooLarge`.
+    ///
+    /// The lengths are checked when verification starts, before any
+    /// intermediate certificate is parsed. The end-entity certificate has
+    /// already been parsed by `EndEntityCert::try_from` by then, so to avoid
+    /// parsing an oversized one at all, check the length of its encoding
+    /// before calling that. By default there is no limit other than what the
+    /// DER parser accepts.
+    pub fn with_max_cert_len(mut self, max_cert_len: usize) -> Self {
+        self.max_cert_len = max_cert_len;
+        self
+    }
+
+    /// Rejects end-entity certificates valid for longer than
+    /// `max_validity_secs` seconds with `Error::LeafValidityTooLong`, e.g.
+    /// to enforce an internal policy of 90-day certificates.
+    ///
+    /// The validity period includes both its notBefore and its notAfter
+    /// second (RFC 5280 Section 4.1.2.5), so a certificate valid for exactly
+    /// 90 days has a notAfter time one second short of 90 days after its
+    /// notBefore time. Intermediate certificates aren't limited. By default
+    /// there is no limit.
+    pub fn with_max_leaf_validity(mut self, max_validity_secs: u64) -> Self {
+        self.max_leaf_validity_secs = max_validity_secs;
+        self
+    }
+
+    /// Rejects more than `max_intermediates` intermediate certificates with
+    /// `Error::InputTooLarge`.
+    ///
+    /// This bounds the number of candidates considered for each certificate
+    /// on the path, which `with_max_sub_ca_count` does not. By default there
+    /// is no limit.
+    pub fn with_max_intermediates(mut self, max_intermediates: usize) -> Self {
+        self.max_intermediates = max_intermediates;
+        self
+    }
+
+    /// Requires the end-entity certificate to have an extended key usage
+    /// extension.
+    ///
+    /// By default a missing extension is taken to mean that the certificate
+    /// may be used for any purpose. With this option, such certificates fail
+    /// with `Error::RequiredEkuNotFound`.
+    pub fn require_eku_extension(mut self) -> Self {
+        self.require_eku_extension = true;
+        self
+    }
+
+    /// Requires intermediate certificates to have a subject key identifier
+    /// extension, as the CA/Browser Forum Baseline Requirements do.
+    ///
+    /// Private PKIs often omit the extension, so by default such
+    /// certificates are accepted, and reported with
+    /// `Warning::MissingSubjectKeyId`. With this option, they fail with
+    /// `Error::MissingSubjectKeyId`.
+    pub fn require_ca_subject_key_id(mut self) -> Self {
+        self.require_ca_subject_key_id = true;
+        self
+    }
+
+    /// Requires intermediate certificates to have a keyUsage extension, as
+    /// RFC 5280 does.
+    ///
+    /// An intermediate certificate whose keyUsage extension doesn't assert
+    /// keyCertSign always fails with `Error::KeyCertSignNotAsserted`. Legacy
+    /// CAs often omit the extension entirely, so by default such
+    /// certificates are accepted, and reported with
+    /// `Warning::MissingKeyUsage`. With this option, they fail with
+    /// `Error::MissingKeyUsage`.
+    ///
+    /// The paths through such a certificate are passed over, as for any
+    /// other unusable issuer; if no other path is found, verification fails
+    /// with its error rather than `Error::UnknownIssuer`.
+    /// `VerificationDiagnostics` tells which candidate issuer it was.
+    pub fn require_ca_key_usage(mut self) -> Self {
+        self.require_ca_key_usage = true;
+        self
+    }
+
+    /// Accepts X.509 v1 certificates as intermediates, for closed legacy
+    /// PKIs.
+    ///
+    /// A v1 certificate has no extensions, so it can't say that it is a CA;
+    /// with this option, a v1 intermediate certificate is assumed to be a
+    /// CA without a path length constraint. By default such certificates
+    /// are rejected. v1 certificates are always accepted as trust anchors by
+    /// `TrustAnchor::try_from_cert_der`.
+    pub fn accept_v1_intermediates(mut self) -> Self {
+        self.v1_intermediates = V1Certs::Accept;
+        self
+    }
+
+    /// Accepts ECDSA certificate signatures whose integers aren't minimally
+    /// encoded: with superfluous leading zeros, or without the leading zero
+    /// that keeps them positive. Some legacy devices produce such
+    /// signatures.
+    ///
+    /// By default only DER-encoded signatures are accepted, and others fail
+    /// like any invalid signature.
+    pub fn accept_lenient_ecdsa_signatures(mut self) -> Self {
+        self.ecdsa_signatures = EcdsaSignatures::Lenient;
+        self
+    }
+
+    /// Accepts certificate signatures whose algorithm identifiers, or those
+    /// of the issuer's public key, have NULL parameters where absent ones
+    /// are specified or vice versa: e.g. ecdsa-with-SHA256 with NULL
+    /// parameters, or sha256WithRSAEncryption without them. Each such
+    /// signature is reported with
+    /// `Warning::NonstandardAlgorithmParameters`.
+    ///
+    /// By default parameters must be encoded exactly as specified: NULL for
+    /// rsaEncryption and the RSA PKCS#1 signature algorithms, and absent for
+    /// the ECDSA signature algorithms and Ed25519. Other encodings fail with
+    /// `Error::UnsupportedSignatureAlgorithm` or
+    /// `Error::UnsupportedSignatureAlgorithmForPublicKey`.
+    pub fn accept_nonstandard_algorithm_parameters(mut self) -> Self {
+        self.algorithm_parameters = AlgorithmParameters::Compatible;
+        self
+    }
+
+    /// Restricts the public exponent of the RSA keys of the end-entity
+    /// certificate, the intermediate certificates and the trust anchor to
+    /// those allowed by `rsa_exponent_policy`.
+    ///
+    /// An end-entity certificate whose key violates the policy fails with
+    /// `Error::RsaExponentNotAllowed`. Otherwise an intermediate certificate
+    /// or trust anchor whose key violates it is treated like any other
+    /// unusable issuer, and `VerificationDiagnostics` receives
+    /// `Error::RsaExponentNotAllowed`.
+    pub fn with_rsa_exponent_policy(mut self, rsa_exponent_policy: RsaExponentPolicy) -> Self {
+        self.rsa_exponent_policy = rsa_exponent_policy;
+        self
+    }
+
+    /// Reports why candidate issuers were rejected to `diagnostics`.
+    pub fn with_diagnostics(mut self, diagnostics: &'a dyn VerificationDiagnostics) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Rejects every certificate matching an entry of `blocklist`, wherever
+    /// it appears on a path.
+    ///
+    /// This is for distrusting certificates in an emergency, without waiting
+    /// for revocation information to be published. An end-entity
+    /// certificate blocked by `IssuerAndSerial` or `CertSha256` fails with
+    /// `Error::CertBlocked`. Otherwise a blocked certificate is treated like
+    /// any other unusable issuer: the paths through it are passed over, so
+    /// unless another path exists verification fails with
+    /// `Error::UnknownIssuer`, and `VerificationDiagnostics` receives
+    /// `Error::CertBlocked`.
+    pub fn with_blocklist(mut self, blocklist: &'a [BlockedCert<'a>]) -> Self {
+        self.blocklist = blocklist;
+        self
+    }
+
+    /// Tells when trust anchors expire, so that paths ending at an expired
+    /// trust anchor can be reported or rejected.
+    ///
+    /// An anchor has expired if the time of the verification is after the
+    /// `not_after` time of an entry of `anchor_expiries` for it; anchors
+    /// without an entry never expire. Like browsers, verification accepts
+    /// a path ending at an expired anchor by default, and reports it with
+    /// `Warning::ExpiredTrustAnchor`; see `reject_expired_anchors`.
+    pub fn with_anchor_expiries(mut self, anchor_expiries: &'a [AnchorExpiry<'a>]) -> Self {
+        self.anchor_expiries = anchor_expiries;
+        self
+    }
+
+    /// Rejects paths ending at a trust anchor that has expired according to
+    /// `with_anchor_expiries`.
+    ///
+    /// The paths are passed over, as for any other unusable issuer, so
+    /// unless another path exists verification fails with
+    /// `Error::UnknownIssuer`, and `VerificationDiagnostics` receives
+    /// `Error::TrustAnchorExpired`.
+    pub fn reject_expired_anchors(mut self) -> Self {
+        self.reject_expired_anchors = true;
+        self
+    }
+
+    /// Honors the caller's restrictions of trust anchors to DNS suffixes,
+    /// for verifying a TLS server named `subject_name`.
+    ///
+    /// A path may only end at a trust anchor that `anchor_dns_suffixes`
+    /// restricts if `subject_name` is a DNS name under one of its suffixes.
+    /// Restrictions are checked against each candidate path, so an
+    /// unrestricted path is found even if a restricted one is also valid.
+    /// If valid paths exist but all of them end at anchors not trusted for
+    /// `subject_name`, verification fails with
+    /// `Error::AnchorNotTrustedForName`.
+    ///
+    /// `subject_name` is not checked against the end-entity certificate;
+    /// that is still done by `EndEntityCert::verify_is_valid_for_subject_name`.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    pub fn with_anchor_dns_suffixes(
+        mut self,
+        subject_name: SubjectNameRef<'a>,
+        anchor_dns_suffixes: &'a [AnchorDnsSuffixes<'a>],
+    ) -> Self {
+        self.anchor_dns_suffixes = Some((subject_name, anchor_dns_suffixes));
+        self
+    }
+
+    /// Enforces `profile`, the date-dependent requirements of the Web PKI,
+    /// on the end-entity and intermediate certificates, for verifying
+    /// publicly-trusted TLS certificates.
+    ///
+    /// By default only the requirements of RFC 5280 are enforced, as suits
+    /// private PKIs. A certificate that doesn't meet the profile fails with
+    /// the error `profile` documents; for an intermediate certificate, the
+    /// paths through it are passed over, as for any other unusable issuer.
+    pub fn with_web_pki_profile(mut self, profile: WebPkiProfile) -> Self {
+        self.web_pki_profile = Some(profile);
+        self
+    }
+
+    /// Checks that the serial number of the end-entity certificate has at
+    /// least 64 bits of entropy, i.e. is at least 8 octets long, not counting
+    /// a leading zero octet, as the CA/Browser Forum Baseline Requirements
+    /// require of publicly-trusted certificates.
+    ///
+    /// Unlike `WebPkiProfile::BaselineRequirements`, which only applies the
+    /// rule to certificates issued from 2016-09-30, this applies it to every
+    /// end-entity certificate, to catch misissued ones. Depending on
+    /// `policy`, a short serial number is reported with
+    /// `Warning::LowEntropyLeafSerial` or fails with
+    /// `Error::LowEntropySerial`. By default it isn't checked.
+    pub fn with_leaf_serial_policy(mut self, policy: LeafSerialPolicy) -> Self {
+        self.leaf_serial_policy = Some(policy);
+        self
+    }
+
+    /// Guides path building with `acceptable_cas`, the distinguished names
+    /// of the CAs the peer accepts certificates from, e.g. those of the
+    /// `certificate_authorities` extension of a TLS 1.3 CertificateRequest
+    /// (RFC 8446 Section 4.2.4). Each is a DER-encoded Name, with its
+    /// SEQUENCE tag and length, as returned by `TrustAnchor::subject_der`.
+    ///
+    /// A path goes through an acceptable CA if the subject of its trust
+    /// anchor or of one of its intermediate certificates is one of
+    /// `acceptable_cas`, compared as for issuer names. Intermediate
+    /// certificates named as or issued by an acceptable CA are tried before
+    /// the others, so that such a path, e.g. through the right one of
+    /// several cross-signed intermediates, is found first. With
+    /// `CaHintPolicy::Restrict`, the other paths are rejected, so unless
+    /// there is a path through an acceptable CA verification fails with
+    /// `Error::UnknownIssuer`. Malformed names match no CA.
+    pub fn with_acceptable_cas(
+        mut self,
+        acceptable_cas: &'a [&'a [u8]],
+        policy: CaHintPolicy,
+    ) -> Self {
+        self.acceptable_cas = Some((acceptable_cas, policy));
+        self
+    }
+
+    /// Tries candidate issuers with keys of the kinds in `issuer_key_order`
+    /// first, in that order, then those with keys of other kinds, e.g.
+    /// `&[KeyKind::Ed25519, KeyKind::EcdsaP256, KeyKind::EcdsaP384]` to
+    /// verify cheap signatures before expensive RSA ones where several
+    /// trust anchors or intermediate certificates could issue the same
+    /// certificate, as with cross-signed CAs.
+    ///
+    /// This only changes which path is found first, not whether one is:
+    /// candidates with other keys are still tried if none of the preferred
+    /// ones leads to a valid path. With `with_acceptable_cas`, acceptable
+    /// CAs are still tried before the others.
+    pub fn with_issuer_key_order(mut self, issuer_key_order: &'a [KeyKind]) -> Self {
+        self.issuer_key_order = issuer_key_order;
+        self
+    }
+
+    /// Skips verifying the signatures of intermediate certificates that
+    /// `validated_intermediates`, returned by `prevalidate_intermediates`,
+    /// says were verified, e.g. to verify many client certificates against
+    /// the same CA bundle.
+    ///
+    /// A signature is only skipped if these options support its algorithm,
+    /// and if it was verified with options no more lenient about the
+    /// encoding of ECDSA signatures.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn with_validated_intermediates(
+        mut self,
+        validated_intermediates: &'a ValidatedIntermediates<'a>,
+    ) -> Self {
+        self.validated_intermediates = Some(validated_intermediates);
+        self
+    }
+
+    /// Applies `cert_check`, the caller's own rules, to each certificate
+    /// considered for a path, e.g. to require a minimum serial number
+    /// length.
+    ///
+    /// `cert_check` is called once the crate's own checks of the certificate
+    /// that don't depend on its issuer have passed, before its issuer is
+    /// looked for; `Cert::ee_or_ca` says whether it is the end-entity
+    /// certificate. An error from `cert_check` is returned as is for the
+    /// end-entity certificate. An intermediate certificate it rejects is
+    /// treated like any other unusable issuer, and `VerificationDiagnostics`
+    /// receives the error.
+    pub fn with_cert_check(mut self, cert_check: &'a dyn Fn(&Cert) -> Result<(), Error>) -> Self {
+        self.cert_check = Some(cert_check);
+        self
+    }
+
+    /// Applies `chain_check`, the caller's own rules, to each complete path
+    /// that is otherwise valid, e.g. to require that the trust anchor be one
+    /// of a set of organizations' roots.
+    ///
+    /// A path that `chain_check` rejects is passed over like one through an
+    /// unusable issuer, and another path is looked for; unless one is found,
+    /// verification fails with `Error::UnknownIssuer`, and
+    /// `VerificationDiagnostics` receives the error.
+    pub fn with_chain_check(
+        mut self,
+        chain_check: &'a dyn Fn(&VerifiedChain) -> Result<(), Error>,
+    ) -> Self {
+        self.chain_check = Some(chain_check);
+        self
+    }
+
+    /// Checks the revocation status of every certificate on a path, with
+    /// `provider`.
+    ///
+    /// A certificate is revoked if `provider` says it was revoked at or
+    /// before `time`; a revoked end-entity certificate fails with
+    /// `Error::CertRevoked`. Whether a certificate whose status `provider`
+    /// doesn't know is accepted is up to `unknown_status_policy`. A revoked
+    /// intermediate certificate, or one rejected for its unknown status, is
+    /// treated like any other unusable issuer, and
+    /// `VerificationDiagnostics` receives the error.
+    ///
+    /// Since the status is looked up by the issuer's key, a certificate
+    /// with several candidate issuers is only rejected if no path through
+    /// any of them is valid.
+    pub fn with_revocation_status_provider(
+        mut self,
+        provider: &'a dyn RevocationStatusProvider,
+        unknown_status_policy: UnknownStatusPolicy,
+    ) -> Self {
+        self.revocation = Some((provider, unknown_status_policy));
+        self
+    }
+
+    pub(crate) fn check_cert(&self, cert: &Cert) -> Result<(), Error> {
+        match self.cert_check {
+            Some(cert_check) => cert_check(cert),
+            None => Ok(()),
+        }
+    }
+
+    // Applies `chain_check` to the path from `trust_anchor` to `path`.
+    pub(crate) fn check_chain(
+        &self,
+        trust_anchor: &TrustAnchor,
+        path: &Cert,
+        intermediate_certs: &[&[u8]],
+    ) -> Result<(), Error> {
+        let chain_check = match self.chain_check {
+            Some(chain_check) => chain_check,
+            None => return Ok(()),
+        };
+        let mut end_entity = path;
+        while let EndEntityOrCa::Ca(child_cert) = &end_entity.ee_or_ca {
+            end_entity = child_cert;
+        }
+        let indices = PathIndices::new(path, intermediate_certs).ok_or(Error::UnknownIssuer)?;
+        chain_check(&indices.into_verified_chain(
+            *trust_anchor,
+            end_entity.der.as_slice_less_safe(),
+            intermediate_certs,
+        ))
+    }
+
+    // Checks whether the trust anchor at `index` has expired, according to
+    // `anchor_expiries`.
+    pub(crate) fn check_anchor_expiry(
+        &self,
+        trust_anchor: &TrustAnchor,
+        index: usize,
+    ) -> Result<(), Error> {
+        let expired = self
+            .anchor_expiries
+            .iter()
+            .any(|expiry| expiry.spki == trust_anchor.spki && self.time > expiry.not_after);
+        if !expired {
+            return Ok(());
+        }
+        if self.reject_expired_anchors {
+            return Err(Error::TrustAnchorExpired);
+        }
+        self.warn(Warning::ExpiredTrustAnchor { index });
+        Ok(())
+    }
+
+    // Whether `name`, the value of a Name, is one of the acceptable CAs.
+    fn is_acceptable_ca(&self, name: untrusted::Input) -> bool {
+        let acceptable_cas = match self.acceptable_cas {
+            Some((acceptable_cas, _)) => acceptable_cas,
+            None => return false,
+        };
+        acceptable_cas.iter().any(|acceptable_ca| {
+            untrusted::Input::from(acceptable_ca)
+                .read_all(Error::BadDer, |value| {
+                    der::expect_tag_and_get_value(value, der::Tag::Sequence)
+                })
+                .map_or(false, |acceptable_ca| {
+                    subject_name::names_match(acceptable_ca, name)
+                })
+        })
+    }
+
+    // Whether the signature of `cert` by the key `issuer_spki` was verified
+    // by `prevalidate_intermediates`, so it needn't be verified again.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn signature_prevalidated(
+        &self,
+        cert: &Cert,
+        issuer_spki: untrusted::Input,
+    ) -> bool {
+        self.validated_intermediates.map_or(false, |validated| {
+            validated.has_verified(cert, issuer_spki, self)
+        })
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) fn signature_prevalidated(&self, _: &Cert, _: untrusted::Input) -> bool {
+        false
+    }
+
+    // Whether a path may end at `trust_anchor` under
+    // `with_anchor_dns_suffixes`.
+    #[cfg(feature = "tls-server-verify")]
+    pub(crate) fn anchor_trusted_for_name(&self, trust_anchor: &TrustAnchor) -> bool {
+        self.anchor_dns_suffixes
+            .map_or(true, |(subject_name, anchor_dns_suffixes)| {
+                AnchorDnsSuffixes::allow(anchor_dns_suffixes, trust_anchor, subject_name)
+            })
+    }
+
+    #[cfg(not(feature = "tls-server-verify"))]
+    pub(crate) fn anchor_trusted_for_name(&self, _: &TrustAnchor) -> bool {
+        true
+    }
+
+    // The position of the kind of the key `spki_value` in the issuer key
+    // order, or the number of kinds in it if the kind isn't there.
+    fn key_rank(&self, spki_value: untrusted::Input) -> usize {
+        if self.issuer_key_order.is_empty() {
+            return 0;
+        }
+        let key_kind = signed_data::public_key_kind(spki_value);
+        self.issuer_key_order
+            .iter()
+            .position(|preferred| *preferred == key_kind)
+            .unwrap_or(self.issuer_key_order.len())
+    }
+
+    // The number of passes of the search for an issuer among the trust
+    // anchors; see `anchor_pass`.
+    pub(crate) fn anchor_passes(&self) -> usize {
+        self.issuer_key_order.len() + 1
+    }
+
+    // The pass of the search for an issuer in which `trust_anchor` is tried:
+    // with an issuer key order, one pass per kind of key in it, then one for
+    // the others.
+    pub(crate) fn anchor_pass(&self, trust_anchor: &TrustAnchor) -> usize {
+        self.key_rank(untrusted::Input::from(trust_anchor.spki))
+    }
+
+    // The number of passes of the search for an issuer among the
+    // intermediate certificates; see `issuer_pass`.
+    pub(crate) fn issuer_passes(&self) -> usize {
+        let acceptable_ca_passes = if self.acceptable_cas.is_some() { 2 } else { 1 };
+        acceptable_ca_passes * self.anchor_passes()
+    }
+
+    // The pass of the search for an issuer in which `potential_issuer` is
+    // tried: with acceptable CAs, first those named as or issued by one of
+    // them, then the others; within each, by the kind of their key, as for
+    // `anchor_pass`.
+    pub(crate) fn issuer_pass(&self, potential_issuer: &Cert) -> usize {
+        let acceptable_ca_pass = if self.acceptable_cas.is_none()
+            || self.is_acceptable_ca(potential_issuer.subject)
+            || self.is_acceptable_ca(potential_issuer.issuer)
+        {
+            0
+        } else {
+            1
+        };
+        acceptable_ca_pass * self.anchor_passes() + self.key_rank(potential_issuer.spki.value())
+    }
+
+    // Checks, with `CaHintPolicy::Restrict`, that the path from
+    // `trust_anchor` to `path` goes through an acceptable CA.
+    pub(crate) fn check_acceptable_ca(
+        &self,
+        trust_anchor: &TrustAnchor,
+        path: &Cert,
+    ) -> Result<(), Error> {
+        match self.acceptable_cas {
+            Some((_, CaHintPolicy::Restrict)) => (),
+            _ => return Ok(()),
+        }
+        if self.is_acceptable_ca(untrusted::Input::from(trust_anchor.subject)) {
+            return Ok(());
+        }
+        let mut cert = path;
+        while let EndEntityOrCa::Ca(child_cert) = &cert.ee_or_ca {
+            if self.is_acceptable_ca(cert.subject) {
+                return Ok(());
+            }
+            cert = child_cert;
+        }
+        Err(Error::UnknownIssuer)
+    }
+
+    // Checks the revocation status of `cert`, given the value of its
+    // issuer's subjectPublicKeyInfo.
+    pub(crate) fn check_revocation(
+        &self,
+        cert: &Cert,
+        issuer_spki: untrusted::Input,
+    ) -> Result<(), Error> {
+        let (provider, unknown_status_policy) = match self.revocation {
+            Some(revocation) => revocation,
+            None => return Ok(()),
+        };
+        match provider.status(&CertId::from_issuer_spki(cert, issuer_spki)?) {
+            RevocationStatus::Good => Ok(()),
+            RevocationStatus::Revoked(_, revoked_at) if revoked_at > self.time => Ok(()),
+            RevocationStatus::Revoked(..) => Err(Error::CertRevoked),
+            RevocationStatus::Unknown => match unknown_status_policy {
+                UnknownStatusPolicy::Allow => Ok(()),
+                UnknownStatusPolicy::Deny => Err(Error::UnknownRevocationStatus),
+            },
+        }
+    }
+
+    pub(crate) fn report(&self, result: Result<(), PathError>) -> Result<(), PathError> {
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(()) => tracing::debug!("candidate issuer accepted"),
+            Err(PathError::Invalid(err)) => {
+                tracing::debug!(error = ?err, "candidate issuer rejected, backtracking")
+            }
+            Err(PathError::Continue) => tracing::debug!("path passed over, backtracking"),
+        }
+        if let (Err(PathError::Invalid(err)), Some(diagnostics)) = (result, self.diagnostics) {
+            diagnostics.issuer_rejected(err);
+        }
+        result
+    }
+
+    pub(crate) fn consider(&self, candidate: Candidate, depth: usize) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?candidate, depth, "candidate issuer considered");
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.candidate_considered(candidate, depth);
+        }
+    }
+
+    pub(crate) fn count(&self, counter: Counter) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.count(counter);
+        }
+    }
+
+    pub(crate) fn warn(&self, warning: Warning) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(?warning, "non-conforming certificate accepted");
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.warning(warning);
+        }
+    }
+
+    pub(crate) fn eku_not_found(&self, mismatch: EkuMismatch) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.required_eku_not_found(mismatch);
+        }
+    }
+
+    pub(crate) fn check_input_sizes(
+        &self,
+        end_entity: &Cert,
+        intermediate_certs: &[&[u8]],
+    ) -> Result<(), Error> {
+        if intermediate_certs.len() > self.max_intermediates
+            || end_entity.der().len() > self.max_cert_len
+            || intermediate_certs
+                .iter()
+                .any(|der| der.len() > self.max_cert_len)
+        {
+            return Err(Error::InputTooLarge);
+        }
+        Ok(())
+    }
+
+    // Checks the validity period of the end-entity certificate `end_entity`
+    // against `max_leaf_validity_secs`.
+    pub(crate) fn check_leaf_validity(&self, end_entity: &Cert) -> Result<(), Error> {
+        if self.max_leaf_validity_secs == u64::MAX {
+            return Ok(());
+        }
+        let (not_before, not_after) = end_entity.validity.read_all(Error::BadDer, |value| {
+            let not_before = der::time_choice(value)?;
+            let not_after = der::time_choice(value)?;
+            Ok((not_before, not_after))
+        })?;
+        let validity_secs = not_after
+            .as_seconds_since_unix_epoch()
+            .saturating_sub(not_before.as_seconds_since_unix_epoch())
+            .saturating_add(1);
+        if validity_secs > self.max_leaf_validity_secs {
+            return Err(Error::LeafValidityTooLong {
+                validity_secs,
+                max_validity_secs: self.max_leaf_validity_secs,
+            });
+        }
+        Ok(())
+    }
+
+    // Checks the serial number of the end-entity certificate `end_entity`
+    // against `leaf_serial_policy`.
+    pub(crate) fn check_leaf_serial(&self, end_entity: &Cert) -> Result<(), Error> {
+        match self.leaf_serial_policy {
+            Some(policy) if !web_pki::has_serial_entropy(end_entity) => match policy {
+                LeafSerialPolicy::Warn => self.warn(Warning::LowEntropyLeafSerial),
+                LeafSerialPolicy::Reject => return Err(Error::LowEntropySerial),
+            },
+            _ => (),
+        }
+        Ok(())
+    }
+
+    // Checks the entries of the blocklist that don't depend on the issuer.
+    pub(crate) fn check_not_blocked(&self, cert: &Cert) -> Result<(), Error> {
+        if self.blocklist.is_empty() {
+            return Ok(());
+        }
+        let cert_sha256 = digest::digest(&digest::SHA256, cert.der());
+        let blocked = self.blocklist.iter().any(|entry| match entry {
+            BlockedCert::IssuerAndSerial { issuer, serial } => {
+                *issuer == cert.issuer() && *serial == cert.serial()
+            }
+            BlockedCert::IssuerSpkiAndSerial { .. } | BlockedCert::CertId(_) => false,
+            BlockedCert::CertSha256(sha256) => sha256[..] == *cert_sha256.as_ref(),
+        });
+        if blocked {
+            return Err(Error::CertBlocked);
+        }
+        Ok(())
+    }
+
+    // Checks the public exponent of `spki_value` if it is an RSA key.
+    pub(crate) fn check_rsa_exponent(&self, spki_value: untrusted::Input) -> Result<(), Error> {
+        match signed_data::rsa_public_exponent(spki_value) {
+            Some(public_exponent) if !self.rsa_exponent_policy.allows(public_exponent) => {
+                Err(Error::RsaExponentNotAllowed)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Checks the entries of the blocklist that name the issuer by its key,
+    // given the value of the issuer's subjectPublicKeyInfo.
+    pub(crate) fn check_not_blocked_by_issuer(
+        &self,
+        cert: &Cert,
+        issuer_spki: untrusted::Input,
+    ) -> Result<(), Error> {
+        if !self.blocklist.iter().any(|entry| {
+            matches!(
+                entry,
+                BlockedCert::IssuerSpkiAndSerial { .. } | BlockedCert::CertId(_)
+            )
+        }) {
+            return Ok(());
+        }
+        let cert_id = CertId::from_issuer_spki(cert, issuer_spki)?;
+        let blocked = self.blocklist.iter().any(|entry| match entry {
+            BlockedCert::IssuerSpkiAndSerial {
+                issuer_spki_sha256,
+                serial,
+            } => *issuer_spki_sha256 == cert_id.issuer_spki_sha256 && *serial == cert_id.serial,
+            BlockedCert::CertId(blocked_id) => *blocked_id == cert_id,
+            _ => false,
+        });
+        if blocked {
+            return Err(Error::CertBlocked);
+        }
+        Ok(())
+    }
+
+    /// Passes everything that affects the outcome of a verification, other
+    /// than the time, to `update`, for `VerificationToken`.
+    pub(crate) fn update_policy_digest(&self, update: &mut impl FnMut(&[u8])) {
+        #[allow(clippy::as_conversions)] // usize is at most 64 bits.
+        let len = |len: usize| (len as u64).to_be_bytes();
+
+        // Two algorithms with the same name may accept different encodings;
+        // the verification function of a custom one can't be digested.
+        update(&len(self.supported_sig_algs.len()));
+        for alg in self.supported_sig_algs {
+            update(alg.name().as_bytes());
+            for alg_id in &alg.alg_ids() {
+                update(alg_id);
+            }
+        }
+        update(&len(self.max_sub_ca_count));
+        update(&[
+            u8::from(self.prefer_shortest_path),
+            u8::from(self.require_eku_extension),
+            u8::from(self.require_ca_subject_key_id),
+            u8::from(self.require_ca_key_usage),
+            u8::from(self.v1_intermediates == V1Certs::Accept),
+            u8::from(self.ecdsa_signatures == EcdsaSignatures::Lenient),
+            u8::from(self.algorithm_parameters == AlgorithmParameters::Compatible),
+            match self.rsa_exponent_policy {
+                RsaExponentPolicy::Any => 0,
+                RsaExponentPolicy::OddAtLeast3 => 1,
+                RsaExponentPolicy::Only65537 => 2,
+            },
+        ]);
+        update(&len(self.max_cert_len));
+        update(&self.max_leaf_validity_secs.to_be_bytes());
+        update(&len(self.anchor_expiries.len()));
+        for expiry in self.anchor_expiries {
+            update(&len(expiry.spki.len()));
+            update(expiry.spki);
+            update(&expiry.not_after.as_seconds_since_unix_epoch().to_be_bytes());
+        }
+        update(&[u8::from(self.reject_expired_anchors)]);
+        update(&len(self.max_intermediates));
+        // The checks and the revocation status provider themselves can't be
+        // digested, only whether there are any.
+        update(&[
+            u8::from(self.cert_check.is_some()),
+            u8::from(self.chain_check.is_some()),
+            match self.revocation {
+                None => 0,
+                Some((_, UnknownStatusPolicy::Allow)) => 1,
+                Some((_, UnknownStatusPolicy::Deny)) => 2,
+            },
+        ]);
+        update(&[match self.web_pki_profile {
+            None => 0,
+            Some(WebPkiProfile::BaselineRequirements) => 1,
+        }]);
+        update(&[match self.leaf_serial_policy {
+            None => 0,
+            Some(LeafSerialPolicy::Warn) => 1,
+            Some(LeafSerialPolicy::Reject) => 2,
+        }]);
+        match self.acceptable_cas {
+            None => update(&[0]),
+            Some((acceptable_cas, policy)) => {
+                update(&[match policy {
+                    CaHintPolicy::Prefer => 1,
+                    CaHintPolicy::Restrict => 2,
+                }]);
+                update(&len(acceptable_cas.len()));
+                for acceptable_ca in acceptable_cas {
+                    update(&len(acceptable_ca.len()));
+                    update(acceptable_ca);
+                }
+            }
+        }
+        update(&len(self.issuer_key_order.len()));
+        for key_kind in self.issuer_key_order {
+            update(&[match key_kind {
+                KeyKind::EcdsaP256 => 0,
+                KeyKind::EcdsaP384 => 1,
+                KeyKind::Ed25519 => 2,
+                KeyKind::Rsa => 3,
+                KeyKind::Other => 4,
+            }]);
+        }
+        update(&len(self.blocklist.len()));
+        for entry in self.blocklist {
+            match entry {
+                BlockedCert::IssuerAndSerial { issuer, serial } => {
+                    update(&[0]);
+                    update(issuer);
+                    update(serial);
+                }
+                BlockedCert::IssuerSpkiAndSerial {
+                    issuer_spki_sha256,
+                    serial,
+                } => {
+                    update(&[1]);
+                    update(issuer_spki_sha256);
+                    update(serial);
+                }
+                BlockedCert::CertSha256(sha256) => {
+                    update(&[2]);
+                    update(sha256);
+                }
+                BlockedCert::CertId(cert_id) => {
+                    update(&[3]);
+                    update(&cert_id.issuer_spki_sha256);
+                    update(&cert_id.issuer_dn_sha256);
+                    update(cert_id.serial);
+                }
+            }
+        }
+        #[cfg(feature = "tls-server-verify")]
+        match self.anchor_dns_suffixes {
+            None => update(&[0]),
+            Some((subject_name, anchor_dns_suffixes)) => {
+                update(&[match subject_name {
+                    SubjectNameRef::DnsName(_) => 1,
+                    SubjectNameRef::IpAddress(_) => 2,
+                }]);
+                update(subject_name.as_ref());
+                update(&len(anchor_dns_suffixes.len()));
+                for entry in anchor_dns_suffixes {
+                    update(entry.spki);
+                    update(&len(entry.dns_suffixes.len()));
+                    for suffix in entry.dns_suffixes {
+                        update(suffix.as_ref());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How the CAs the peer accepts certificates from are used; see
+/// `VerificationOptions::with_acceptable_cas`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaHintPolicy {
+    /// Paths through an acceptable CA are tried first, but other paths are
+    /// accepted too.
+    Prefer,
+
+    /// Only paths through an acceptable CA are accepted.
+    Restrict,
+}
+
+/// How an end-entity certificate whose serial number is too short to have
+/// 64 bits of entropy is treated; see
+/// `VerificationOptions::with_leaf_serial_policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeafSerialPolicy {
+    /// The certificate is accepted, and reported with
+    /// `Warning::LowEntropyLeafSerial`.
+    Warn,
+
+    /// The certificate fails with `Error::LowEntropySerial`.
+    Reject,
+}
+
+/// The public exponents of RSA keys accepted during verification; see
+/// `VerificationOptions::with_rsa_exponent_policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RsaExponentPolicy {
+    /// Any exponent that the signature algorithm itself accepts. This is the
+    /// default.
+    Any,
+
+    /// Odd exponents of at least 3.
+    OddAtLeast3,
+
+    /// Only 65537, as the CA/Browser Forum Baseline Requirements recommend.
+    Only65537,
+}
+
+impl RsaExponentPolicy {
+    // `public_exponent` is big-endian, without leading zeros.
+    fn allows(self, public_exponent: &[u8]) -> bool {
+        match self {
+            Self::Any => true,
+            Self::OddAtLeast3 => match public_exponent {
+                [] | [1] => false,
+                [.., last] => last & 1 == 1,
+            },
+            Self::Only65537 => public_exponent == [0x01, 0x00, 0x01],
+        }
+    }
+}
+
+/// A certificate to reject during verification; see
+/// `VerificationOptions::with_blocklist`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockedCert<'a> {
+    /// The certificate with this issuer and serial number.
+    ///
+    /// `issuer` and `serial` are as returned by `Cert::issuer` and
+    /// `Cert::serial`: the values, without tag and length, of the
+    /// certificate's `issuer` Name and `serialNumber` INTEGER.
+    IssuerAndSerial {
+        /// The value of the certificate's `issuer` Name.
+        issuer: &'a [u8],
+        /// The value of the certificate's `serialNumber` INTEGER.
+        serial: &'a [u8],
+    },
+
+    /// The certificate with this serial number, issued by the holder of the
+    /// public key with this SHA-256 subjectPublicKeyInfo digest, as in CRLite.
+    IssuerSpkiAndSerial {
+        /// The SHA-256 digest of the issuer's DER-encoded
+        /// subjectPublicKeyInfo.
+        issuer_spki_sha256: [u8; 32],
+        /// The value of the certificate's `serialNumber` INTEGER.
+        serial: &'a [u8],
+    },
+
+    /// The certificate with this SHA-256 digest of its DER encoding.
+    CertSha256([u8; 32]),
+
+    /// The certificate with this identity.
+    CertId(CertId<'a>),
+}
+
+/// Receives details of a path verification, to help explain a failure.
+pub trait VerificationDiagnostics {
+    /// Called when a trust anchor or intermediate certificate whose subject
+    /// matches the issuer of a certificate on the path being built cannot
+    /// be used to extend it, with the reason why.
+    ///
+    /// Path building continues with the next candidate, so this may be
+    /// called several times even if verification eventually succeeds.
+    fn issuer_rejected(&self, error: Error);
+
+    /// Called when `candidate`, whose subject matches the issuer of the
+    /// certificate at `depth` on the path being built, is about to be
+    /// checked. The end-entity certificate is at depth 0, its issuer at
+    /// depth 1, and so on.
+    ///
+    /// If the candidate is rejected, `issuer_rejected` is called next.
+    /// For each certificate, the trust anchors are considered before the
+    /// intermediate certificates, each in the order they are given in
+    /// unless `VerificationOptions::with_issuer_key_order` or
+    /// `VerificationOptions::with_acceptable_cas` reorders them. The order
+    /// only depends on the inputs and the options, so verifying the same
+    /// inputs with the same options always makes the same sequence of calls;
+    /// a recorded sequence explains how a path was chosen, and can be
+    /// reproduced by verifying again.
+    ///
+    /// The default implementation does nothing.
+    fn candidate_considered(&self, candidate: Candidate, depth: usize) {
+        let _ = (candidate, depth);
+    }
+
+    /// Called each time an event measured by `counter` happens.
+    ///
+    /// The default implementation does nothing; `VerificationCounters`
+    /// tallies the events.
+    fn count(&self, counter: Counter) {
+        let _ = counter;
+    }
+
+    /// Called when a certificate that doesn't conform to RFC 5280, but is
+    /// accepted anyway for compatibility, is on the path being built.
+    ///
+    /// This is called each time the certificate is checked, so it may be
+    /// called more than once for the same certificate, and also for
+    /// certificates that end up not being on the verified path.
+    ///
+    /// The default implementation does nothing.
+    fn warning(&self, warning: Warning) {
+        let _ = warning;
+    }
+
+    /// Called by `EndEntityCert::verify_is_valid_for_subject_name_with_diagnostics`
+    /// when the certificate isn't valid for the name, with each DNS name and
+    /// IP address in the certificate's subjectAltName extension, up to 16 of
+    /// them, in the order they appear in.
+    ///
+    /// The default implementation does nothing.
+    fn name_presented(&self, name: PresentedName) {
+        let _ = name;
+    }
+
+    /// Called when path building fails with `Error::RequiredEkuNotFound`
+    /// because of the certificate at `mismatch.depth`, with the purpose that
+    /// was required and those the certificate asserts.
+    ///
+    /// The default implementation does nothing.
+    fn required_eku_not_found(&self, mismatch: EkuMismatch) {
+        let _ = mismatch;
+    }
+}
+
+/// A certificate whose extended key usage extension doesn't include the
+/// required purpose; see `VerificationDiagnostics::required_eku_not_found`.
+#[derive(Clone, Copy)]
+pub struct EkuMismatch<'a> {
+    /// The required purpose.
+    pub required: KeyPurposeId,
+
+    /// The position of the certificate on the path, as in
+    /// `VerificationDiagnostics::candidate_considered`.
+    pub depth: usize,
+
+    pub(crate) eku: Option<untrusted::Input<'a>>,
+}
+
+impl<'a> EkuMismatch<'a> {
+    /// The purposes the certificate asserts, as the encoded values (without
+    /// tag and length) of their object identifiers, in the order they appear
+    /// in.
+    ///
+    /// This is empty if the certificate has no extended key usage extension,
+    /// which fails when `VerificationOptions::require_eku_extension` is set
+    /// or the required purpose is `EKU_OCSP_SIGNING`.
+    pub fn found(&self) -> impl Iterator<Item = &'a [u8]> {
+        let mut eku = self.eku.map(untrusted::Reader::new);
+        core::iter::from_fn(move || {
+            let eku = eku.as_mut()?;
+            if eku.at_end() {
+                return None;
+            }
+            der::expect_tag_and_get_value(eku, der::Tag::OID)
+                .ok()
+                .map(|purpose| purpose.as_slice_less_safe())
+        })
+    }
+}
+
+/// A deviation from RFC 5280 that is tolerated during path building; see
+/// `VerificationDiagnostics::warning`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The serial number of the certificate at this depth is negative, zero
+    /// length or longer than 20 octets. It is compared as the raw bytes of
+    /// the INTEGER, e.g. against a blocklist.
+    NonConformingSerial {
+        /// The position of the certificate on the path, as in
+        /// `VerificationDiagnostics::candidate_considered`.
+        depth: usize,
+    },
+
+    /// The intermediate certificate at this depth has no subject key
+    /// identifier extension; see
+    /// `VerificationOptions::require_ca_subject_key_id`.
+    MissingSubjectKeyId {
+        /// The position of the certificate on the path, as in
+        /// `VerificationDiagnostics::candidate_considered`.
+        depth: usize,
+    },
+
+    /// The intermediate certificate at this depth has no keyUsage extension;
+    /// see `VerificationOptions::require_ca_key_usage`.
+    MissingKeyUsage {
+        /// The position of the certificate on the path, as in
+        /// `VerificationDiagnostics::candidate_considered`.
+        depth: usize,
+    },
+
+    /// The signature algorithm of the certificate at this depth, or the
+    /// public key algorithm of its issuer, has NULL parameters where absent
+    /// ones are specified or vice versa; see
+    /// `VerificationOptions::accept_nonstandard_algorithm_parameters`.
+    NonstandardAlgorithmParameters {
+        /// The position of the certificate on the path, as in
+        /// `VerificationDiagnostics::candidate_considered`.
+        depth: usize,
+    },
+
+    /// The serial number of the end-entity certificate is too short to have
+    /// 64 bits of entropy; see `VerificationOptions::with_leaf_serial_policy`.
+    LowEntropyLeafSerial,
+
+    /// The serial number of the certificate at this depth is too short to
+    /// have the 64 bits of entropy that `WebPkiProfile::BaselineRequirements`
+    /// requires of it.
+    LowEntropySerial {
+        /// The position of the certificate on the path, as in
+        /// `VerificationDiagnostics::candidate_considered`.
+        depth: usize,
+    },
+
+    /// The trust anchor at this index has expired; see
+    /// `VerificationOptions::with_anchor_expiries`.
+    ExpiredTrustAnchor {
+        /// The index of the trust anchor, as in `Candidate::TrustAnchor`.
+        index: usize,
+    },
+}
+
+/// A candidate issuer, identified by its position in the trust anchors or
+/// intermediate certificates given to the verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Candidate {
+    /// The trust anchor at this index.
+    TrustAnchor(usize),
+
+    /// The intermediate certificate at this index.
+    Intermediate(usize),
+}
+
+/// An event counted during path building; see
+/// `VerificationDiagnostics::count`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Counter {
+    /// A certificate from `intermediate_certs` was parsed, as a candidate
+    /// issuer.
+    CertParsed,
+
+    /// A trust anchor whose subject matches the issuer of a certificate on
+    /// the path was tried.
+    AnchorTried,
+
+    /// A complete candidate path, from the end-entity certificate to a trust
+    /// anchor, had its signatures checked.
+    PathExplored,
+
+    /// A certificate signature was verified, successfully or not.
+    SignatureVerified,
+}
+
+/// A `VerificationDiagnostics` that counts the work done by a verification,
+/// for capacity planning and detecting regressions.
+///
+/// Use a new one for each verification, or read it before reusing it.
+#[derive(Debug, Default)]
+pub struct VerificationCounters {
+    certs_parsed: Cell<usize>,
+    anchors_tried: Cell<usize>,
+    paths_explored: Cell<usize>,
+    signatures_verified: Cell<usize>,
+}
+
+impl VerificationCounters {
+    /// Returns how many candidate issuer certificates were parsed.
+    pub fn certs_parsed(&self) -> usize {
+        self.certs_parsed.get()
+    }
+
+    /// Returns how many trust anchors were tried.
+    pub fn anchors_tried(&self) -> usize {
+        self.anchors_tried.get()
+    }
+
+    /// Returns how many complete candidate paths were checked.
+    pub fn paths_explored(&self) -> usize {
+        self.paths_explored.get()
+    }
+
+    /// Returns how many signatures were verified.
+    pub fn signatures_verified(&self) -> usize {
+        self.signatures_verified.get()
+    }
+}
+
+impl VerificationDiagnostics for VerificationCounters {
+    fn issuer_rejected(&self, _: Error) {}
+
+    fn count(&self, counter: Counter) {
+        let count = match counter {
+            Counter::CertParsed => &self.certs_parsed,
+            Counter::AnchorTried => &self.anchors_tried,
+            Counter::PathExplored => &self.paths_explored,
+            Counter::SignatureVerified => &self.signatures_verified,
+        };
+        count.set(count.get() + 1);
+    }
+}
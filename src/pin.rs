@@ -0,0 +1,185 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Public key pinning, as in HTTP Public Key Pinning ([RFC 7469]).
+//!
+//! [RFC 7469]: https://tools.ietf.org/html/rfc7469
+
+use crate::der;
+#[cfg(feature = "tls-server-verify")]
+use crate::{
+    cert::{Cert, EndEntityOrCa},
+    TrustAnchor,
+};
+use ring::digest;
+
+/// A pin: the SHA-256 digest of a DER-encoded subjectPublicKeyInfo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpkiPin([u8; 32]);
+
+impl SpkiPin {
+    /// Constructs a pin from its base64 encoding, as used in the `pin-sha256`
+    /// directive of RFC 7469.
+    pub fn from_base64(pin: &str) -> Result<Self, InvalidPinError> {
+        decode_base64_sha256(pin.as_bytes())
+            .map(Self)
+            .ok_or(InvalidPinError)
+    }
+
+    /// Constructs a pin from a SHA-256 digest.
+    pub fn from_sha256(digest: [u8; 32]) -> Self {
+        Self(digest)
+    }
+}
+
+impl AsRef<[u8]> for SpkiPin {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// An error indicating that a `SpkiPin` could not be built because the input
+/// is not the base64 encoding of a SHA-256 digest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvalidPinError;
+
+impl core::fmt::Display for InvalidPinError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+impl ::std::error::Error for InvalidPinError {}
+
+/// Returns true if any certificate on the path from `trust_anchor` to
+/// `path`, including the trust anchor, matches one of `pins`.
+#[cfg(feature = "tls-server-verify")]
+pub(crate) fn path_matches_pins(pins: &[SpkiPin], trust_anchor: &TrustAnchor, path: &Cert) -> bool {
+    let matches = |spki_sha256: Option<digest::Digest>| match spki_sha256 {
+        Some(spki_sha256) => pins.iter().any(|pin| pin.as_ref() == spki_sha256.as_ref()),
+        None => false,
+    };
+
+    let mut cert = path;
+    loop {
+        let spki_sha256 = digest::digest(&digest::SHA256, cert.spki_der.as_slice_less_safe());
+        if matches(Some(spki_sha256)) {
+            return true;
+        }
+        match &cert.ee_or_ca {
+            EndEntityOrCa::Ca(child_cert) => {
+                cert = child_cert;
+            }
+            EndEntityOrCa::EndEntity => {
+                break;
+            }
+        }
+    }
+
+    matches(spki_sha256(untrusted::Input::from(trust_anchor.spki)))
+}
+
+/// Computes the SHA-256 digest of a subjectPublicKeyInfo, given its value
+/// without the outer SEQUENCE tag and length, as stored in `TrustAnchor`.
+pub(crate) fn spki_sha256(spki: untrusted::Input) -> Option<digest::Digest> {
+    let header = der::Header::new(der::Tag::Sequence, spki.len())?;
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(header.as_ref());
+    ctx.update(spki.as_slice_less_safe());
+    Some(ctx.finish())
+}
+
+// Decodes the 44-character, padded, standard base64 encoding of a 32-byte
+// value.
+fn decode_base64_sha256(encoded: &[u8]) -> Option<[u8; 32]> {
+    fn sextet(c: u8) -> Option<u32> {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(u32::from(value))
+    }
+
+    // 32 bytes need 43 sextets (258 bits), plus one padding character.
+    let encoded = match encoded {
+        [encoded @ .., b'='] if encoded.len() == 43 => encoded,
+        _ => return None,
+    };
+
+    let mut out = [0u8; 32];
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut len = 0;
+    for c in encoded {
+        acc = (acc << 6) | sextet(*c)?;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            #[allow(clippy::as_conversions)] // Masked to fit in a u8.
+            let byte = ((acc >> bits) & 0xff) as u8;
+            out[len] = byte;
+            len += 1;
+            acc &= (1 << bits) - 1;
+        }
+    }
+
+    // The two leftover bits must be zero in a canonical encoding.
+    if acc != 0 {
+        return None;
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        // The SHA-256 digest of the empty string.
+        let pin = SpkiPin::from_base64("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=").unwrap();
+        assert_eq!(pin.as_ref(), digest::digest(&digest::SHA256, b"").as_ref());
+    }
+
+    #[test]
+    fn base64_rejects_invalid() {
+        // Too short.
+        assert_eq!(
+            SpkiPin::from_base64("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuF="),
+            Err(InvalidPinError)
+        );
+        // Missing padding.
+        assert_eq!(
+            SpkiPin::from_base64("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFUA"),
+            Err(InvalidPinError)
+        );
+        // Non-canonical trailing bits.
+        assert_eq!(
+            SpkiPin::from_base64("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFV="),
+            Err(InvalidPinError)
+        );
+        // Invalid character.
+        assert_eq!(
+            SpkiPin::from_base64("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuF!="),
+            Err(InvalidPinError)
+        );
+    }
+}
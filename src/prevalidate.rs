@@ -0,0 +1,215 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Verifying the signatures of a CA bundle's intermediate certificates once,
+//! for the many end-entity certificates verified against it.
+
+use crate::{
+    cert::{self, Cert, EndEntityOrCa},
+    signed_data::{self, EcdsaSignatures},
+    subject_name, verify_cert, Error, TrustAnchor, VerificationOptions,
+};
+use alloc::vec::Vec;
+
+/// The intermediate certificates of a CA bundle that chain to a trust
+/// anchor, with the signatures that were verified to chain them, as returned
+/// by `prevalidate_intermediates`.
+///
+/// Verifications given this set with
+/// `VerificationOptions::with_validated_intermediates` don't verify those
+/// signatures again. Everything else about a path, such as validity
+/// periods, basic and name constraints and extended key usages, is still
+/// checked for each verification, at its own time.
+///
+/// Requires the `alloc` feature.
+#[derive(Clone, Debug)]
+pub struct ValidatedIntermediates<'a> {
+    signatures: Vec<ValidatedSignature<'a>>,
+    errors: Vec<(usize, Error)>,
+    lenient_ecdsa_signatures: bool,
+}
+
+// The signature of the certificate `cert_der` was verified with the key of
+// its issuer, whose subjectPublicKeyInfo has the value `issuer_spki`.
+#[derive(Clone, Copy, Debug)]
+struct ValidatedSignature<'a> {
+    cert_der: &'a [u8],
+    issuer_spki: &'a [u8],
+}
+
+impl<'a> ValidatedIntermediates<'a> {
+    /// Returns the number of intermediate certificates that chain to a trust
+    /// anchor.
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Returns true if no intermediate certificate chains to a trust anchor.
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Returns whether the intermediate certificate `cert_der` chains to a
+    /// trust anchor.
+    pub fn contains(&self, cert_der: &[u8]) -> bool {
+        self.signatures
+            .iter()
+            .any(|signature| signature.cert_der == cert_der)
+    }
+
+    /// Returns the position in `intermediate_certs` of each intermediate
+    /// certificate that doesn't chain to a trust anchor, with the last error
+    /// found for it.
+    pub fn errors(&self) -> &[(usize, Error)] {
+        &self.errors
+    }
+
+    // Whether the signature of `cert` by the key `issuer_spki` was verified,
+    // with options that accept no more than `options` do.
+    pub(crate) fn has_verified(
+        &self,
+        cert: &Cert,
+        issuer_spki: untrusted::Input,
+        options: &VerificationOptions,
+    ) -> bool {
+        if self.lenient_ecdsa_signatures && options.ecdsa_signatures != EcdsaSignatures::Lenient {
+            return false;
+        }
+        let cert_der = cert.der.as_slice_less_safe();
+        let issuer_spki = issuer_spki.as_slice_less_safe();
+        self.signatures
+            .iter()
+            .any(|signature| signature.cert_der == cert_der && signature.issuer_spki == issuer_spki)
+            && signed_data::is_supported_signature(
+                options.supported_sig_algs,
+                untrusted::Input::from(issuer_spki),
+                &cert.signed_data,
+                options.algorithm_parameters,
+            )
+    }
+}
+
+/// Checks that each of `intermediate_certs`, e.g. those of a CA bundle
+/// loaded at startup, chains to one of `trust_anchors`, directly or through
+/// the others, with a valid signature by each issuer and each certificate
+/// valid at the time of `options`, returning the set of those that do.
+///
+/// The set is meant to be given to the verification of each end-entity
+/// certificate with `VerificationOptions::with_validated_intermediates`,
+/// with the same `intermediate_certs` and `trust_anchors`, so that the
+/// signatures of the intermediates are only verified once. It should be
+/// computed again when the bundle or the trust anchors change.
+///
+/// Requires the `alloc` feature.
+pub fn prevalidate_intermediates<'a>(
+    intermediate_certs: &[&'a [u8]],
+    trust_anchors: &[TrustAnchor<'a>],
+    options: &VerificationOptions,
+) -> ValidatedIntermediates<'a> {
+    let certs: Vec<Result<Cert<'a>, Error>> = intermediate_certs
+        .iter()
+        .map(|cert_der| {
+            cert::parse_cert_internal(
+                untrusted::Input::from(*cert_der),
+                EndEntityOrCa::EndEntity,
+                options.v1_intermediates,
+            )
+        })
+        .collect();
+    let mut errors: Vec<Option<Error>> = certs
+        .iter()
+        .map(|cert| cert.as_ref().err().copied())
+        .collect();
+    let mut validated = ValidatedIntermediates {
+        signatures: Vec::new(),
+        errors: Vec::new(),
+        lenient_ecdsa_signatures: options.ecdsa_signatures == EcdsaSignatures::Lenient,
+    };
+    let mut chained = Vec::new();
+    chained.resize(certs.len(), false);
+
+    // Each pass chains the certificates issued by a trust anchor or by a
+    // certificate chained in an earlier pass, until no more can be.
+    loop {
+        let mut progress = false;
+        for (index, cert) in certs.iter().enumerate() {
+            let cert = match cert {
+                Ok(cert) if !chained[index] => cert,
+                _ => continue,
+            };
+            let result = cert
+                .validity
+                .read_all(Error::BadDer, |value| {
+                    verify_cert::check_validity(value, options.time)
+                })
+                .and_then(|()| {
+                    let anchor_issuers = trust_anchors
+                        .iter()
+                        .filter(|anchor| {
+                            subject_name::names_match(
+                                cert.issuer,
+                                untrusted::Input::from(anchor.subject),
+                            )
+                        })
+                        .map(|anchor| anchor.spki);
+                    let intermediate_issuers = certs
+                        .iter()
+                        .zip(&chained)
+                        .filter_map(|(issuer, chained)| match issuer {
+                            Ok(issuer) if *chained => Some(issuer),
+                            _ => None,
+                        })
+                        .filter(|issuer| subject_name::names_match(cert.issuer, issuer.subject))
+                        .map(|issuer| issuer.spki.value().as_slice_less_safe());
+                    let mut result = Err(Error::UnknownIssuer);
+                    for issuer_spki in anchor_issuers.chain(intermediate_issuers) {
+                        result = signed_data::verify_signed_data(
+                            options.supported_sig_algs,
+                            untrusted::Input::from(issuer_spki),
+                            &cert.signed_data,
+                            options.ecdsa_signatures,
+                            options.algorithm_parameters,
+                        )
+                        .map(|()| issuer_spki);
+                        if result.is_ok() {
+                            break;
+                        }
+                    }
+                    result
+                });
+            match result {
+                Ok(issuer_spki) => {
+                    validated.signatures.push(ValidatedSignature {
+                        cert_der: intermediate_certs[index],
+                        issuer_spki,
+                    });
+                    chained[index] = true;
+                    errors[index] = None;
+                    progress = true;
+                }
+                Err(err) => errors[index] = Some(err),
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    validated.errors = errors
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, err)| err.map(|err| (index, err)))
+        .collect();
+    validated
+}
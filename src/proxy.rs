@@ -0,0 +1,161 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Proxy certificates, as specified in [RFC 3820].
+//!
+//! A proxy certificate is issued by an end-entity certificate, or by another
+//! proxy certificate, rather than by a CA. The end-entity certificate at the
+//! bottom of the proxy chain is verified as usual.
+//!
+//! [RFC 3820]: https://tools.ietf.org/html/rfc3820
+
+use crate::{
+    cert::{self, Cert, EndEntityOrCa},
+    der, oid, signed_data, verify_cert, Error, SignatureAlgorithm, Time, TrustAnchor,
+    VerificationOptions,
+};
+
+/// Verifies the proxy chain from `cert` through `proxy_issuers`, then
+/// verifies the end-entity certificate it ends with using `build_chain`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_proxy_chain(
+    required_eku_if_present: verify_cert::KeyPurposeId,
+    supported_sig_algs: &[&SignatureAlgorithm],
+    trust_anchors: &[TrustAnchor],
+    proxy_issuers: &[&[u8]],
+    intermediate_certs: &[&[u8]],
+    cert: &Cert,
+    time: Time,
+) -> Result<(), Error> {
+    let mut issuer: Option<Cert> = None;
+    for (proxies_below, issuer_der) in proxy_issuers.iter().enumerate() {
+        let proxy = issuer.as_ref().unwrap_or(cert);
+        let proxy_cert_info = proxy.proxy_cert_info.ok_or(Error::InvalidProxyCert)?;
+        let path_len_constraint = proxy_cert_info.read_all(Error::BadDer, parse_proxy_cert_info)?;
+        if let Some(len) = path_len_constraint {
+            if proxies_below > len {
+                return Err(Error::ProxyPathLenConstraintViolated);
+            }
+        }
+
+        let potential_issuer =
+            cert::parse_cert(untrusted::Input::from(issuer_der), EndEntityOrCa::EndEntity)?;
+        check_proxy(supported_sig_algs, proxy, &potential_issuer, time)?;
+        issuer = Some(potential_issuer);
+    }
+
+    // The last certificate must be the end-entity certificate the proxies
+    // were derived from.
+    let end_entity = issuer.as_ref().unwrap_or(cert);
+    if end_entity.proxy_cert_info.is_some() {
+        return Err(Error::InvalidProxyCert);
+    }
+
+    verify_cert::build_chain(
+        required_eku_if_present,
+        verify_cert::Precertificates::Reject,
+        &VerificationOptions::new(supported_sig_algs, time),
+        trust_anchors,
+        intermediate_certs,
+        end_entity,
+        0,
+        &|_, _| Ok(()),
+    )
+}
+
+// https://tools.ietf.org/html/rfc3820#section-3.8
+//
+// ProxyCertInfo ::= SEQUENCE {
+//      pCPathLenConstraint   INTEGER (0..MAX) OPTIONAL,
+//      proxyPolicy           ProxyPolicy }
+//
+// The proxy policy is not interpreted; applications that use policy
+// languages other than id-ppl-inheritAll must check it themselves.
+fn parse_proxy_cert_info(input: &mut untrusted::Reader) -> Result<Option<usize>, Error> {
+    let path_len_constraint = if input.peek(der::Tag::Integer.into()) {
+        Some(usize::from(der::small_nonnegative_integer(input)?))
+    } else {
+        None
+    };
+    der::nested(input, der::Tag::Sequence, Error::BadDer, |policy| {
+        let _policy_language = der::expect_tag_and_get_value(policy, der::Tag::OID)?;
+        policy.skip_to_end();
+        Ok(())
+    })?;
+    Ok(path_len_constraint)
+}
+
+// https://tools.ietf.org/html/rfc3820#section-4.1
+fn check_proxy(
+    supported_sig_algs: &[&SignatureAlgorithm],
+    proxy: &Cert,
+    issuer: &Cert,
+    time: Time,
+) -> Result<(), Error> {
+    // Proxies are never issued by CAs.
+    let issuer_is_ca = untrusted::read_all_optional(
+        issuer.basic_constraints,
+        Error::BadDer,
+        |value| match value {
+            Some(value) => {
+                let is_ca = der::optional_boolean(value)?;
+                value.skip_to_end();
+                Ok(is_ca)
+            }
+            None => Ok(false),
+        },
+    )?;
+
+    if issuer_is_ca
+        || proxy.issuer != issuer.subject
+        || proxy.subject_alt_name.is_some()
+        || !subject_extends_issuer(proxy.subject, issuer.subject)
+    {
+        return Err(Error::InvalidProxyCert);
+    }
+
+    proxy.validity.read_all(Error::BadDer, |value| {
+        verify_cert::check_validity(value, time)
+    })?;
+
+    signed_data::verify_signed_data(
+        supported_sig_algs,
+        issuer.spki.value(),
+        &proxy.signed_data,
+        signed_data::EcdsaSignatures::Der,
+        signed_data::AlgorithmParameters::Strict,
+    )
+}
+
+// The subject of a proxy is its issuer's subject with a single commonName
+// RDN appended.
+fn subject_extends_issuer(subject: untrusted::Input, issuer: untrusted::Input) -> bool {
+    let subject = subject.as_slice_less_safe();
+    let issuer = issuer.as_slice_less_safe();
+    if !subject.starts_with(issuer) {
+        return false;
+    }
+
+    untrusted::Input::from(&subject[issuer.len()..])
+        .read_all(Error::BadDer, |rdn| {
+            der::nested(rdn, der::Tag::Set, Error::BadDer, |set| {
+                der::nested(set, der::Tag::Sequence, Error::BadDer, |attribute| {
+                    let attribute_type = der::expect_tag_and_get_value(attribute, der::Tag::OID)?;
+                    let _value = der::read_tag_and_get_value(attribute)?;
+                    Ok(oid::COMMON_NAME.matches(attribute_type))
+                })
+            })
+        })
+        .unwrap_or(false)
+}
@@ -0,0 +1,225 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Revocation checking against the caller's own source of revocation
+//! status, such as a database, a CRLite filter or an OCSP response cache.
+
+use crate::{CertId, Time};
+use ring::digest;
+
+/// A source of revocation status, looked up by a certificate's issuer and
+/// serial number; see `VerificationOptions::with_revocation_status_provider`.
+pub trait RevocationStatusProvider {
+    /// Returns the revocation status of the certificate identified by
+    /// `cert_id`.
+    fn status(&self, cert_id: &CertId) -> RevocationStatus;
+
+    /// Returns when the revocation data the status of the certificate
+    /// identified by `cert_id` comes from, such as a CRL or an OCSP
+    /// response, must be replaced: its nextUpdate time. See
+    /// `VerifiedChain::revocation_next_update`.
+    ///
+    /// Returns `None` if the provider doesn't know, which is the default.
+    fn next_update(&self, cert_id: &CertId) -> Option<Time> {
+        let _ = cert_id;
+        None
+    }
+}
+
+/// Returns those of `cert_ids`, the identities of previously verified
+/// certificates, that `provider` says were revoked at or before `time`,
+/// e.g. to evict the sessions they were verified for when new revocation
+/// data, such as a CRL, is loaded, without verifying them again.
+///
+/// Certificates whose status `provider` doesn't know are not returned.
+pub fn revoked_cert_ids<'c, 'a>(
+    cert_ids: &'c [CertId<'a>],
+    provider: &'c dyn RevocationStatusProvider,
+    time: Time,
+) -> impl Iterator<Item = &'c CertId<'a>> + 'c {
+    cert_ids
+        .iter()
+        .filter(move |cert_id| match provider.status(cert_id) {
+            RevocationStatus::Revoked(_, revoked_at) => revoked_at <= time,
+            RevocationStatus::Good | RevocationStatus::Unknown => false,
+        })
+}
+
+/// The revocation status of a certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevocationStatus {
+    /// The certificate is not revoked.
+    Good,
+
+    /// The certificate was revoked for this reason at this time.
+    Revoked(RevocationReason, Time),
+
+    /// The provider doesn't know the certificate's status.
+    Unknown,
+}
+
+/// The reason a certificate was revoked, as in a CRL's reasonCode
+/// extension (RFC 5280 Section 5.3.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevocationReason {
+    /// No reason was given.
+    Unspecified,
+
+    /// The certificate's key was compromised.
+    KeyCompromise,
+
+    /// The key of a CA certificate was compromised.
+    CaCompromise,
+
+    /// The subject's name or other information changed.
+    AffiliationChanged,
+
+    /// The certificate was replaced.
+    Superseded,
+
+    /// The certificate is no longer needed.
+    CessationOfOperation,
+
+    /// The certificate is suspended, and may be reinstated.
+    CertificateHold,
+
+    /// The certificate was reinstated after `CertificateHold`; only used in
+    /// delta CRLs.
+    RemoveFromCrl,
+
+    /// A privilege granted to the subject was withdrawn.
+    PrivilegeWithdrawn,
+
+    /// The key of an attribute authority was compromised.
+    AaCompromise,
+}
+
+/// Whether a certificate whose revocation status is
+/// `RevocationStatus::Unknown` is accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnknownStatusPolicy {
+    /// The certificate is accepted, as if it were not revoked.
+    Allow,
+
+    /// The certificate is rejected with `Error::UnknownRevocationStatus`.
+    Deny,
+}
+
+/// A filter cascade, a compact encoding of the set of revoked certificates
+/// of some issuers; see `VerificationOptions::with_revocation_status_provider`.
+///
+/// A cascade is a sequence of Bloom filters. The first contains the
+/// revoked certificates, and some others; the second contains those others,
+/// and some of the revoked certificates; and so on, until a filter has no
+/// false positives. A certificate is revoked if the first filter that
+/// doesn't contain it is the second, fourth and so on, or if every filter
+/// contains it and there is an odd number of them.
+///
+/// A certificate is looked up by its `CertId`, with hash functions of this
+/// crate's own: the key of filter `layer`, counting from 0, for its hash
+/// function `i`, counting from 0, is the first 8 bytes, as a big-endian
+/// integer, of the SHA-256 digest of `salt`, `layer` and `i` as 4
+/// big-endian bytes each, `issuer_spki_sha256` and `serial`, modulo the
+/// number of bits of the filter. Bit `n` of a filter is the bit of value
+/// `1 << (n % 8)` of byte `n / 8` of its bits. A cascade must be built with
+/// these hash functions; the filters of other formats, such as those of
+/// CRLite, hash certificates differently and can't be used.
+///
+/// A cascade only knows the certificates it was built from, those of
+/// `enrolled_issuers`, so the status of others is
+/// `RevocationStatus::Unknown`. Neither the reason nor the time of a
+/// revocation are recorded, so revoked certificates are reported as
+/// revoked for `RevocationReason::Unspecified` at the Unix epoch; the
+/// cascade must be replaced as new revocations are published.
+#[derive(Clone, Copy, Debug)]
+pub struct FilterCascade<'a> {
+    /// The salt of the hash functions of all the filters.
+    pub salt: &'a [u8],
+
+    /// The filters, starting with the one containing the revoked
+    /// certificates.
+    pub layers: &'a [BloomFilter<'a>],
+
+    /// The SHA-256 digests of the DER-encoded subjectPublicKeyInfo of the
+    /// issuers whose certificates the cascade was built from, as in
+    /// `CertId::issuer_spki_sha256`.
+    pub enrolled_issuers: &'a [[u8; 32]],
+}
+
+/// A Bloom filter of a `FilterCascade`.
+#[derive(Clone, Copy, Debug)]
+pub struct BloomFilter<'a> {
+    bits: &'a [u8],
+    hash_count: u32,
+}
+
+impl FilterCascade<'_> {
+    /// Returns whether the cascade says the certificate identified by
+    /// `cert_id` is revoked, whether or not its issuer is enrolled.
+    pub fn contains(&self, cert_id: &CertId) -> bool {
+        for (layer, filter) in (0..).zip(self.layers) {
+            if !filter.contains(self.salt, layer, cert_id) {
+                return layer % 2 == 1;
+            }
+        }
+        self.layers.len() % 2 == 1
+    }
+}
+
+impl RevocationStatusProvider for FilterCascade<'_> {
+    fn status(&self, cert_id: &CertId) -> RevocationStatus {
+        if !self.enrolled_issuers.contains(&cert_id.issuer_spki_sha256) {
+            return RevocationStatus::Unknown;
+        }
+        match self.contains(cert_id) {
+            true => RevocationStatus::Revoked(
+                RevocationReason::Unspecified,
+                Time::from_seconds_since_unix_epoch(0),
+            ),
+            false => RevocationStatus::Good,
+        }
+    }
+}
+
+impl<'a> BloomFilter<'a> {
+    /// Returns the filter with these bits and `hash_count` hash functions,
+    /// or `None` if `hash_count` is 0, since such a filter would contain
+    /// everything. A filter without bits contains nothing.
+    pub fn new(bits: &'a [u8], hash_count: u32) -> Option<Self> {
+        match hash_count {
+            0 => None,
+            _ => Some(Self { bits, hash_count }),
+        }
+    }
+
+    #[allow(clippy::as_conversions)] // usize is at most 64 bits.
+    fn contains(&self, salt: &[u8], layer: u32, cert_id: &CertId) -> bool {
+        let bit_count = (self.bits.len() as u64).saturating_mul(8);
+        if bit_count == 0 {
+            return false;
+        }
+        (0..self.hash_count).all(|i| {
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update(salt);
+            ctx.update(&layer.to_be_bytes());
+            ctx.update(&i.to_be_bytes());
+            ctx.update(&cert_id.issuer_spki_sha256);
+            ctx.update(cert_id.serial);
+            let mut key = [0; 8];
+            key.copy_from_slice(&ctx.finish().as_ref()[..8]);
+            let bit = u64::from_be_bytes(key) % bit_count;
+            self.bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+        })
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Signed Certificate Timestamps embedded in certificates, and
+//! precertificates.
+//!
+//! See [RFC 6962 Section 3.1] and [RFC 6962 Section 3.3].
+//!
+//! [RFC 6962 Section 3.1]: https://tools.ietf.org/html/rfc6962#section-3.1
+//! [RFC 6962 Section 3.3]: https://tools.ietf.org/html/rfc6962#section-3.3
+
+use crate::{cert::Cert, der, oid, EndEntityCert, Error, Time};
+
+/// A Signed Certificate Timestamp (SCT) from a certificate's embedded
+/// SignedCertificateTimestampList extension.
+///
+/// The SCT is only parsed, not verified: checking its signature requires the
+/// log's public key, which is the business of a [`CtPolicy`].
+#[derive(Clone, Copy, Debug)]
+pub struct SignedCertificateTimestamp<'a> {
+    /// The SCT version. Only v1 (0) is defined.
+    pub version: u8,
+
+    /// The SHA-256 hash of the log's public key.
+    pub log_id: &'a [u8],
+
+    /// The time the SCT was issued, in milliseconds since the UNIX epoch.
+    pub timestamp: u64,
+
+    /// The encoded `CtExtensions`, usually empty.
+    pub extensions: &'a [u8],
+
+    /// The TLS `HashAlgorithm` of the signature.
+    pub hash_algorithm: u8,
+
+    /// The TLS `SignatureAlgorithm` of the signature.
+    pub signature_algorithm: u8,
+
+    /// The signature over the certificate entry.
+    pub signature: &'a [u8],
+}
+
+/// An iterator over the SCTs embedded in a certificate.
+///
+/// SCTs with a version other than v1 are reported as
+/// `Err(Error::UnsupportedSctVersion)` and iteration continues with the next
+/// SCT. A structurally malformed list is reported as `Err(Error::BadSctList)`,
+/// after which iteration stops.
+pub struct Scts<'a> {
+    list: Option<untrusted::Reader<'a>>,
+    error: Option<Error>,
+}
+
+impl<'a> Iterator for Scts<'a> {
+    type Item = Result<SignedCertificateTimestamp<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+
+        let list = self.list.as_mut()?;
+        if list.at_end() {
+            return None;
+        }
+
+        let result = match read_u16_prefixed(list) {
+            Ok(sct) => sct.read_all(Error::BadSctList, parse_sct),
+            Err(err) => Err(err),
+        };
+        if let Err(Error::BadSctList) = result {
+            self.list = None;
+        }
+        Some(result)
+    }
+}
+
+pub(crate) fn scts(extension: Option<untrusted::Input>) -> Scts {
+    let extension = match extension {
+        Some(extension) => extension,
+        None => {
+            return Scts {
+                list: None,
+                error: None,
+            };
+        }
+    };
+
+    // The extension value is an OCTET STRING wrapping the TLS-encoded
+    // `SignedCertificateTimestampList`.
+    let list = extension.read_all(Error::BadSctList, |value| {
+        let list = der::expect_tag_and_get_value(value, der::Tag::OctetString)
+            .map_err(|_| Error::BadSctList)?;
+        list.read_all(Error::BadSctList, read_u16_prefixed)
+    });
+
+    match list {
+        Ok(list) => Scts {
+            list: Some(untrusted::Reader::new(list)),
+            error: None,
+        },
+        Err(err) => Scts {
+            list: None,
+            error: Some(err),
+        },
+    }
+}
+
+fn parse_sct<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<SignedCertificateTimestamp<'a>, Error> {
+    let version = read_u8(input)?;
+    if version != 0 {
+        // The layout of other versions is unknown; skip the rest of the entry.
+        input.skip_to_end();
+        return Err(Error::UnsupportedSctVersion);
+    }
+
+    const LOG_ID_LEN: usize = 32;
+    let log_id = input
+        .read_bytes(LOG_ID_LEN)
+        .map_err(|_| Error::BadSctList)?;
+    let timestamp = input
+        .read_bytes(8)
+        .map_err(|_| Error::BadSctList)?
+        .as_slice_less_safe()
+        .iter()
+        .fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+    let extensions = read_u16_prefixed(input)?;
+    let hash_algorithm = read_u8(input)?;
+    let signature_algorithm = read_u8(input)?;
+    let signature = read_u16_prefixed(input)?;
+
+    Ok(SignedCertificateTimestamp {
+        version,
+        log_id: log_id.as_slice_less_safe(),
+        timestamp,
+        extensions: extensions.as_slice_less_safe(),
+        hash_algorithm,
+        signature_algorithm,
+        signature: signature.as_slice_less_safe(),
+    })
+}
+
+fn read_u8(input: &mut untrusted::Reader) -> Result<u8, Error> {
+    input.read_byte().map_err(|_| Error::BadSctList)
+}
+
+fn read_u16_prefixed<'a>(input: &mut untrusted::Reader<'a>) -> Result<untrusted::Input<'a>, Error> {
+    let hi = read_u8(input)?;
+    let lo = read_u8(input)?;
+    let len = (usize::from(hi) << 8) | usize::from(lo);
+    input.read_bytes(len).map_err(|_| Error::BadSctList)
+}
+
+/// A Certificate Transparency policy consulted during server certificate
+/// verification.
+///
+/// The policy is consulted once a path to a trust anchor has been found, so
+/// that it has access to the issuer's key, which is needed to verify SCTs
+/// over precertificate entries. If the policy fails, path building carries
+/// on with the next candidate path, as for any other path-dependent check.
+pub trait CtPolicy {
+    /// Decides whether `cert` satisfies the policy.
+    ///
+    /// `issuer_spki` is the value of the `subjectPublicKeyInfo` field of the
+    /// certificate's issuer on the candidate path, in the same form as
+    /// `TrustAnchor::spki`. `time` is the time for which the validation is
+    /// effective.
+    ///
+    /// Policies should return `Error::CtPolicyNotSatisfied` when the SCTs are
+    /// well-formed but insufficient.
+    fn check(&self, cert: &EndEntityCert, issuer_spki: &[u8], time: Time) -> Result<(), Error>;
+}
+
+/// The `TBSCertificate` of a precertificate with the poison extension
+/// removed, as logged in a `PreCert` entry and covered by the log's SCT.
+///
+/// The encoding is not contiguous in the precertificate, so it is exposed as
+/// a sequence of chunks; concatenating them gives the DER encoding.
+pub struct PrecertTbsCertificate<'a> {
+    tbs_header: der::Header,
+    fields_before_issuer: &'a [u8],
+    issuer_header: der::Header,
+    issuer: &'a [u8],
+    fields_after_issuer: &'a [u8],
+    extensions_headers: Option<(der::Header, der::Header)>,
+    extensions: [&'a [u8]; 4],
+}
+
+impl<'a> PrecertTbsCertificate<'a> {
+    /// Returns the chunks making up the DER encoding, in order.
+    pub fn chunks(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        let (tagged_header, sequence_header): (&[u8], &[u8]) = match &self.extensions_headers {
+            Some((tagged, sequence)) => (tagged.as_ref(), sequence.as_ref()),
+            None => (&[], &[]),
+        };
+        core::iter::once(self.tbs_header.as_ref())
+            .chain(core::iter::once(self.fields_before_issuer))
+            .chain(core::iter::once(self.issuer_header.as_ref()))
+            .chain(core::iter::once(self.issuer))
+            .chain(core::iter::once(self.fields_after_issuer))
+            .chain(core::iter::once(tagged_header))
+            .chain(core::iter::once(sequence_header))
+            .chain(self.extensions.iter().copied())
+    }
+
+    /// Returns the length of the DER encoding.
+    pub fn len(&self) -> usize {
+        self.chunks().map(<[u8]>::len).sum()
+    }
+
+    /// Returns true if the encoding is empty, which it never is.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+// Returns the logged `TBSCertificate` of the precertificate `cert`, if it is
+// one. If it was issued by `precert_signing_cert`, its issuer and
+// authorityKeyIdentifier are replaced with those of the signing certificate's
+// own issuer, which issues the final certificate (RFC 6962 Section 3.2).
+pub(crate) fn precert_tbs_certificate<'a>(
+    cert: &Cert<'a>,
+    precert_signing_cert: Option<&Cert<'a>>,
+) -> Option<PrecertTbsCertificate<'a>> {
+    if !cert.ct_poison {
+        return None;
+    }
+
+    let final_issuer = match precert_signing_cert {
+        Some(signing_cert) => {
+            let authority_key_id = signing_cert
+                .tbs
+                .read_all(Error::BadDer, |tbs| {
+                    find_extension(tbs, oid::AUTHORITY_KEY_IDENTIFIER)
+                })
+                .ok()?;
+            Some((signing_cert.issuer, authority_key_id))
+        }
+        None => None,
+    };
+
+    // `parse_cert` has already checked the structure, so this only has to
+    // find the boundaries of the fields and extensions that change.
+    cert.tbs
+        .read_all(Error::BadDer, |tbs| {
+            // version, serialNumber and signature.
+            let (fields_before_issuer, ()) = tbs.read_partial(|tbs| skip_fields(tbs, 3))?;
+            let issuer = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
+            // validity, subject and subjectPublicKeyInfo.
+            let (fields_after_issuer, ()) = tbs.read_partial(|tbs| skip_fields(tbs, 3))?;
+            let issuer = final_issuer.map_or(issuer, |(issuer, _)| issuer);
+            let issuer_header =
+                der::Header::new(der::Tag::Sequence, issuer.len()).ok_or(Error::BadDer)?;
+
+            // The extensions, without the poison extension and with the
+            // authorityKeyIdentifier extension replaced, as up to three runs
+            // of consecutive extensions and the replacement.
+            let mut chunks: [&[u8]; 4] = [&[]; 4];
+            let mut chunk_count = 0;
+            let mut push = |chunk: untrusted::Input<'a>| -> Result<(), Error> {
+                *chunks.get_mut(chunk_count).ok_or(Error::BadDer)? = chunk.as_slice_less_safe();
+                chunk_count += 1;
+                Ok(())
+            };
+            let extensions =
+                der::expect_tag_and_get_value(tbs, der::Tag::ContextSpecificConstructed3)?;
+            extensions.read_all(Error::BadDer, |tagged| {
+                der::nested(tagged, der::Tag::Sequence, Error::BadDer, |extensions| {
+                    let mut run_start = extensions.mark();
+                    while !extensions.at_end() {
+                        let extension_start = extensions.mark();
+                        let extn_id = read_extension(extensions)?.1;
+                        let replacement = if oid::CT_POISON.matches(extn_id) {
+                            Some(None)
+                        } else if oid::AUTHORITY_KEY_IDENTIFIER.matches(extn_id) {
+                            final_issuer.map(|(_, authority_key_id)| authority_key_id)
+                        } else {
+                            None
+                        };
+                        if let Some(replacement) = replacement {
+                            push(
+                                extensions
+                                    .get_input_between_marks(run_start, extension_start)
+                                    .map_err(|_| Error::BadDer)?,
+                            )?;
+                            if let Some(replacement) = replacement {
+                                push(replacement)?;
+                            }
+                            run_start = extensions.mark();
+                        }
+                    }
+                    push(
+                        extensions
+                            .get_input_between_marks(run_start, extensions.mark())
+                            .map_err(|_| Error::BadDer)?,
+                    )
+                })
+            })?;
+
+            let extensions_len: usize = chunks.iter().copied().map(<[u8]>::len).sum();
+            let extensions_headers = match extensions_len {
+                // An empty `Extensions` is not allowed, so omit the field.
+                0 => None,
+                _ => {
+                    let sequence = der::Header::new(der::Tag::Sequence, extensions_len)
+                        .ok_or(Error::BadDer)?;
+                    let tagged = der::Header::new(
+                        der::Tag::ContextSpecificConstructed3,
+                        sequence.as_ref().len() + extensions_len,
+                    )
+                    .ok_or(Error::BadDer)?;
+                    Some((tagged, sequence))
+                }
+            };
+            let tagged_len = extensions_headers
+                .as_ref()
+                .map(|(tagged, sequence)| {
+                    tagged.as_ref().len() + sequence.as_ref().len() + extensions_len
+                })
+                .unwrap_or(0);
+            let tbs_header = der::Header::new(
+                der::Tag::Sequence,
+                fields_before_issuer.len()
+                    + issuer_header.as_ref().len()
+                    + issuer.len()
+                    + fields_after_issuer.len()
+                    + tagged_len,
+            )
+            .ok_or(Error::BadDer)?;
+
+            Ok(PrecertTbsCertificate {
+                tbs_header,
+                fields_before_issuer: fields_before_issuer.as_slice_less_safe(),
+                issuer_header,
+                issuer: issuer.as_slice_less_safe(),
+                fields_after_issuer: fields_after_issuer.as_slice_less_safe(),
+                extensions_headers,
+                extensions: chunks,
+            })
+        })
+        .ok()
+}
+
+// Returns the DER encoding, including tag and length, of the extension `id`
+// of the certificate whose `TBSCertificate` is `tbs`, if it has one.
+fn find_extension<'a>(
+    tbs: &mut untrusted::Reader<'a>,
+    id: oid::ExtensionId,
+) -> Result<Option<untrusted::Input<'a>>, Error> {
+    // version, serialNumber, signature, issuer, validity, subject and
+    // subjectPublicKeyInfo.
+    skip_fields(tbs, 7)?;
+    let mut found = None;
+    if !tbs.at_end() {
+        let extensions = der::expect_tag_and_get_value(tbs, der::Tag::ContextSpecificConstructed3)?;
+        extensions.read_all(Error::BadDer, |tagged| {
+            der::nested(tagged, der::Tag::Sequence, Error::BadDer, |extensions| {
+                while !extensions.at_end() {
+                    let (extension, extn_id) = read_extension(extensions)?;
+                    if id.matches(extn_id) {
+                        found = Some(extension);
+                    }
+                }
+                Ok(())
+            })
+        })?;
+    }
+    tbs.skip_to_end();
+    Ok(found)
+}
+
+// Reads an `Extension`, returning its DER encoding and its `extnID`.
+fn read_extension<'a>(
+    extensions: &mut untrusted::Reader<'a>,
+) -> Result<(untrusted::Input<'a>, untrusted::Input<'a>), Error> {
+    extensions.read_partial(|extensions| {
+        let extension = der::expect_tag_and_get_value(extensions, der::Tag::Sequence)?;
+        extension.read_all(Error::BadDer, |extension| {
+            let extn_id = der::expect_tag_and_get_value(extension, der::Tag::OID)?;
+            extension.skip_to_end();
+            Ok(extn_id)
+        })
+    })
+}
+
+fn skip_fields(tbs: &mut untrusted::Reader, count: usize) -> Result<(), Error> {
+    for _ in 0..count {
+        der::read_tag_and_get_value(tbs)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_extension() {
+        assert!(scts(None).next().is_none());
+    }
+
+    #[test]
+    fn empty_list() {
+        let ext = [0x04, 0x02, 0x00, 0x00];
+        assert!(scts(Some(untrusted::Input::from(&ext))).next().is_none());
+    }
+
+    #[test]
+    fn truncated_list() {
+        let ext = [0x04, 0x03, 0x00, 0x05, 0x00];
+        let mut scts = scts(Some(untrusted::Input::from(&ext)));
+        assert!(matches!(scts.next(), Some(Err(Error::BadSctList))));
+        assert!(scts.next().is_none());
+    }
+
+    #[test]
+    fn unsupported_version_is_skipped() {
+        #[rustfmt::skip]
+        let ext = [
+            0x04, 0x09,
+            0x00, 0x07,
+            // An SCT with an unknown version.
+            0x00, 0x02, 0x01, 0xff,
+            // A truncated v1 SCT.
+            0x00, 0x01, 0x00,
+        ];
+        let mut scts = scts(Some(untrusted::Input::from(&ext)));
+        assert!(matches!(
+            scts.next(),
+            Some(Err(Error::UnsupportedSctVersion))
+        ));
+        assert!(matches!(scts.next(), Some(Err(Error::BadSctList))));
+        assert!(scts.next().is_none());
+    }
+}
@@ -13,11 +13,13 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use crate::{der, Error};
+use core::{convert::TryFrom, fmt};
 use ring::signature;
 
 /// X.509 certificates and related items that are signed are almost always
 /// encoded in the format "tbs||signatureAlgorithm||signature". This structure
 /// captures this pattern.
+#[derive(Clone)]
 pub struct SignedData<'a> {
     /// The signed data. This would be `tbsCertificate` in the case of an X.509
     /// certificate, `tbsResponseData` in the case of an OCSP response, and the
@@ -96,6 +98,8 @@ pub(crate) fn verify_signed_data(
     supported_algorithms: &[&SignatureAlgorithm],
     spki_value: untrusted::Input,
     signed_data: &SignedData,
+    ecdsa_signatures: EcdsaSignatures,
+    algorithm_parameters: AlgorithmParameters,
 ) -> Result<(), Error> {
     // We need to verify the signature in `signed_data` using the public key
     // in `public_key`. In order to know which *ring* signature verification
@@ -119,13 +123,15 @@ pub(crate) fn verify_signed_data(
     let mut found_signature_alg_match = false;
     for supported_alg in supported_algorithms.iter().filter(|alg| {
         alg.signature_alg_id
-            .matches_algorithm_id_value(signed_data.algorithm)
+            .matches(signed_data.algorithm, algorithm_parameters)
     }) {
         match verify_signature(
             supported_alg,
             spki_value,
             signed_data.data,
             signed_data.signature,
+            ecdsa_signatures,
+            algorithm_parameters,
         ) {
             Err(Error::UnsupportedSignatureAlgorithmForPublicKey) => {
                 found_signature_alg_match = true;
@@ -144,25 +150,162 @@ pub(crate) fn verify_signed_data(
     }
 }
 
+// Whether one of `supported_algorithms` matches the algorithms of
+// `signed_data` and of the key `spki_value`, without verifying the
+// signature.
+#[cfg(feature = "alloc")]
+pub(crate) fn is_supported_signature(
+    supported_algorithms: &[&SignatureAlgorithm],
+    spki_value: untrusted::Input,
+    signed_data: &SignedData,
+    algorithm_parameters: AlgorithmParameters,
+) -> bool {
+    let spki = match parse_spki_value(spki_value) {
+        Ok(spki) => spki,
+        Err(_) => return false,
+    };
+    supported_algorithms.iter().any(|alg| {
+        alg.signature_alg_id
+            .matches(signed_data.algorithm, algorithm_parameters)
+            && alg
+                .public_key_alg_id
+                .matches(spki.algorithm_id_value, algorithm_parameters)
+    })
+}
+
+/// How strictly the encoding of ECDSA signatures is checked.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum EcdsaSignatures {
+    /// Only DER-encoded signatures are accepted.
+    Der,
+
+    /// The integers of a signature may have superfluous leading zeros, or
+    /// lack the leading zero that keeps them positive.
+    Lenient,
+}
+
+/// How the parameters of algorithm identifiers are matched.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum AlgorithmParameters {
+    /// Parameters must be encoded exactly as specified: NULL for
+    /// rsaEncryption and the RSA PKCS#1 signature algorithms (RFC 4055
+    /// Section 5), and absent for the ECDSA signature algorithms (RFC 5758
+    /// Section 3.2) and Ed25519 (RFC 8410 Section 3).
+    Strict,
+
+    /// NULL parameters and absent parameters are interchangeable.
+    Compatible,
+}
+
 pub(crate) fn verify_signature(
     signature_alg: &SignatureAlgorithm,
     spki_value: untrusted::Input,
     msg: untrusted::Input,
     signature: untrusted::Input,
+    ecdsa_signatures: EcdsaSignatures,
+    algorithm_parameters: AlgorithmParameters,
 ) -> Result<(), Error> {
     let spki = parse_spki_value(spki_value)?;
     if !signature_alg
         .public_key_alg_id
-        .matches_algorithm_id_value(spki.algorithm_id_value)
+        .matches(spki.algorithm_id_value, algorithm_parameters)
     {
+        // X25519 and X448 keys are for key agreement only, so no signature
+        // algorithm could ever match them.
+        if X25519.matches_algorithm_id_value(spki.algorithm_id_value)
+            || X448.matches_algorithm_id_value(spki.algorithm_id_value)
+        {
+            return Err(Error::KeyTypeNotUsableForSignatures);
+        }
         return Err(Error::UnsupportedSignatureAlgorithmForPublicKey);
     }
-    signature::UnparsedPublicKey::new(
-        signature_alg.verification_alg,
-        spki.key_value.as_slice_less_safe(),
-    )
-    .verify(msg.as_slice_less_safe(), signature.as_slice_less_safe())
-    .map_err(|_| Error::InvalidSignatureForPublicKey)
+
+    let mut der_signature = [0; MAX_ECDSA_SIGNATURE_LEN];
+    let signature = match ecdsa_signatures {
+        EcdsaSignatures::Lenient if signature_alg.is_ecdsa() => {
+            ecdsa_signature_to_der(signature, &mut der_signature)
+                .ok_or(Error::InvalidSignatureForPublicKey)?
+        }
+        _ => signature.as_slice_less_safe(),
+    };
+
+    match signature_alg.verification_alg {
+        VerificationAlgorithm::Ring(verification_alg) => {
+            signature::UnparsedPublicKey::new(verification_alg, spki.key_value.as_slice_less_safe())
+                .verify(msg.as_slice_less_safe(), signature)
+                .map_err(|_| Error::InvalidSignatureForPublicKey)
+        }
+        VerificationAlgorithm::Custom(verify) => verify(
+            spki.key_value.as_slice_less_safe(),
+            msg.as_slice_less_safe(),
+            signature,
+        ),
+    }
+}
+
+/// Returns whether `signed_data` and the public key `spki_value` have the
+/// parameters of some algorithm in `supported_algorithms` encoded exactly as
+/// specified; see `AlgorithmParameters::Strict`.
+pub(crate) fn has_strict_algorithm_parameters(
+    supported_algorithms: &[&SignatureAlgorithm],
+    spki_value: untrusted::Input,
+    signed_data: &SignedData,
+) -> bool {
+    let spki = match parse_spki_value(spki_value) {
+        Ok(spki) => spki,
+        Err(_) => return false,
+    };
+    supported_algorithms.iter().any(|alg| {
+        alg.signature_alg_id
+            .matches_algorithm_id_value(signed_data.algorithm)
+            && alg
+                .public_key_alg_id
+                .matches_algorithm_id_value(spki.algorithm_id_value)
+    })
+}
+
+// The largest supported curve is P-384, so each integer of a DER-encoded
+// signature has at most 48 bytes, plus a leading zero.
+const MAX_ECDSA_INTEGER_LEN: usize = 48;
+const MAX_ECDSA_SIGNATURE_LEN: usize = 2 + 2 * (2 + 1 + MAX_ECDSA_INTEGER_LEN);
+
+// Re-encodes the ECDSA-Sig-Value `signature`, whose integers may not be
+// minimally encoded, as DER into `out`.
+fn ecdsa_signature_to_der<'o>(
+    signature: untrusted::Input,
+    out: &'o mut [u8; MAX_ECDSA_SIGNATURE_LEN],
+) -> Option<&'o [u8]> {
+    let (r, s) = signature
+        .read_all(Error::BadDer, |input| {
+            der::nested(input, der::Tag::Sequence, Error::BadDer, |input| {
+                let r = der::expect_tag_and_get_value(input, der::Tag::Integer)?;
+                let s = der::expect_tag_and_get_value(input, der::Tag::Integer)?;
+                Ok((r, s))
+            })
+        })
+        .ok()?;
+
+    let mut len = 2;
+    for integer in &[r, s] {
+        let mut integer = integer.as_slice_less_safe();
+        while let [0, rest @ ..] = integer {
+            integer = rest;
+        }
+        let (first, _) = integer.split_first()?;
+        if integer.len() > MAX_ECDSA_INTEGER_LEN {
+            return None;
+        }
+        let padding = usize::from(first & 0x80 != 0);
+
+        out[len] = der::Tag::Integer.into();
+        out[len + 1] = u8::try_from(padding + integer.len()).ok()?;
+        out[len + 2] = 0;
+        out[len + 2 + padding..len + 2 + padding + integer.len()].copy_from_slice(integer);
+        len += 2 + padding + integer.len();
+    }
+    out[0] = der::Tag::Sequence.into();
+    out[1] = u8::try_from(len - 2).ok()?;
+    Some(&out[..len])
 }
 
 struct SubjectPublicKeyInfo<'a> {
@@ -185,39 +328,281 @@ fn parse_spki_value(input: untrusted::Input) -> Result<SubjectPublicKeyInfo, Err
     })
 }
 
+/// Returns the size in bits of the public key in the SubjectPublicKeyInfo
+/// `spki_value`: the size of the modulus of an RSA key, or of the curve of
+/// an elliptic curve key. Returns `None` for unsupported algorithms.
+pub(crate) fn public_key_bits(spki_value: untrusted::Input) -> Option<usize> {
+    let spki = parse_spki_value(spki_value).ok()?;
+    if ECDSA_P256.matches_algorithm_id_value(spki.algorithm_id_value)
+        || ED_25519.matches_algorithm_id_value(spki.algorithm_id_value)
+    {
+        Some(256)
+    } else if ECDSA_P384.matches_algorithm_id_value(spki.algorithm_id_value) {
+        Some(384)
+    } else if RSA_ENCRYPTION.matches_algorithm_id_value(spki.algorithm_id_value) {
+        let (modulus, _) = parse_rsa_public_key(spki.key_value)?;
+        let (first, _) = modulus.split_first()?;
+        #[allow(clippy::as_conversions)] // At most 8.
+        let leading_zeros = first.leading_zeros() as usize;
+        Some(modulus.len() * 8 - leading_zeros)
+    } else {
+        None
+    }
+}
+
+/// Returns the kind of the public key in the SubjectPublicKeyInfo
+/// `spki_value`.
+pub(crate) fn public_key_kind(spki_value: untrusted::Input) -> KeyKind {
+    let algorithm_id_value = match parse_spki_value(spki_value) {
+        Ok(spki) => spki.algorithm_id_value,
+        Err(_) => return KeyKind::Other,
+    };
+    if ECDSA_P256.matches_algorithm_id_value(algorithm_id_value) {
+        KeyKind::EcdsaP256
+    } else if ECDSA_P384.matches_algorithm_id_value(algorithm_id_value) {
+        KeyKind::EcdsaP384
+    } else if ED_25519.matches_algorithm_id_value(algorithm_id_value) {
+        KeyKind::Ed25519
+    } else if RSA_ENCRYPTION.matches_algorithm_id_value(algorithm_id_value) {
+        KeyKind::Rsa
+    } else {
+        KeyKind::Other
+    }
+}
+
+/// Returns the public exponent of the RSA key in the SubjectPublicKeyInfo
+/// `spki_value`, without leading zeros. Returns `None` for other keys.
+pub(crate) fn rsa_public_exponent(spki_value: untrusted::Input) -> Option<&[u8]> {
+    let spki = parse_spki_value(spki_value).ok()?;
+    if !RSA_ENCRYPTION.matches_algorithm_id_value(spki.algorithm_id_value) {
+        return None;
+    }
+    let (_, public_exponent) = parse_rsa_public_key(spki.key_value)?;
+    Some(public_exponent)
+}
+
+// RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+//
+// Returns the modulus and the public exponent, without leading zeros.
+fn parse_rsa_public_key(key_value: untrusted::Input) -> Option<(&[u8], &[u8])> {
+    fn without_leading_zeros(mut integer: &[u8]) -> &[u8] {
+        while let [0, rest @ ..] = integer {
+            integer = rest;
+        }
+        integer
+    }
+
+    let (modulus, public_exponent) = key_value
+        .read_all(Error::BadDer, |input| {
+            der::nested(input, der::Tag::Sequence, Error::BadDer, |input| {
+                let modulus = der::expect_tag_and_get_value(input, der::Tag::Integer)?;
+                let public_exponent = der::expect_tag_and_get_value(input, der::Tag::Integer)?;
+                Ok((modulus, public_exponent))
+            })
+        })
+        .ok()?;
+    Some((
+        without_leading_zeros(modulus.as_slice_less_safe()),
+        without_leading_zeros(public_exponent.as_slice_less_safe()),
+    ))
+}
+
 /// A signature algorithm.
 pub struct SignatureAlgorithm {
+    name: &'static str,
+    key_kind: KeyKind,
+    digest: Option<Digest>,
     public_key_alg_id: AlgorithmIdentifier,
     signature_alg_id: AlgorithmIdentifier,
-    verification_alg: &'static dyn signature::VerificationAlgorithm,
+    verification_alg: VerificationAlgorithm,
+}
+
+#[derive(Clone, Copy)]
+enum VerificationAlgorithm {
+    Ring(&'static dyn signature::VerificationAlgorithm),
+    Custom(fn(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), Error>),
 }
 
+impl SignatureAlgorithm {
+    /// Defines a signature algorithm that isn't built into this crate, e.g.
+    /// a GOST or other national algorithm.
+    ///
+    /// `public_key_alg_id` and `signature_alg_id` are the contents of the
+    /// DER-encoded `AlgorithmIdentifier` SEQUENCEs, i.e. the algorithm OID
+    /// followed by the parameters, if any. They are compared byte-for-byte
+    /// with the subjectPublicKeyInfo algorithm and the signature algorithm of
+    /// signed data, so every accepted encoding of the parameters needs its own
+    /// `SignatureAlgorithm`; see src/data/README.md for how the built-in ones
+    /// are generated.
+    ///
+    /// `verify` is called with the contents of the subjectPublicKeyInfo
+    /// subjectPublicKey BIT STRING, the signed message and the signature. It
+    /// should return `Err(Error::InvalidSignatureForPublicKey)` if the
+    /// signature doesn't verify.
+    ///
+    /// The `key_kind()` of the algorithm is `KeyKind::Other`, and its
+    /// `digest()` is `None`.
+    pub fn custom(
+        name: &'static str,
+        public_key_alg_id: &'static [u8],
+        signature_alg_id: &'static [u8],
+        verify: fn(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), Error>,
+    ) -> Self {
+        Self {
+            name,
+            key_kind: KeyKind::Other,
+            digest: None,
+            public_key_alg_id: AlgorithmIdentifier {
+                asn1_id_value: untrusted::Input::from(public_key_alg_id),
+            },
+            signature_alg_id: AlgorithmIdentifier {
+                asn1_id_value: untrusted::Input::from(signature_alg_id),
+            },
+            verification_alg: VerificationAlgorithm::Custom(verify),
+        }
+    }
+
+    /// The name of the algorithm's constant in this crate, e.g.
+    /// `"ECDSA_P256_SHA256"`, or the name given to `SignatureAlgorithm::custom`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The kind of public key the algorithm verifies signatures with.
+    pub fn key_kind(&self) -> KeyKind {
+        self.key_kind
+    }
+
+    // Returns the contents of the subjectPublicKeyInfo and signature
+    // `AlgorithmIdentifier`s the algorithm accepts, in that order.
+    pub(crate) fn alg_ids(&self) -> [&'static [u8]; 2] {
+        [
+            self.public_key_alg_id.asn1_id_value.as_slice_less_safe(),
+            self.signature_alg_id.asn1_id_value.as_slice_less_safe(),
+        ]
+    }
+
+    /// The digest algorithm the signed message is hashed with, or `None` for
+    /// algorithms like Ed25519 that sign the message itself, and for custom
+    /// algorithms.
+    pub fn digest(&self) -> Option<Digest> {
+        self.digest
+    }
+
+    fn is_ecdsa(&self) -> bool {
+        self.public_key_alg_id
+            .matches_algorithm_id_value(ECDSA_P256.asn1_id_value)
+            || self
+                .public_key_alg_id
+                .matches_algorithm_id_value(ECDSA_P384.asn1_id_value)
+    }
+}
+
+impl fmt::Debug for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// The kind of public key of a `SignatureAlgorithm` or of a certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyKind {
+    /// An ECDSA key on the P-256 curve.
+    EcdsaP256,
+
+    /// An ECDSA key on the P-384 curve.
+    EcdsaP384,
+
+    /// An Ed25519 key.
+    Ed25519,
+
+    /// An RSA key.
+    Rsa,
+
+    /// Any other key, e.g. that of an algorithm defined with
+    /// `SignatureAlgorithm::custom`.
+    Other,
+}
+
+/// The digest algorithm of a `SignatureAlgorithm`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Digest {
+    /// SHA-256.
+    Sha256,
+
+    /// SHA-384.
+    Sha384,
+
+    /// SHA-512.
+    Sha512,
+}
+
+/// All the signature algorithms supported with the enabled features, for
+/// building allow-lists from configuration, e.g. by `SignatureAlgorithm::name`.
+///
+/// The RSA algorithms are only included with the `alloc` feature.
+pub static ALL_SIGNATURE_ALGORITHMS: &[&SignatureAlgorithm] = &[
+    &ECDSA_P256_SHA256,
+    &ECDSA_P256_SHA384,
+    &ECDSA_P384_SHA256,
+    &ECDSA_P384_SHA384,
+    &ED25519,
+    #[cfg(feature = "alloc")]
+    &RSA_PKCS1_2048_8192_SHA256,
+    #[cfg(feature = "alloc")]
+    &RSA_PKCS1_2048_8192_SHA384,
+    #[cfg(feature = "alloc")]
+    &RSA_PKCS1_2048_8192_SHA512,
+    #[cfg(feature = "alloc")]
+    &RSA_PKCS1_3072_8192_SHA384,
+    #[cfg(feature = "alloc")]
+    &RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    #[cfg(feature = "alloc")]
+    &RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    #[cfg(feature = "alloc")]
+    &RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+];
+
 /// ECDSA signatures using the P-256 curve and SHA-256.
 pub static ECDSA_P256_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    name: "ECDSA_P256_SHA256",
+    key_kind: KeyKind::EcdsaP256,
+    digest: Some(Digest::Sha256),
     public_key_alg_id: ECDSA_P256,
     signature_alg_id: ECDSA_SHA256,
-    verification_alg: &signature::ECDSA_P256_SHA256_ASN1,
+    verification_alg: VerificationAlgorithm::Ring(&signature::ECDSA_P256_SHA256_ASN1),
 };
 
 /// ECDSA signatures using the P-256 curve and SHA-384. Deprecated.
 pub static ECDSA_P256_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    name: "ECDSA_P256_SHA384",
+    key_kind: KeyKind::EcdsaP256,
+    digest: Some(Digest::Sha384),
     public_key_alg_id: ECDSA_P256,
     signature_alg_id: ECDSA_SHA384,
-    verification_alg: &signature::ECDSA_P256_SHA384_ASN1,
+    verification_alg: VerificationAlgorithm::Ring(&signature::ECDSA_P256_SHA384_ASN1),
 };
 
 /// ECDSA signatures using the P-384 curve and SHA-256. Deprecated.
 pub static ECDSA_P384_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    name: "ECDSA_P384_SHA256",
+    key_kind: KeyKind::EcdsaP384,
+    digest: Some(Digest::Sha256),
     public_key_alg_id: ECDSA_P384,
     signature_alg_id: ECDSA_SHA256,
-    verification_alg: &signature::ECDSA_P384_SHA256_ASN1,
+    verification_alg: VerificationAlgorithm::Ring(&signature::ECDSA_P384_SHA256_ASN1),
 };
 
 /// ECDSA signatures using the P-384 curve and SHA-384.
 pub static ECDSA_P384_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    name: "ECDSA_P384_SHA384",
+    key_kind: KeyKind::EcdsaP384,
+    digest: Some(Digest::Sha384),
     public_key_alg_id: ECDSA_P384,
     signature_alg_id: ECDSA_SHA384,
-    verification_alg: &signature::ECDSA_P384_SHA384_ASN1,
+    verification_alg: VerificationAlgorithm::Ring(&signature::ECDSA_P384_SHA384_ASN1),
 };
 
 /// RSA PKCS#1 1.5 signatures using SHA-256 for keys of 2048-8192 bits.
@@ -225,9 +610,12 @@ pub static ECDSA_P384_SHA384: SignatureAlgorithm = SignatureAlgorithm {
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PKCS1_2048_8192_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PKCS1_2048_8192_SHA256",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha256),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PKCS1_SHA256,
-    verification_alg: &signature::RSA_PKCS1_2048_8192_SHA256,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PKCS1_2048_8192_SHA256),
 };
 
 /// RSA PKCS#1 1.5 signatures using SHA-384 for keys of 2048-8192 bits.
@@ -235,9 +623,12 @@ pub static RSA_PKCS1_2048_8192_SHA256: SignatureAlgorithm = SignatureAlgorithm {
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PKCS1_2048_8192_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PKCS1_2048_8192_SHA384",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha384),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PKCS1_SHA384,
-    verification_alg: &signature::RSA_PKCS1_2048_8192_SHA384,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PKCS1_2048_8192_SHA384),
 };
 
 /// RSA PKCS#1 1.5 signatures using SHA-512 for keys of 2048-8192 bits.
@@ -245,9 +636,12 @@ pub static RSA_PKCS1_2048_8192_SHA384: SignatureAlgorithm = SignatureAlgorithm {
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PKCS1_2048_8192_SHA512: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PKCS1_2048_8192_SHA512",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha512),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PKCS1_SHA512,
-    verification_alg: &signature::RSA_PKCS1_2048_8192_SHA512,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PKCS1_2048_8192_SHA512),
 };
 
 /// RSA PKCS#1 1.5 signatures using SHA-384 for keys of 3072-8192 bits.
@@ -255,9 +649,12 @@ pub static RSA_PKCS1_2048_8192_SHA512: SignatureAlgorithm = SignatureAlgorithm {
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PKCS1_3072_8192_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PKCS1_3072_8192_SHA384",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha384),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PKCS1_SHA384,
-    verification_alg: &signature::RSA_PKCS1_3072_8192_SHA384,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PKCS1_3072_8192_SHA384),
 };
 
 /// RSA PSS signatures using SHA-256 for keys of 2048-8192 bits and of
@@ -268,9 +665,12 @@ pub static RSA_PKCS1_3072_8192_SHA384: SignatureAlgorithm = SignatureAlgorithm {
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PSS_2048_8192_SHA256_LEGACY_KEY: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PSS_2048_8192_SHA256_LEGACY_KEY",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha256),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PSS_SHA256,
-    verification_alg: &signature::RSA_PSS_2048_8192_SHA256,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PSS_2048_8192_SHA256),
 };
 
 /// RSA PSS signatures using SHA-384 for keys of 2048-8192 bits and of
@@ -281,9 +681,12 @@ pub static RSA_PSS_2048_8192_SHA256_LEGACY_KEY: SignatureAlgorithm = SignatureAl
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PSS_2048_8192_SHA384_LEGACY_KEY: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PSS_2048_8192_SHA384_LEGACY_KEY",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha384),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PSS_SHA384,
-    verification_alg: &signature::RSA_PSS_2048_8192_SHA384,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PSS_2048_8192_SHA384),
 };
 
 /// RSA PSS signatures using SHA-512 for keys of 2048-8192 bits and of
@@ -294,16 +697,22 @@ pub static RSA_PSS_2048_8192_SHA384_LEGACY_KEY: SignatureAlgorithm = SignatureAl
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub static RSA_PSS_2048_8192_SHA512_LEGACY_KEY: SignatureAlgorithm = SignatureAlgorithm {
+    name: "RSA_PSS_2048_8192_SHA512_LEGACY_KEY",
+    key_kind: KeyKind::Rsa,
+    digest: Some(Digest::Sha512),
     public_key_alg_id: RSA_ENCRYPTION,
     signature_alg_id: RSA_PSS_SHA512,
-    verification_alg: &signature::RSA_PSS_2048_8192_SHA512,
+    verification_alg: VerificationAlgorithm::Ring(&signature::RSA_PSS_2048_8192_SHA512),
 };
 
 /// ED25519 signatures according to RFC 8410
 pub static ED25519: SignatureAlgorithm = SignatureAlgorithm {
+    name: "ED25519",
+    key_kind: KeyKind::Ed25519,
+    digest: None,
     public_key_alg_id: ED_25519,
     signature_alg_id: ED_25519,
-    verification_alg: &signature::ED25519,
+    verification_alg: VerificationAlgorithm::Ring(&signature::ED25519),
 };
 
 struct AlgorithmIdentifier {
@@ -314,6 +723,36 @@ impl AlgorithmIdentifier {
     fn matches_algorithm_id_value(&self, encoded: untrusted::Input) -> bool {
         encoded == self.asn1_id_value
     }
+
+    fn matches(&self, encoded: untrusted::Input, parameters: AlgorithmParameters) -> bool {
+        if self.matches_algorithm_id_value(encoded) {
+            return true;
+        }
+        if parameters == AlgorithmParameters::Strict {
+            return false;
+        }
+        match (
+            oid_if_null_or_absent_parameters(self.asn1_id_value),
+            oid_if_null_or_absent_parameters(encoded),
+        ) {
+            (Some(expected), Some(encoded)) => expected == encoded,
+            _ => false,
+        }
+    }
+}
+
+// Returns the algorithm OID of the `AlgorithmIdentifier` value `value` if its
+// parameters are NULL or absent.
+fn oid_if_null_or_absent_parameters(value: untrusted::Input) -> Option<untrusted::Input> {
+    value
+        .read_all(Error::BadDer, |input| {
+            let oid = der::expect_tag_and_get_value(input, der::Tag::OID)?;
+            if !input.at_end() && input.read_bytes_to_end().as_slice_less_safe() != [0x05, 0x00] {
+                return Err(Error::BadDer);
+            }
+            Ok(oid)
+        })
+        .ok()
 }
 
 // See src/data/README.md.
@@ -334,7 +773,6 @@ const ECDSA_SHA384: AlgorithmIdentifier = AlgorithmIdentifier {
     asn1_id_value: untrusted::Input::from(include_bytes!("data/alg-ecdsa-sha384.der")),
 };
 
-#[cfg(feature = "alloc")]
 const RSA_ENCRYPTION: AlgorithmIdentifier = AlgorithmIdentifier {
     asn1_id_value: untrusted::Input::from(include_bytes!("data/alg-rsa-encryption.der")),
 };
@@ -373,6 +811,14 @@ const ED_25519: AlgorithmIdentifier = AlgorithmIdentifier {
     asn1_id_value: untrusted::Input::from(include_bytes!("data/alg-ed25519.der")),
 };
 
+const X25519: AlgorithmIdentifier = AlgorithmIdentifier {
+    asn1_id_value: untrusted::Input::from(include_bytes!("data/alg-x25519.der")),
+};
+
+const X448: AlgorithmIdentifier = AlgorithmIdentifier {
+    asn1_id_value: untrusted::Input::from(include_bytes!("data/alg-x448.der")),
+};
+
 #[cfg(test)]
 mod tests {
     use crate::{der, signed_data, Error};
@@ -438,7 +884,9 @@ mod tests {
             signed_data::verify_signed_data(
                 SUPPORTED_ALGORITHMS_IN_TESTS,
                 spki_value,
-                &signed_data
+                &signed_data,
+                signed_data::EcdsaSignatures::Der,
+                signed_data::AlgorithmParameters::Strict,
             )
         );
     }
@@ -785,4 +1233,100 @@ mod tests {
         &signed_data::ECDSA_P256_SHA384, // Truncates digest.
         &signed_data::ECDSA_P384_SHA256, // Digest is unnecessarily short.
     ];
+
+    #[test]
+    fn ecdsa_signature_to_der_test() {
+        let to_der = |signature: &[u8]| {
+            let mut out = [0; signed_data::MAX_ECDSA_SIGNATURE_LEN];
+            signed_data::ecdsa_signature_to_der(untrusted::Input::from(signature), &mut out)
+                .map(|der| der.to_vec())
+        };
+
+        // Already DER.
+        assert_eq!(
+            to_der(&[0x30, 0x07, 0x02, 0x02, 0x00, 0x80, 0x02, 0x01, 0x01]),
+            Some(vec![0x30, 0x07, 0x02, 0x02, 0x00, 0x80, 0x02, 0x01, 0x01])
+        );
+        // Superfluous leading zeros.
+        assert_eq!(
+            to_der(&[0x30, 0x08, 0x02, 0x03, 0x00, 0x00, 0x01, 0x02, 0x01, 0x02]),
+            Some(vec![0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02])
+        );
+        // A missing leading zero.
+        assert_eq!(
+            to_der(&[0x30, 0x06, 0x02, 0x01, 0x80, 0x02, 0x01, 0xff]),
+            Some(vec![
+                0x30, 0x08, 0x02, 0x02, 0x00, 0x80, 0x02, 0x02, 0x00, 0xff
+            ])
+        );
+        // Zero, and integers too long for any supported curve.
+        assert_eq!(
+            to_der(&[0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x01]),
+            None
+        );
+        let mut too_long = vec![0x30, 0x36, 0x02, 0x31];
+        too_long.extend_from_slice(&[0x01; 0x31]);
+        too_long.extend_from_slice(&[0x02, 0x01, 0x01]);
+        assert_eq!(to_der(&too_long), None);
+        // Not an ECDSA-Sig-Value.
+        assert_eq!(to_der(&[0x30, 0x03, 0x02, 0x01, 0x01]), None);
+    }
+
+    #[test]
+    fn algorithm_parameters_test() {
+        use signed_data::AlgorithmParameters::{Compatible, Strict};
+
+        let matches = |alg: &signed_data::AlgorithmIdentifier, encoded: &[u8]| {
+            let encoded = untrusted::Input::from(encoded);
+            (
+                alg.matches(encoded, Strict),
+                alg.matches(encoded, Compatible),
+            )
+        };
+
+        const ECDSA_WITH_SHA256: &[u8] =
+            &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+        const RSA_ENCRYPTION: &[u8] = &[
+            0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01,
+        ];
+        let with_null = |oid: &[u8]| [oid, &[0x05, 0x00][..]].concat();
+
+        // Absent parameters are specified for ECDSA, and NULL for RSA.
+        assert_eq!(
+            matches(&signed_data::ECDSA_SHA256, ECDSA_WITH_SHA256),
+            (true, true)
+        );
+        assert_eq!(
+            matches(&signed_data::ECDSA_SHA256, &with_null(ECDSA_WITH_SHA256)),
+            (false, true)
+        );
+        assert_eq!(
+            matches(&signed_data::RSA_ENCRYPTION, &with_null(RSA_ENCRYPTION)),
+            (true, true)
+        );
+        assert_eq!(
+            matches(&signed_data::RSA_ENCRYPTION, RSA_ENCRYPTION),
+            (false, true)
+        );
+
+        // Other parameters, and other algorithms, never match.
+        assert_eq!(
+            matches(
+                &signed_data::ECDSA_SHA256,
+                &[ECDSA_WITH_SHA256, &[0x05, 0x01, 0x00][..]].concat()
+            ),
+            (false, false)
+        );
+        assert_eq!(
+            matches(&signed_data::ECDSA_SHA384, &with_null(ECDSA_WITH_SHA256)),
+            (false, false)
+        );
+        assert_eq!(
+            matches(
+                &signed_data::ECDSA_P256,
+                &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]
+            ),
+            (false, false)
+        );
+    }
 }
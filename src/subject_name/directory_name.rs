@@ -0,0 +1,640 @@
+// Copyright 2015-2020 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use crate::{der, oid, Error};
+use core::{convert::TryFrom, fmt};
+
+/// An X.501 `Name`, such as a certificate's subject or issuer, or a trust
+/// anchor's subject.
+///
+/// `PartialEq` compares the encodings byte for byte; `eq_rfc5280` compares
+/// names the way issuers are matched to the certificates they issue.
+///
+/// `Display` renders the name as an RFC 4514 string, e.g.
+/// `CN=www.example.com,O=Example Corp,C=US`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DistinguishedName<'a>(&'a [u8]);
+
+impl<'a> TryFrom<&'a [u8]> for DistinguishedName<'a> {
+    type Error = Error;
+
+    /// Parses the value (without tag and length) of a `Name` SEQUENCE, as
+    /// returned by `Cert::subject` and `Cert::issuer`.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        untrusted::Input::from(value).read_all(Error::BadDer, |name| {
+            while !name.at_end() {
+                let rdn = der::expect_tag_and_get_value(name, der::Tag::Set)?;
+                if attribute_count(rdn)? == 0 {
+                    return Err(Error::BadDer);
+                }
+            }
+            Ok(())
+        })?;
+        Ok(Self(value))
+    }
+}
+
+impl<'a> DistinguishedName<'a> {
+    /// Returns the value (without tag and length) of the `Name` SEQUENCE.
+    pub fn as_der(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Returns the relative distinguished names, most significant (e.g.
+    /// the country) first, as they are encoded.
+    pub fn rdns(&self) -> Rdns<'a> {
+        Rdns(self.0)
+    }
+
+    /// Returns whether the names are equal under the comparison rules of RFC
+    /// 5280 section 7.1: they have the same RDNs in the same order, and the
+    /// attributes of each RDN are equal in any order.
+    ///
+    /// PrintableString and UTF8String values are equal regardless of which
+    /// of the two encodings each uses, ignoring ASCII case, leading and
+    /// trailing whitespace, and the length of runs of whitespace. Values of
+    /// other types must be byte-for-byte equal.
+    pub fn eq_rfc5280(&self, other: &DistinguishedName) -> bool {
+        names_match(
+            untrusted::Input::from(self.0),
+            untrusted::Input::from(other.0),
+        )
+    }
+}
+
+impl fmt::Display for DistinguishedName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // RFC 4514 puts the least significant RDN first.
+        let count = self.rdns().count();
+        for (i, rdn) in (0..count)
+            .rev()
+            .filter_map(|i| self.rdns().nth(i))
+            .enumerate()
+        {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            for (j, attribute) in rdn.attributes().enumerate() {
+                if j > 0 {
+                    f.write_str("+")?;
+                }
+                write!(f, "{}", attribute)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the relative distinguished names of a
+/// `DistinguishedName`.
+#[derive(Clone, Debug)]
+pub struct Rdns<'a>(&'a [u8]);
+
+impl<'a> Iterator for Rdns<'a> {
+    type Item = RelativeDistinguishedName<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (rdn, rest) = split_first(self.0, |reader| {
+            der::expect_tag_and_get_value(reader, der::Tag::Set)
+        })?;
+        self.0 = rest;
+        Some(RelativeDistinguishedName(rdn.as_slice_less_safe()))
+    }
+}
+
+/// One of the relative distinguished names of a `DistinguishedName`: a set
+/// of attributes, usually just one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RelativeDistinguishedName<'a>(&'a [u8]);
+
+impl<'a> RelativeDistinguishedName<'a> {
+    /// Returns the attributes, in the order they are encoded.
+    pub fn attributes(&self) -> Attributes<'a> {
+        Attributes(self.0)
+    }
+}
+
+/// An iterator over the attributes of a `RelativeDistinguishedName`.
+#[derive(Clone, Debug)]
+pub struct Attributes<'a>(&'a [u8]);
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = AttributeTypeAndValue<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (attribute, rest) = split_first(self.0, attribute_type_and_value)?;
+        self.0 = rest;
+        Some(attribute)
+    }
+}
+
+// Reads the first element of `input` with `read`, returning it and the
+// rest of `input`. The names are validated when `DistinguishedName` is
+// constructed, so a failure can only mean the end of `input`.
+fn split_first<'a, T>(
+    input: &'a [u8],
+    read: impl FnOnce(&mut untrusted::Reader<'a>) -> Result<T, Error>,
+) -> Option<(T, &'a [u8])> {
+    let mut reader = untrusted::Reader::new(untrusted::Input::from(input));
+    let first = read(&mut reader).ok()?;
+    let rest = reader.read_bytes_to_end().as_slice_less_safe();
+    Some((first, rest))
+}
+
+/// An attribute of a `RelativeDistinguishedName`, e.g. `CN=www.example.com`.
+///
+/// `Display` renders the attribute as in an RFC 4514 string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AttributeTypeAndValue<'a> {
+    type_: &'a [u8],
+    value_tag: u8,
+    value: &'a [u8],
+}
+
+impl<'a> AttributeTypeAndValue<'a> {
+    /// Returns the value (without tag and length) of the DER encoding of the
+    /// attribute type's object identifier, e.g. `oid::COMMON_NAME.value()`.
+    pub fn oid(&self) -> &'a [u8] {
+        self.type_
+    }
+
+    /// Returns the tag of the attribute value, e.g. 0x0C for a UTF8String.
+    pub fn value_tag(&self) -> u8 {
+        self.value_tag
+    }
+
+    /// Returns the attribute value, without tag and length.
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Returns the attribute value if it is a PrintableString, UTF8String
+    /// or IA5String of valid UTF-8.
+    pub fn value_str(&self) -> Option<&'a str> {
+        if is_string(self.value_tag) || self.value_tag == IA5_STRING {
+            core::str::from_utf8(self.value).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for AttributeTypeAndValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match SHORT_NAMES
+            .iter()
+            .find(|(type_, _)| type_.value() == self.type_)
+        {
+            Some((_, short_name)) => f.write_str(short_name)?,
+            None => oid::write_dotted_decimal(f, self.type_)?,
+        }
+        f.write_str("=")?;
+
+        let value = match self.value_str() {
+            Some(value) => value,
+            None => {
+                // RFC 4514 section 2.4: the hexadecimal DER encoding of the
+                // value.
+                write!(f, "#{:02x}", self.value_tag)?;
+                let len = self.value.len();
+                if len < 0x80 {
+                    write!(f, "{:02x}", len)?;
+                } else {
+                    let len = len.to_be_bytes();
+                    let len = &len[len.iter().take_while(|&&byte| byte == 0).count()..];
+                    write!(f, "{:02x}", 0x80 + len.len())?;
+                    for byte in len {
+                        write!(f, "{:02x}", byte)?;
+                    }
+                }
+                for byte in self.value {
+                    write!(f, "{:02x}", byte)?;
+                }
+                return Ok(());
+            }
+        };
+
+        let last = value.chars().count().saturating_sub(1);
+        for (i, c) in value.chars().enumerate() {
+            match c {
+                '"' | '+' | ',' | ';' | '<' | '>' | '\\' => write!(f, "\\{}", c)?,
+                '#' if i == 0 => f.write_str("\\#")?,
+                ' ' if i == 0 || i == last => f.write_str("\\ ")?,
+                '\0' => f.write_str("\\00")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// The attribute types with short names in RFC 4514 section 3.
+const SHORT_NAMES: &[(oid::Oid, &str)] = &[
+    (oid::COMMON_NAME, "CN"),
+    (oid::Oid::new(&oid![2, 5, 4, 7]), "L"),
+    (oid::Oid::new(&oid![2, 5, 4, 8]), "ST"),
+    (oid::Oid::new(&oid![2, 5, 4, 10]), "O"),
+    (oid::Oid::new(&oid![2, 5, 4, 11]), "OU"),
+    (oid::Oid::new(&oid![2, 5, 4, 6]), "C"),
+    (oid::Oid::new(&oid![2, 5, 4, 9]), "STREET"),
+    (
+        oid::Oid::new(&oid![0, 9, 0x92, 0x26, 0x89, 0x93, 0xf2, 0x2c, 0x64, 1, 25]),
+        "DC",
+    ),
+    (
+        oid::Oid::new(&oid![0, 9, 0x92, 0x26, 0x89, 0x93, 0xf2, 0x2c, 0x64, 1, 1]),
+        "UID",
+    ),
+];
+
+const IA5_STRING: u8 = 0x16;
+
+// Whether the issuer name `issuer` of a certificate matches the subject name
+// `subject` of a potential issuer, under the comparison rules of RFC 5280
+// section 7.1. Both are the contents of the `Name` SEQUENCEs.
+pub(crate) fn names_match(issuer: untrusted::Input, subject: untrusted::Input) -> bool {
+    if issuer == subject {
+        return true;
+    }
+    let rdn_count = |name: untrusted::Input| {
+        name.read_all(Error::BadDer, |name| {
+            let mut count = 0;
+            while !name.at_end() {
+                der::expect_tag_and_get_value(name, der::Tag::Set)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    };
+    match (rdn_count(issuer), rdn_count(subject)) {
+        (Ok(a), Ok(b)) if a == b => presented_id_matches_constraint(issuer, subject) == Ok(true),
+        _ => false,
+    }
+}
+
+// https://tools.ietf.org/html/rfc5280#section-4.2.1.10 says:
+//
+//     Restrictions of the form directoryName MUST be applied to the subject
+//     field in the certificate (when the certificate includes a non-empty
+//     subject field) and to any names of type directoryName in the
+//     subjectAltName extension.
+//
+// and https://tools.ietf.org/html/rfc5280#section-7.1 says that a name is
+// within a directoryName subtree when the subtree's RDNs are a prefix of the
+// name's RDNs, compared with the rules of RFC 4518.
+//
+// `name` and `constraint` are the contents of the `Name` SEQUENCEs, i.e. the
+// RDNs themselves.
+pub(super) fn presented_id_matches_constraint(
+    name: untrusted::Input,
+    constraint: untrusted::Input,
+) -> Result<bool, Error> {
+    let mut name = untrusted::Reader::new(name);
+    let mut constraint = untrusted::Reader::new(constraint);
+    while !constraint.at_end() {
+        let constraint_rdn = der::expect_tag_and_get_value(&mut constraint, der::Tag::Set)?;
+        if name.at_end() {
+            return Ok(false);
+        }
+        let name_rdn = der::expect_tag_and_get_value(&mut name, der::Tag::Set)?;
+        if !rdns_match(name_rdn, constraint_rdn)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Two RDNs match when they have the same number of attributes, and each
+// attribute of one matches an attribute of the other, in any order.
+fn rdns_match(a: untrusted::Input, b: untrusted::Input) -> Result<bool, Error> {
+    if attribute_count(a)? != attribute_count(b)? {
+        return Ok(false);
+    }
+
+    let mut b = untrusted::Reader::new(b);
+    while !b.at_end() {
+        let b_attribute = attribute_type_and_value(&mut b)?;
+        let mut a = untrusted::Reader::new(a);
+        let mut found = false;
+        while !a.at_end() {
+            if attributes_match(attribute_type_and_value(&mut a)?, b_attribute) {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn attribute_count(rdn: untrusted::Input) -> Result<usize, Error> {
+    let mut rdn = untrusted::Reader::new(rdn);
+    let mut count = 0;
+    while !rdn.at_end() {
+        attribute_type_and_value(&mut rdn)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn attribute_type_and_value<'a>(
+    rdn: &mut untrusted::Reader<'a>,
+) -> Result<AttributeTypeAndValue<'a>, Error> {
+    der::nested(rdn, der::Tag::Sequence, Error::BadDer, |attribute| {
+        let type_ = der::expect_tag_and_get_value(attribute, der::Tag::OID)?;
+        let (value_tag, value) = der::read_tag_and_get_value(attribute)?;
+        Ok(AttributeTypeAndValue {
+            type_: type_.as_slice_less_safe(),
+            value_tag,
+            value: value.as_slice_less_safe(),
+        })
+    })
+}
+
+// PrintableString and UTF8String values match regardless of which of the two
+// encodings each uses, ignoring ASCII case, leading and trailing whitespace,
+// and the length of runs of whitespace. Non-ASCII characters must be equal;
+// full Unicode case folding and normalization aren't done. Values of other
+// types must be byte-for-byte equal.
+fn attributes_match(a: AttributeTypeAndValue, b: AttributeTypeAndValue) -> bool {
+    if a.type_ != b.type_ {
+        return false;
+    }
+    if is_string(a.value_tag) && is_string(b.value_tag) {
+        strings_match(a.value, b.value)
+    } else {
+        a.value_tag == b.value_tag && a.value == b.value
+    }
+}
+
+fn is_string(tag: u8) -> bool {
+    tag == u8::from(der::Tag::PrintableString) || tag == u8::from(der::Tag::UTF8String)
+}
+
+fn strings_match(a: &[u8], b: &[u8]) -> bool {
+    let mut a = a
+        .split(u8::is_ascii_whitespace)
+        .filter(|word| !word.is_empty());
+    let mut b = b
+        .split(u8::is_ascii_whitespace)
+        .filter(|word| !word.is_empty());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => (),
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec::Vec};
+
+    const COUNTRY: &[u8] = &[0x55, 0x04, 0x06];
+    const ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0a];
+    const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+    const UNKNOWN: &[u8] = &[0x2a, 0x03, 0x04]; // 1.2.3.4
+
+    fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+        assert!(value.len() < 0x80);
+        let mut encoded = vec![tag, value.len() as u8];
+        encoded.extend_from_slice(value);
+        encoded
+    }
+
+    // Encodes an RDN with the given (type, value tag, value) attributes.
+    fn rdn(attributes: &[(&[u8], u8, &str)]) -> Vec<u8> {
+        let mut value = Vec::new();
+        for (type_, value_tag, attribute_value) in attributes {
+            let mut attribute = tlv(0x06, type_);
+            attribute.extend(tlv(*value_tag, attribute_value.as_bytes()));
+            value.extend(tlv(0x30, &attribute));
+        }
+        tlv(0x31, &value)
+    }
+
+    fn matches(name: &[Vec<u8>], constraint: &[Vec<u8>]) -> Result<bool, Error> {
+        let name = name.concat();
+        let constraint = constraint.concat();
+        presented_id_matches_constraint(
+            untrusted::Input::from(&name[..]),
+            untrusted::Input::from(&constraint[..]),
+        )
+    }
+
+    const PRINTABLE: u8 = 0x13;
+    const UTF8: u8 = 0x0c;
+    const IA5: u8 = 0x16;
+
+    #[test]
+    fn prefix_test() {
+        let c = rdn(&[(COUNTRY, PRINTABLE, "US")]);
+        let o = rdn(&[(ORGANIZATION, UTF8, "Example Corp")]);
+        let cn = rdn(&[(COMMON_NAME, UTF8, "www")]);
+
+        assert_eq!(matches(&[c.clone(), o.clone(), cn.clone()], &[]), Ok(true));
+        assert_eq!(
+            matches(&[c.clone(), o.clone(), cn.clone()], &[c.clone()]),
+            Ok(true)
+        );
+        assert_eq!(
+            matches(&[c.clone(), o.clone(), cn.clone()], &[c.clone(), o.clone()]),
+            Ok(true)
+        );
+        assert_eq!(
+            matches(&[c.clone(), o.clone()], &[c.clone(), o.clone()]),
+            Ok(true)
+        );
+        assert_eq!(matches(&[c.clone()], &[c.clone(), o.clone()]), Ok(false));
+        assert_eq!(
+            matches(&[o.clone(), c.clone()], &[c.clone(), o.clone()]),
+            Ok(false)
+        );
+        assert_eq!(matches(&[c.clone(), cn], &[c, o]), Ok(false));
+    }
+
+    #[test]
+    fn string_matching_test() {
+        let constraint = [rdn(&[(ORGANIZATION, PRINTABLE, "Example Corp")])];
+        for &(value_tag, value, expected) in &[
+            (PRINTABLE, "Example Corp", true),
+            (UTF8, "Example Corp", true),
+            (UTF8, "EXAMPLE corp", true),
+            (UTF8, "  Example \t  Corp ", true),
+            (PRINTABLE, "ExampleCorp", false),
+            (PRINTABLE, "Example Corp.", false),
+            (IA5, "Example Corp", false),
+        ] {
+            assert_eq!(
+                matches(&[rdn(&[(ORGANIZATION, value_tag, value)])], &constraint),
+                Ok(expected),
+                "{:?}",
+                value
+            );
+        }
+
+        // Non-ASCII characters are compared exactly.
+        let constraint = [rdn(&[(ORGANIZATION, UTF8, "Ölfabrik")])];
+        assert_eq!(
+            matches(&[rdn(&[(ORGANIZATION, UTF8, "ölfabrik")])], &constraint),
+            Ok(false)
+        );
+        assert_eq!(
+            matches(&[rdn(&[(ORGANIZATION, UTF8, "ÖLFABRIK")])], &constraint),
+            Ok(true)
+        );
+
+        // The attribute types must be the same.
+        assert_eq!(
+            matches(
+                &[rdn(&[(COMMON_NAME, PRINTABLE, "Example Corp")])],
+                &constraint
+            ),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn multi_valued_rdn_test() {
+        let constraint = [rdn(&[
+            (ORGANIZATION, UTF8, "Example"),
+            (COMMON_NAME, UTF8, "www"),
+        ])];
+        assert_eq!(
+            matches(
+                &[rdn(&[
+                    (COMMON_NAME, PRINTABLE, "WWW"),
+                    (ORGANIZATION, UTF8, "example")
+                ])],
+                &constraint
+            ),
+            Ok(true)
+        );
+        assert_eq!(
+            matches(&[rdn(&[(ORGANIZATION, UTF8, "Example")])], &constraint),
+            Ok(false)
+        );
+        assert_eq!(
+            matches(
+                &[rdn(&[
+                    (ORGANIZATION, UTF8, "Example"),
+                    (COMMON_NAME, UTF8, "www"),
+                    (COUNTRY, PRINTABLE, "US"),
+                ])],
+                &constraint
+            ),
+            Ok(false)
+        );
+    }
+
+    fn distinguished_name(rdns: &[Vec<u8>]) -> Vec<u8> {
+        rdns.concat()
+    }
+
+    #[test]
+    fn distinguished_name_test() {
+        let encoded = distinguished_name(&[
+            rdn(&[(COUNTRY, PRINTABLE, "US")]),
+            rdn(&[(ORGANIZATION, UTF8, "Example, Corp")]),
+            rdn(&[(COMMON_NAME, UTF8, "www"), (UNKNOWN, 0x02, "\x01")]),
+        ]);
+        let name = DistinguishedName::try_from(&encoded[..]).unwrap();
+        assert_eq!(name.as_der(), &encoded[..]);
+        assert_eq!(
+            name.to_string(),
+            "CN=www+1.2.3.4=#020101,O=Example\\, Corp,C=US"
+        );
+
+        let rdns = name.rdns().collect::<Vec<_>>();
+        assert_eq!(rdns.len(), 3);
+        let attributes = rdns[2].attributes().collect::<Vec<_>>();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].oid(), COMMON_NAME);
+        assert_eq!(attributes[0].value_tag(), UTF8);
+        assert_eq!(attributes[0].value(), b"www");
+        assert_eq!(attributes[0].value_str(), Some("www"));
+        assert_eq!(attributes[1].value_str(), None);
+
+        assert_eq!(
+            DistinguishedName::try_from(&[][..]).unwrap().to_string(),
+            ""
+        );
+    }
+
+    #[test]
+    fn distinguished_name_escaping_test() {
+        for &(value, expected) in &[
+            ("a+b;c<d>e\"f\\g", "O=a\\+b\\;c\\<d\\>e\\\"f\\\\g"),
+            ("#a#", "O=\\#a#"),
+            (" a b ", "O=\\ a b\\ "),
+            ("a\0b", "O=a\\00b"),
+        ] {
+            let encoded = rdn(&[(ORGANIZATION, UTF8, value)]);
+            let name = DistinguishedName::try_from(&encoded[..]).unwrap();
+            assert_eq!(name.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn eq_rfc5280_test() {
+        let name = |rdns: &[Vec<u8>]| distinguished_name(rdns);
+        let a = name(&[
+            rdn(&[(COUNTRY, PRINTABLE, "US")]),
+            rdn(&[(ORGANIZATION, UTF8, "Example  Corp")]),
+        ]);
+        let b = name(&[
+            rdn(&[(COUNTRY, UTF8, "us")]),
+            rdn(&[(ORGANIZATION, PRINTABLE, "example corp ")]),
+        ]);
+        let prefix = name(&[rdn(&[(COUNTRY, PRINTABLE, "US")])]);
+        let a = DistinguishedName::try_from(&a[..]).unwrap();
+        let b = DistinguishedName::try_from(&b[..]).unwrap();
+        let prefix = DistinguishedName::try_from(&prefix[..]).unwrap();
+        assert_ne!(a, b);
+        assert!(a.eq_rfc5280(&b));
+        assert!(b.eq_rfc5280(&a));
+        assert!(!a.eq_rfc5280(&prefix));
+        assert!(!prefix.eq_rfc5280(&a));
+    }
+
+    #[test]
+    fn distinguished_name_malformed_test() {
+        assert_eq!(
+            DistinguishedName::try_from(&[0x31, 0x00][..]),
+            Err(Error::BadDer)
+        );
+        assert_eq!(
+            DistinguishedName::try_from(&[0x30, 0x00][..]),
+            Err(Error::BadDer)
+        );
+        assert_eq!(
+            DistinguishedName::try_from(&[0x31, 0x02, 0x30, 0x00][..]),
+            Err(Error::BadDer)
+        );
+    }
+
+    #[test]
+    fn malformed_test() {
+        let o = rdn(&[(ORGANIZATION, UTF8, "Example")]);
+        assert_eq!(
+            matches(&[o.clone()], &[vec![0x30, 0x00]]),
+            Err(Error::BadDer)
+        );
+        assert_eq!(matches(&[vec![0x31, 0x01]], &[o]), Err(Error::BadDer));
+    }
+}
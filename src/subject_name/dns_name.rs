@@ -12,6 +12,7 @@
 // ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
+use crate::Error;
 #[cfg(feature = "alloc")]
 use alloc::string::String;
 
@@ -44,6 +45,35 @@ impl DnsName {
     pub fn as_ref(&self) -> DnsNameRef {
         DnsNameRef(self.0.as_bytes())
     }
+
+    /// Converts `dns_name`, which may contain Unicode labels, to a `DnsName`
+    /// of A-labels, with the IDNA processing of UTS #46.
+    ///
+    /// Only use this for names entered by a user; certificates are always
+    /// verified against the A-labels.
+    ///
+    /// Requires the `idna` feature.
+    #[cfg(feature = "idna")]
+    pub fn from_unicode(dns_name: &str) -> Result<Self, InvalidDnsNameError> {
+        let dns_name =
+            idna::domain_to_ascii(dns_name).map_err(|_| InvalidDnsNameError::InvalidSyntax)?;
+        DnsNameRef::try_from_ascii(dns_name.as_bytes()).map(|dns_name| dns_name.to_owned())
+    }
+
+    /// Returns this name with its A-labels (`xn--...`) decoded to Unicode,
+    /// with the IDNA processing of UTS #46, to show to a user.
+    ///
+    /// If a label that starts with `xn--` isn't a valid A-label, the name is
+    /// returned as it is, rather than partially decoded.
+    ///
+    /// Requires the `idna` feature.
+    #[cfg(feature = "idna")]
+    pub fn to_unicode(&self) -> String {
+        match idna::domain_to_unicode(&self.0) {
+            (unicode, Ok(())) => unicode,
+            (_, Err(_)) => self.0.clone(),
+        }
+    }
 }
 
 /// Requires the `alloc` feature.
@@ -89,7 +119,19 @@ impl AsRef<[u8]> for DnsNameRef<'_> {
 /// An error indicating that a `DnsNameRef` could not built because the input
 /// is not a syntactically-valid DNS Name.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct InvalidDnsNameError;
+#[non_exhaustive]
+pub enum InvalidDnsNameError {
+    /// The input contains a byte that is not ASCII.
+    NonAscii,
+
+    /// The input is ASCII, but not a syntactically-valid DNS name.
+    InvalidSyntax,
+
+    /// The input contains a `*`, like the wildcard name `*.example.com`.
+    /// Wildcards are only meaningful in the names a certificate presents,
+    /// never in the name it is verified for.
+    Wildcard,
+}
 
 impl core::fmt::Display for InvalidDnsNameError {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -104,9 +146,19 @@ impl ::std::error::Error for InvalidDnsNameError {}
 impl<'a> DnsNameRef<'a> {
     /// Constructs a `DnsNameRef` from the given input if the input is a
     /// syntactically-valid DNS name.
+    ///
+    /// The input does not need to be UTF-8; bytes that are not ASCII are
+    /// rejected with `InvalidDnsNameError::NonAscii`. Wildcard names are
+    /// rejected with `InvalidDnsNameError::Wildcard`.
     pub fn try_from_ascii(dns_name: &'a [u8]) -> Result<Self, InvalidDnsNameError> {
+        if !dns_name.is_ascii() {
+            return Err(InvalidDnsNameError::NonAscii);
+        }
+        if dns_name.contains(&b'*') {
+            return Err(InvalidDnsNameError::Wildcard);
+        }
         if !is_valid_reference_dns_id(untrusted::Input::from(dns_name)) {
-            return Err(InvalidDnsNameError);
+            return Err(InvalidDnsNameError::InvalidSyntax);
         }
 
         Ok(Self(dns_name))
@@ -154,6 +206,129 @@ impl<'a> From<DnsNameRef<'a>> for &'a str {
     }
 }
 
+/// Verifies that the DNS name `presented_dns_id`, as it would appear in a
+/// certificate's subjectAltName extension, matches `reference_dns_id`.
+///
+/// This is the matching done by `EndEntityCert::verify_is_valid_for_subject_name`:
+/// ASCII case is ignored, and a presented identifier may have a wildcard
+/// as its entire first label, matching exactly one label. Fails with
+/// `Error::CertNotValidForName` if the names don't match or
+/// `presented_dns_id` is not a valid presented identifier.
+pub fn verify_dns_names_match(
+    presented_dns_id: &[u8],
+    reference_dns_id: DnsNameRef,
+) -> Result<(), Error> {
+    match presented_id_matches_reference_id(
+        untrusted::Input::from(presented_dns_id),
+        untrusted::Input::from(reference_dns_id.0),
+    ) {
+        Some(true) => Ok(()),
+        Some(false) | None => Err(Error::CertNotValidForName),
+    }
+}
+
+/// The reason a DNS name in a certificate's subjectAltName extension is not
+/// a valid presented identifier; see `validate_presented_dns_name`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InvalidPresentedDnsNameError {
+    /// The name contains a byte that is not ASCII, e.g. a U-label that should
+    /// have been encoded as an A-label (`xn--...`).
+    NonAscii,
+
+    /// The name contains a NUL byte, e.g. `www.example.com\0.evil.example`,
+    /// which software that handles names as C strings would truncate.
+    EmbeddedNul,
+
+    /// The name contains an ASCII character other than a letter, a digit,
+    /// `-`, `_`, `.` or `*`.
+    InvalidCharacter,
+
+    /// The name is absolute, i.e. it ends with a dot.
+    Absolute,
+
+    /// The name contains a `*` that isn't the entire leftmost label.
+    InvalidWildcard,
+
+    /// The name is a wildcard name with fewer than two labels following the
+    /// wildcard label, like `*.com`.
+    WildcardTooFewLabels,
+
+    /// The name is ASCII, but not a syntactically-valid DNS name for any
+    /// other reason.
+    InvalidSyntax,
+}
+
+impl core::fmt::Display for InvalidPresentedDnsNameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+impl ::std::error::Error for InvalidPresentedDnsNameError {}
+
+/// Checks that `presented_dns_id`, as it would appear in a certificate's
+/// subjectAltName extension, is a valid presented identifier.
+///
+/// Name verification fails with `Error::BadDer` when it reaches a presented
+/// identifier that isn't valid, without saying what is wrong with it; this
+/// does, e.g. for the names reported to
+/// `VerificationDiagnostics::name_presented`.
+pub fn validate_presented_dns_name(
+    presented_dns_id: &[u8],
+) -> Result<(), InvalidPresentedDnsNameError> {
+    check_presented_dns_id_characters(presented_dns_id)?;
+    if is_valid_dns_id(
+        untrusted::Input::from(presented_dns_id),
+        IdRole::Presented,
+        AllowWildcards::Yes,
+    ) {
+        return Ok(());
+    }
+
+    let (is_wildcard, rest) = match presented_dns_id {
+        [b'*', b'.', rest @ ..] => (true, rest),
+        _ => (false, presented_dns_id),
+    };
+    if rest.contains(&b'*') {
+        return Err(InvalidPresentedDnsNameError::InvalidWildcard);
+    }
+    if rest.last() == Some(&b'.') && is_valid_reference_dns_id(untrusted::Input::from(rest)) {
+        return Err(InvalidPresentedDnsNameError::Absolute);
+    }
+    if is_wildcard
+        && presented_dns_id.len() <= 253
+        && is_valid_dns_id(
+            untrusted::Input::from(rest),
+            IdRole::Presented,
+            AllowWildcards::No,
+        )
+    {
+        return Err(InvalidPresentedDnsNameError::WildcardTooFewLabels);
+    }
+    Err(InvalidPresentedDnsNameError::InvalidSyntax)
+}
+
+// Checks that `presented_dns_id` contains only bytes that may appear in a
+// presented identifier, whether or not it is otherwise valid.
+pub(super) fn check_presented_dns_id_characters(
+    presented_dns_id: &[u8],
+) -> Result<(), InvalidPresentedDnsNameError> {
+    if !presented_dns_id.is_ascii() {
+        return Err(InvalidPresentedDnsNameError::NonAscii);
+    }
+    if presented_dns_id.contains(&0) {
+        return Err(InvalidPresentedDnsNameError::EmbeddedNul);
+    }
+    let is_valid = |byte: &u8| byte.is_ascii_alphanumeric() || b"-_.*".contains(byte);
+    if !presented_dns_id.iter().all(is_valid) {
+        return Err(InvalidPresentedDnsNameError::InvalidCharacter);
+    }
+    Ok(())
+}
+
 pub(super) fn presented_id_matches_reference_id(
     presented_dns_id: untrusted::Input,
     reference_dns_id: untrusted::Input,
@@ -176,6 +351,16 @@ pub(super) fn presented_id_matches_constraint(
     )
 }
 
+// Whether `dns_name` is `suffix` or a subdomain of it, as for a dNSName name
+// constraint of `suffix`.
+#[cfg(feature = "tls-server-verify")]
+pub(super) fn is_under_suffix(dns_name: DnsNameRef, suffix: DnsNameRef) -> bool {
+    presented_id_matches_constraint(
+        untrusted::Input::from(dns_name.0),
+        untrusted::Input::from(suffix.0),
+    ) == Some(true)
+}
+
 // We do not distinguish between a syntactically-invalid presented_dns_id and
 // one that is syntactically valid but does not match reference_dns_id; in both
 // cases, the result is false.
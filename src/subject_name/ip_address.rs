@@ -13,7 +13,7 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use core::convert::TryInto;
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
 use core::fmt::Write;
 
 use crate::Error;
@@ -99,6 +99,11 @@ impl ::std::error::Error for AddrParseError {}
 impl<'a> IpAddrRef<'a> {
     /// Constructs an `IpAddrRef` from the given input if the input is
     /// a valid IPv4 or IPv6 address.
+    ///
+    /// An IPv6 address may be in any of the text forms of RFC 4291 section
+    /// 2.2, e.g. `2001:db8::1`, other than with an embedded IPv4 address.
+    /// Whichever form it is in, `octets` returns the same address, and it
+    /// matches the same names and name constraints.
     pub fn try_from_ascii(ip_address: &'a [u8]) -> Result<Self, AddrParseError> {
         if let Ok(ip_address) = parse_ipv4_address(ip_address) {
             Ok(ip_address)
@@ -115,6 +120,15 @@ impl<'a> IpAddrRef<'a> {
         Self::try_from_ascii(ip_address.as_bytes())
     }
 
+    /// Returns the address in network byte order: 4 octets for an IPv4
+    /// address and 16 for an IPv6 address, as in an iPAddress name.
+    pub fn octets(&self) -> &[u8] {
+        match self {
+            IpAddrRef::V4(_, ip_address_octets) => ip_address_octets,
+            IpAddrRef::V6(_, ip_address_octets) => ip_address_octets,
+        }
+    }
+
     /// Constructs an `IpAddr` from this `IpAddrRef`
     ///
     /// Requires the `alloc` feature.
@@ -133,7 +147,35 @@ impl<'a> IpAddrRef<'a> {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
+impl IpAddr {
+    /// Constructs an `IpAddr` from an address in network byte order: 4
+    /// octets for an IPv4 address and 16 for an IPv6 address, as in an
+    /// iPAddress name.
+    ///
+    /// The string representation is dotted decimal for IPv4, and eight
+    /// groups of four lowercase hexadecimal digits for IPv6.
+    ///
+    /// Requires the `alloc` feature.
+    pub fn try_from_octets(ip_address_octets: &[u8]) -> Result<Self, AddrParseError> {
+        if let Ok(octets) = TryInto::<[u8; 4]>::try_into(ip_address_octets) {
+            let mut ip_address = String::with_capacity(15);
+            ip_address
+                .write_fmt(format_args!(
+                    "{}.{}.{}.{}",
+                    octets[0], octets[1], octets[2], octets[3]
+                ))
+                .expect("unexpected error while formatting IPv4 address");
+            Ok(IpAddr::V4(ip_address, octets))
+        } else if let Ok(octets) = TryInto::<[u8; 16]>::try_into(ip_address_octets) {
+            Ok(IpAddr::V6(ipv6_to_uncompressed_string(octets), octets))
+        } else {
+            Err(AddrParseError)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
 fn ipv6_to_uncompressed_string(octets: [u8; 16]) -> String {
     let mut result = String::with_capacity(39);
     for i in 0..7 {
@@ -160,13 +202,9 @@ impl From<std::net::IpAddr> for IpAddr {
                 IpAddr::V4(ip_address.to_string(), ip_address.octets())
             }
             std::net::IpAddr::V6(ip_address) => IpAddr::V6(
-                // We cannot rely on the Display implementation of
-                // std::net::Ipv6Addr given that it might return
-                // compressed IPv6 addresses if the address can be
-                // expressed in such form. However, given we don't
-                // support the IPv6 compressed form, we should not
-                // generate such format either when converting from a
-                // type that supports it.
+                // The Display implementation of std::net::Ipv6Addr
+                // compresses addresses where it can; `IpAddr` always uses
+                // the uncompressed form, as `IpAddr::try_from_octets` does.
                 ipv6_to_uncompressed_string(ip_address.octets()),
                 ip_address.octets(),
             ),
@@ -174,6 +212,25 @@ impl From<std::net::IpAddr> for IpAddr {
     }
 }
 
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+impl From<IpAddrRef<'_>> for std::net::IpAddr {
+    fn from(ip_address: IpAddrRef<'_>) -> std::net::IpAddr {
+        match ip_address {
+            IpAddrRef::V4(_, ip_address_octets) => std::net::IpAddr::from(ip_address_octets),
+            IpAddrRef::V6(_, ip_address_octets) => std::net::IpAddr::from(ip_address_octets),
+        }
+    }
+}
+
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+impl From<&IpAddr> for std::net::IpAddr {
+    fn from(ip_address: &IpAddr) -> std::net::IpAddr {
+        IpAddrRef::from(ip_address).into()
+    }
+}
+
 impl<'a> From<IpAddrRef<'a>> for &'a str {
     fn from(ip_address: IpAddrRef<'a>) -> &'a str {
         match ip_address {
@@ -402,97 +459,56 @@ pub(crate) fn parse_ipv4_address(ip_address_: &[u8]) -> Result<IpAddrRef, AddrPa
 }
 
 pub(crate) fn parse_ipv6_address(ip_address_: &[u8]) -> Result<IpAddrRef, AddrParseError> {
-    // Compressed addresses are not supported. Also, IPv4-mapped IPv6
-    // addresses are not supported. This makes 8 groups of 4
-    // hexadecimal characters + 7 colons.
-    if ip_address_.len() != 39 {
-        return Err(AddrParseError);
-    }
-
-    let mut ip_address = untrusted::Reader::new(untrusted::Input::from(ip_address_));
-    let mut is_first_byte = true;
-    let mut current_textual_block_size = 0;
-    let mut colon_count = 0;
-
-    let mut octet = 0;
-    let mut previous_character = None;
-    let mut octets: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
-
-    loop {
-        match ip_address.read_byte() {
-            Ok(b':') => {
-                if is_first_byte {
-                    // Uncompressed IPv6 address cannot start with a colon.
-                    return Err(AddrParseError);
-                }
-                if ip_address.at_end() {
-                    // Uncompressed IPv6 address cannot end with a colon.
-                    return Err(AddrParseError);
-                }
-                if colon_count == 7 {
-                    // IPv6 address cannot have more than seven colons.
-                    return Err(AddrParseError);
-                }
-                colon_count += 1;
-                if current_textual_block_size == 0 {
-                    // Uncompressed IPv6 address cannot contain two colons in a row.
-                    return Err(AddrParseError);
-                }
-                if current_textual_block_size != 4 {
-                    // Compressed IPv6 addresses are not supported.
-                    return Err(AddrParseError);
-                }
-                // We move on to the next textual block.
-                current_textual_block_size = 0;
-                previous_character = None;
+    // The text forms of RFC 4291 section 2.2 are accepted: leading zeros
+    // may be omitted from each group, and one run of zero groups may be
+    // replaced with "::". IPv4-mapped IPv6 addresses, with a dotted quad as
+    // their last 32 bits, are not supported.
+
+    // Parses colon-separated groups of one to four hexadecimal digits into
+    // `groups`, returning how many there were.
+    fn parse_groups(text: &[u8], groups: &mut [u16; 8]) -> Result<usize, AddrParseError> {
+        if text.is_empty() {
+            return Ok(0);
+        }
+        let mut count = 0;
+        for group in text.split(|&character| character == b':') {
+            if group.is_empty() || group.len() > 4 {
+                return Err(AddrParseError);
             }
-            Ok(character @ b'0'..=b'9')
-            | Ok(character @ b'a'..=b'f')
-            | Ok(character @ b'A'..=b'F') => {
-                if current_textual_block_size == 4 {
-                    // Blocks cannot contain more than 4 hexadecimal characters.
-                    return Err(AddrParseError);
-                }
-                if let Some(previous_character_) = previous_character {
-                    octets[octet] = (TryInto::<u8>::try_into(
-                        TryInto::<u8>::try_into(
-                            (TryInto::<char>::try_into(previous_character_)
-                                .expect("invalid character"))
-                            .to_digit(16)
-                            // Safe to unwrap because we know character is within hexadecimal bounds ([0-9a-f])
-                            .unwrap(),
-                        )
-                        .expect("invalid character"),
-                    )
-                    .expect("invalid character")
-                        << 4)
-                        | (TryInto::<u8>::try_into(
-                            TryInto::<char>::try_into(character)
-                                .expect("invalid character")
-                                .to_digit(16)
-                                // Safe to unwrap because we know character is within hexadecimal bounds ([0-9a-f])
-                                .unwrap(),
-                        )
-                        .expect("invalid character"));
-                    previous_character = None;
-                    octet += 1;
-                } else {
-                    previous_character = Some(character);
-                }
-                current_textual_block_size += 1;
+            let mut value = 0;
+            for &character in group {
+                let digit = char::from(character).to_digit(16).ok_or(AddrParseError)?;
+                value = (value << 4) | digit;
             }
-            _ => {
+            *groups.get_mut(count).ok_or(AddrParseError)? =
+                TryInto::<u16>::try_into(value).expect("four hexadecimal digits");
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    let mut groups = [0; 8];
+    match ip_address_.windows(2).position(|pair| pair == b"::") {
+        Some(compressed_at) => {
+            let head_len = parse_groups(&ip_address_[..compressed_at], &mut groups)?;
+            let mut tail = [0; 8];
+            let tail_len = parse_groups(&ip_address_[compressed_at + 2..], &mut tail)?;
+            // "::" stands for at least one group of zeros.
+            if head_len + tail_len > 7 {
                 return Err(AddrParseError);
             }
+            groups[8 - tail_len..].copy_from_slice(&tail[..tail_len]);
         }
-        is_first_byte = false;
-
-        if ip_address.at_end() {
-            break;
+        None => {
+            if parse_groups(ip_address_, &mut groups)? != 8 {
+                return Err(AddrParseError);
+            }
         }
     }
-    if colon_count != 7 {
-        return Err(AddrParseError);
+
+    let mut octets = [0; 16];
+    for (octets, group) in octets.chunks_mut(2).zip(groups.iter()) {
+        octets.copy_from_slice(&group.to_be_bytes());
     }
     Ok(IpAddrRef::V6(ip_address_, octets))
 }
@@ -592,40 +608,85 @@ mod tests {
                 0xff, 0xff,
             ],
         ),
-        // Invalid IPv6 addresses
-        // Missing octets on uncompressed addresses. The unmatching letter has the violation
-        (
+        // Leading zeros may be omitted from a group.
+        ipv6_address(
             b"aaa:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
-            Err(AddrParseError),
+            [
+                0x0a, 0xaa, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:aaa:ffff:ffff:ffff:ffff:ffff:ffff",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0x0a, 0xaa, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:ffff:aaa:ffff:ffff:ffff:ffff:ffff",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0xff, 0xff, 0x0a, 0xaa, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:ffff:ffff:aaa:ffff:ffff:ffff:ffff",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0a, 0xaa, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:ffff:ffff:ffff:aaa:ffff:ffff:ffff",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0a, 0xaa, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:ffff:ffff:ffff:ffff:aaa:ffff:ffff",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0a, 0xaa, 0xff, 0xff,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:ffff:ffff:ffff:ffff:ffff:aaa:ffff",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0a, 0xaa,
+                0xff, 0xff,
+            ],
         ),
-        (
+        ipv6_address(
             b"ffff:ffff:ffff:ffff:ffff:ffff:ffff:aaa",
-            Err(AddrParseError),
+            [
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0x0a, 0xaa,
+            ],
         ),
+        // One run of zero groups may be compressed.
+        ipv6_address(b"::", [0; 16]),
+        ipv6_address(b"::1", [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+        ipv6_address(
+            b"2001:db8::",
+            [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        ),
+        ipv6_address(
+            b"2001:db8::8a2e:370:7334",
+            [
+                0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0x8a, 0x2e, 0x03, 0x70, 0x73, 0x34,
+            ],
+        ),
+        ipv6_address(
+            b"0:0:0:0:0:0:0:1",
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        ),
+        // Invalid IPv6 addresses
+        (b":::", Err(AddrParseError)),
+        (b"1::2::3", Err(AddrParseError)),
+        (b"::ffff:127.0.0.1", Err(AddrParseError)),
+        (b"", Err(AddrParseError)),
         // Wrong hexadecimal characters on different positions
         (
             b"ffgf:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
@@ -659,7 +720,7 @@ mod tests {
             b"ffff:ffff:ffff:ffff:ffff:ffff:ffgf:fffg",
             Err(AddrParseError),
         ),
-        // Wrong colons on uncompressed addresses
+        // "::" must stand for at least one group
         (
             b":ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
             Err(AddrParseError),
@@ -726,7 +787,7 @@ mod tests {
             b"2a05:d018:076c:b685:e8ab:afd3:af51:3a::",
             Err(AddrParseError),
         ),
-        // Cannot contain two colons in a row
+        // "::" must stand for at least one group
         (
             b"2a05::018:076c:b685:e8ab:afd3:af51:3aed",
             Err(AddrParseError),
@@ -748,12 +809,8 @@ mod tests {
             b"2a05:d018:076c:b685:e8ab:afd3:af51:3aed3aed",
             Err(AddrParseError),
         ),
-        // These are valid IPv6 addresses, but we don't support compressed addresses
-        (b"0:0:0:0:0:0:0:1", Err(AddrParseError)),
-        (
-            b"2a05:d018:76c:b685:e8ab:afd3:af51:3aed",
-            Err(AddrParseError),
-        ),
+        // IPv4-mapped IPv6 addresses are not supported
+        (b"::ffff:7f00:1.1", Err(AddrParseError)),
     ];
 
     #[test]
@@ -785,10 +842,18 @@ mod tests {
                     [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
                 )),
             ),
+            (
+                // Compressed IPv6 addresses
+                b"::1",
+                Ok(IpAddrRef::V6(
+                    b"::1",
+                    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                )),
+            ),
             // Invalid IPv6 addresses
             (
-                // IPv6 addresses in compressed form are not supported
-                b"0:0:0:0:0:0:0:1",
+                // IPv4-mapped IPv6 addresses are not supported
+                b"::ffff:127.0.0.1",
                 Err(AddrParseError),
             ),
             // Something else
@@ -822,10 +887,18 @@ mod tests {
                     [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
                 )),
             ),
+            (
+                // Compressed IPv6 addresses
+                "::1",
+                Ok(IpAddrRef::V6(
+                    b"::1",
+                    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                )),
+            ),
             // Invalid IPv6 addresses
             (
-                // IPv6 addresses in compressed form are not supported
-                "0:0:0:0:0:0:0:1",
+                // IPv4-mapped IPv6 addresses are not supported
+                "::ffff:127.0.0.1",
                 Err(AddrParseError),
             ),
             // Something else
@@ -1372,6 +1445,35 @@ mod alloc_tests {
         }
     }
 
+    #[test]
+    fn ip_address_try_from_octets_test() {
+        assert_eq!(
+            IpAddr::try_from_octets(&[127, 0, 0, 1]),
+            Ok(IpAddr::V4(String::from("127.0.0.1"), [127, 0, 0, 1]))
+        );
+        assert_eq!(
+            IpAddr::try_from_octets(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+            Ok(IpAddr::V6(
+                String::from("0000:0000:0000:0000:0000:0000:0000:0001"),
+                [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+            ))
+        );
+        assert_eq!(IpAddr::try_from_octets(&[127, 0, 0]), Err(AddrParseError));
+    }
+
+    #[test]
+    fn std_net_ipaddr_from_ip_address_test() {
+        let ip_address = IpAddrRef::try_from_ascii_str("2001:db8::1").unwrap();
+        assert_eq!(
+            std::net::IpAddr::from(ip_address),
+            "2001:db8::1".parse::<std::net::IpAddr>().unwrap()
+        );
+        assert_eq!(
+            std::net::IpAddr::from(&ip_address.to_owned()),
+            "2001:db8::1".parse::<std::net::IpAddr>().unwrap()
+        );
+    }
+
     #[test]
     fn ip_address_from_std_net_ipaddr_test() {
         let ip_addresses = vec![
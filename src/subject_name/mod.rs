@@ -13,14 +13,26 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 mod dns_name;
-pub use dns_name::{DnsNameRef, InvalidDnsNameError};
+pub use dns_name::{
+    validate_presented_dns_name, verify_dns_names_match, DnsNameRef, InvalidDnsNameError,
+    InvalidPresentedDnsNameError,
+};
 
 /// Requires the `alloc` feature.
 #[cfg(feature = "alloc")]
 pub use dns_name::DnsName;
 
+#[cfg(feature = "tls-server-verify")]
+pub(super) use dns_name::is_under_suffix;
+
+mod directory_name;
+pub(super) use directory_name::names_match;
+pub use directory_name::{
+    AttributeTypeAndValue, Attributes, DistinguishedName, Rdns, RelativeDistinguishedName,
+};
+
 mod name;
-pub use name::{InvalidSubjectNameError, SubjectNameRef};
+pub use name::{InvalidSubjectNameError, IpAddressInDnsName, PresentedName, SubjectNameRef};
 
 mod ip_address;
 pub use ip_address::{AddrParseError, IpAddrRef};
@@ -30,5 +42,13 @@ pub use ip_address::IpAddr;
 
 mod verify;
 pub(super) use verify::{
-    check_name_constraints, verify_cert_subject_name, SubjectCommonNameContents,
+    check_name_constraints, report_presented_names, verify_cert_subject_name,
+    SubjectCommonNameContents,
 };
+
+#[cfg(feature = "alloc")]
+pub(super) use verify::presented_names;
+
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+pub use verify::DnsNameMatcher;
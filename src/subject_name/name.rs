@@ -26,6 +26,47 @@ pub enum SubjectNameRef<'a> {
     IpAddress(IpAddrRef<'a>),
 }
 
+/// A DNS name or IP address in a certificate's subjectAltName extension,
+/// as it is encoded there; see `VerificationDiagnostics::name_presented`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PresentedName<'a> {
+    /// The ASCII text of a dNSName, which may be a wildcard name, and isn't
+    /// necessarily a valid DNS name.
+    DnsName(&'a [u8]),
+
+    /// The octets of an iPAddress, in network byte order; see
+    /// `IpAddrRef::octets`.
+    IpAddress(&'a [u8]),
+}
+
+/// What name verification does with a dNSName in a certificate's
+/// subjectAltName extension that is an IP address written as text, like
+/// `192.0.2.1`, which some CAs issue instead of an iPAddress.
+///
+/// Such a dNSName is never a valid DNS name, so it never matches a
+/// `SubjectNameRef::DnsName`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IpAddressInDnsName {
+    /// Skip it. This is the default.
+    Ignore,
+
+    /// Match it against a `SubjectNameRef::IpAddress` as if it were an
+    /// iPAddress.
+    MatchAsIpAddress,
+
+    /// Reject the certificate with `Error::IpAddressInDnsName`, whatever
+    /// name it is verified for.
+    Reject,
+}
+
+impl Default for IpAddressInDnsName {
+    fn default() -> Self {
+        Self::Ignore
+    }
+}
+
 /// An error indicating that a `SubjectNameRef` could not built
 /// because the input is not a syntactically-valid DNS Name or IP
 /// address.
@@ -39,11 +80,10 @@ impl<'a> SubjectNameRef<'a> {
     /// overlap with a valid IPv4 address), and IPv6 addresses are separated by colons but
     /// cannot contain periods.
     ///
-    /// The IPv6 address encoding supported here is extremely simplified; it does not support
-    /// compression, all leading zeroes must be present in each 16-bit word, etc.  Generally
-    /// this is not suitable as a parse for human-provided addresses for this reason.  Instead:
-    /// consider parsing these with `std::net::IpAddr` and then using
-    /// `IpAddr::from<std::net::IpAddr>`.
+    /// IPv6 addresses are accepted in the text forms that
+    /// `IpAddrRef::try_from_ascii` accepts, which don't include IPv4-mapped
+    /// addresses written with a dotted quad. For those, consider parsing with
+    /// `std::net::IpAddr` and then using `IpAddr::from<std::net::IpAddr>`.
     pub fn try_from_ascii(subject_name: &'a [u8]) -> Result<Self, InvalidSubjectNameError> {
         if let Ok(ip_address) = ip_address::parse_ipv4_address(subject_name) {
             return Ok(SubjectNameRef::IpAddress(ip_address));
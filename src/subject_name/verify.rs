@@ -13,20 +13,26 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use super::{
+    directory_name,
     dns_name::{self, DnsNameRef},
     ip_address::{self, IpAddrRef},
-    name::SubjectNameRef,
+    name::{IpAddressInDnsName, PresentedName, SubjectNameRef},
 };
 use crate::{
     cert::{Cert, EndEntityOrCa},
-    der, Error,
+    der, limits, oid, Error, VerificationDiagnostics,
 };
+use core::cell::Cell;
+
+#[cfg(feature = "alloc")]
+use {crate::fallible, alloc::vec::Vec};
 
 pub(crate) fn verify_cert_dns_name(
     cert: &crate::EndEntityCert,
     dns_name: DnsNameRef,
 ) -> Result<(), Error> {
     let cert = cert.inner();
+    check_presented_dns_id_characters(cert)?;
     let dns_name = untrusted::Input::from(dns_name.as_ref());
     iterate_names(
         Some(cert.subject),
@@ -35,6 +41,9 @@ pub(crate) fn verify_cert_dns_name(
         Err(Error::CertNotValidForName),
         &|name| {
             if let GeneralName::DnsName(presented_id) = name {
+                if dns_name_as_ip_address(presented_id).is_some() {
+                    return NameIteration::KeepGoing;
+                }
                 match dns_name::presented_id_matches_reference_id(presented_id, dns_name) {
                     Some(true) => return NameIteration::Stop(Ok(())),
                     Some(false) => (),
@@ -49,7 +58,25 @@ pub(crate) fn verify_cert_dns_name(
 pub(crate) fn verify_cert_subject_name(
     cert: &crate::EndEntityCert,
     subject_name: SubjectNameRef,
+    ip_address_in_dns_name: IpAddressInDnsName,
 ) -> Result<(), Error> {
+    if ip_address_in_dns_name == IpAddressInDnsName::Reject {
+        iterate_names(
+            None,
+            cert.inner().subject_alt_name,
+            SubjectCommonNameContents::Ignore,
+            Ok(()),
+            &|name| match name {
+                GeneralName::DnsName(presented_id)
+                    if dns_name_as_ip_address(presented_id).is_some() =>
+                {
+                    NameIteration::Stop(Err(Error::IpAddressInDnsName))
+                }
+                _ => NameIteration::KeepGoing,
+            },
+        )?;
+    }
+
     let ip_address = match subject_name {
         SubjectNameRef::DnsName(dns_name) => return verify_cert_dns_name(cert, dns_name),
         SubjectNameRef::IpAddress(IpAddrRef::V4(_, ref ip_address_octets)) => {
@@ -59,6 +86,7 @@ pub(crate) fn verify_cert_subject_name(
             untrusted::Input::from(ip_address_octets)
         }
     };
+    check_presented_dns_id_characters(cert.inner())?;
 
     iterate_names(
         // IP addresses are not compared against the subject field;
@@ -68,20 +96,193 @@ pub(crate) fn verify_cert_subject_name(
         SubjectCommonNameContents::Ignore,
         Err(Error::CertNotValidForName),
         &|name| {
-            if let GeneralName::IpAddress(presented_id) = name {
-                match ip_address::presented_id_matches_reference_id(presented_id, ip_address) {
-                    Ok(true) => return NameIteration::Stop(Ok(())),
-                    Ok(false) => (),
-                    Err(_) => {
-                        return NameIteration::Stop(Err(Error::BadDer));
+            match name {
+                GeneralName::IpAddress(presented_id) => {
+                    match ip_address::presented_id_matches_reference_id(presented_id, ip_address) {
+                        Ok(true) => return NameIteration::Stop(Ok(())),
+                        Ok(false) => (),
+                        Err(_) => {
+                            return NameIteration::Stop(Err(Error::BadDer));
+                        }
+                    }
+                }
+                GeneralName::DnsName(presented_id)
+                    if ip_address_in_dns_name == IpAddressInDnsName::MatchAsIpAddress =>
+                {
+                    if let Some(presented_ip_address) = dns_name_as_ip_address(presented_id) {
+                        if presented_ip_address.octets() == ip_address.as_slice_less_safe() {
+                            return NameIteration::Stop(Ok(()));
+                        }
+                    }
+                }
+                _ => (),
+            }
+            NameIteration::KeepGoing
+        },
+    )
+}
+
+// Rejects a certificate with a dNSName, anywhere in its subjectAltName, that
+// contains a byte that can't appear in a DNS name; see
+// `Error::InvalidDnsNameInSubjectAltName`. Other invalid names only fail to
+// match.
+fn check_presented_dns_id_characters(cert: &Cert) -> Result<(), Error> {
+    let index = Cell::new(0);
+    iterate_names(
+        None,
+        cert.subject_alt_name,
+        SubjectCommonNameContents::Ignore,
+        Ok(()),
+        &|name| {
+            let name_index = index.get();
+            index.set(name_index + 1);
+            match name {
+                GeneralName::DnsName(presented_id)
+                    if dns_name_as_ip_address(presented_id).is_none() =>
+                {
+                    match dns_name::check_presented_dns_id_characters(
+                        presented_id.as_slice_less_safe(),
+                    ) {
+                        Ok(()) => NameIteration::KeepGoing,
+                        Err(reason) => {
+                            NameIteration::Stop(Err(Error::InvalidDnsNameInSubjectAltName {
+                                index: name_index,
+                                reason,
+                            }))
+                        }
+                    }
+                }
+                _ => NameIteration::KeepGoing,
+            }
+        },
+    )
+}
+
+// Some CAs put IP addresses, as text, in dNSNames; see `IpAddressInDnsName`.
+fn dns_name_as_ip_address(presented_id: untrusted::Input) -> Option<IpAddrRef> {
+    let presented_id = presented_id.as_slice_less_safe();
+    ip_address::parse_ipv4_address(presented_id)
+        .or_else(|_| ip_address::parse_ipv6_address(presented_id))
+        .ok()
+}
+
+/// The DNS names that an end-entity certificate is valid for, extracted from
+/// its subjectAltName extension once, so that many names can be checked
+/// against them without parsing the extension again each time; see
+/// `EndEntityCert::dns_name_matcher`.
+///
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct DnsNameMatcher<'a> {
+    presented_dns_ids: Vec<&'a [u8]>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> DnsNameMatcher<'a> {
+    pub(crate) fn new(cert: &Cert<'a>) -> Result<Self, Error> {
+        check_presented_dns_id_characters(cert)?;
+        let mut presented_dns_ids = Vec::new();
+        if let Some(subject_alt_name) = cert.subject_alt_name {
+            let mut subject_alt_name = untrusted::Reader::new(subject_alt_name);
+            let mut name_count = 0;
+            while !subject_alt_name.at_end() {
+                name_count += 1;
+                if name_count > limits::MAX_SUBJECT_ALT_NAMES {
+                    return Err(Error::MaximumSubjectAltNamesExceeded);
+                }
+                if let GeneralName::DnsName(presented_id) = general_name(&mut subject_alt_name)? {
+                    // Like `verify_cert_dns_name`, skip IP addresses, and stop
+                    // at the first invalid name, since no name after it can
+                    // ever match.
+                    if dns_name_as_ip_address(presented_id).is_some() {
+                        continue;
+                    }
+                    let presented_id = presented_id.as_slice_less_safe();
+                    if dns_name::validate_presented_dns_name(presented_id).is_err() {
+                        break;
                     }
+                    fallible::push(&mut presented_dns_ids, presented_id)?;
+                }
+            }
+        }
+        Ok(Self { presented_dns_ids })
+    }
+
+    /// Returns whether the certificate is valid for `dns_name`, with the same
+    /// result as `EndEntityCert::covers_dns_name`.
+    pub fn matches(&self, dns_name: DnsNameRef) -> bool {
+        let dns_name = untrusted::Input::from(dns_name.as_ref());
+        self.presented_dns_ids.iter().any(|presented_id| {
+            dns_name::presented_id_matches_reference_id(
+                untrusted::Input::from(presented_id),
+                dns_name,
+            ) == Some(true)
+        })
+    }
+}
+
+// Reports the DNS names and IP addresses in the subjectAltName of `cert`, up
+// to `MAX_REPORTED_NAMES` of them, to `diagnostics`.
+pub(crate) fn report_presented_names(
+    cert: &Cert,
+    diagnostics: &dyn VerificationDiagnostics,
+) -> Result<(), Error> {
+    const MAX_REPORTED_NAMES: usize = 16;
+
+    let reported = Cell::new(0);
+    iterate_names(
+        None,
+        cert.subject_alt_name,
+        SubjectCommonNameContents::Ignore,
+        Ok(()),
+        &|name| {
+            let name = match name {
+                GeneralName::DnsName(name) => PresentedName::DnsName(name.as_slice_less_safe()),
+                GeneralName::IpAddress(ip_address) => {
+                    PresentedName::IpAddress(ip_address.as_slice_less_safe())
                 }
+                _ => return NameIteration::KeepGoing,
+            };
+            if reported.get() == MAX_REPORTED_NAMES {
+                return NameIteration::Stop(Ok(()));
             }
+            diagnostics.name_presented(name);
+            reported.set(reported.get() + 1);
             NameIteration::KeepGoing
         },
     )
 }
 
+// Returns the DNS names and IP addresses in the subjectAltName of `cert`, in
+// the order they appear in.
+#[cfg(feature = "alloc")]
+pub(crate) fn presented_names<'a>(cert: &Cert<'a>) -> Result<Vec<PresentedName<'a>>, Error> {
+    let mut names = Vec::new();
+    if let Some(subject_alt_name) = cert.subject_alt_name {
+        let mut subject_alt_name = untrusted::Reader::new(subject_alt_name);
+        let mut name_count = 0;
+        while !subject_alt_name.at_end() {
+            name_count += 1;
+            if name_count > limits::MAX_SUBJECT_ALT_NAMES {
+                return Err(Error::MaximumSubjectAltNamesExceeded);
+            }
+            match general_name(&mut subject_alt_name)? {
+                GeneralName::DnsName(name) => fallible::push(
+                    &mut names,
+                    PresentedName::DnsName(name.as_slice_less_safe()),
+                )?,
+                GeneralName::IpAddress(ip_address) => fallible::push(
+                    &mut names,
+                    PresentedName::IpAddress(ip_address.as_slice_less_safe()),
+                )?,
+                _ => (),
+            }
+        }
+    }
+    Ok(names)
+}
+
 // https://tools.ietf.org/html/rfc5280#section-4.2.1.10
 pub(crate) fn check_name_constraints(
     input: Option<&mut untrusted::Reader>,
@@ -110,19 +311,25 @@ pub(crate) fn check_name_constraints(
 
     let mut child = subordinate_certs;
     loop {
-        iterate_names(
-            Some(child.subject),
-            child.subject_alt_name,
-            subject_common_name_contents,
-            Ok(()),
-            &|name| {
-                check_presented_id_conforms_to_constraints(
-                    name,
-                    permitted_subtrees,
-                    excluded_subtrees,
-                )
-            },
-        )?;
+        // Self-issued intermediate certificates are exempt; see RFC 5280
+        // section 6.1.3 (b) and (c).
+        let self_issued_ca =
+            matches!(child.ee_or_ca, EndEntityOrCa::Ca(_)) && child.is_self_issued();
+        if !self_issued_ca {
+            iterate_names(
+                Some(child.subject),
+                child.subject_alt_name,
+                subject_common_name_contents,
+                Ok(()),
+                &|name| {
+                    check_presented_id_conforms_to_constraints(
+                        name,
+                        permitted_subtrees,
+                        excluded_subtrees,
+                    )
+                },
+            )?;
+        }
 
         child = match child.ee_or_ca {
             EndEntityOrCa::Ca(child_cert) => child_cert,
@@ -178,8 +385,14 @@ fn check_presented_id_conforms_to_constraints_in_subtree(
 
     let mut has_permitted_subtrees_match = false;
     let mut has_permitted_subtrees_mismatch = false;
+    let mut subtree_count = 0;
 
     while !constraints.at_end() {
+        subtree_count += 1;
+        if subtree_count > limits::MAX_NAME_CONSTRAINT_SUBTREES {
+            return NameIteration::Stop(Err(Error::MaximumNameConstraintSubtreesExceeded));
+        }
+
         // http://tools.ietf.org/html/rfc5280#section-4.2.1.10: "Within this
         // profile, the minimum and maximum fields are not used with any name
         // forms, thus, the minimum MUST be zero, and maximum MUST be absent."
@@ -206,9 +419,16 @@ fn check_presented_id_conforms_to_constraints_in_subtree(
                 dns_name::presented_id_matches_constraint(name, base).ok_or(Error::BadDer)
             }
 
-            (GeneralName::DirectoryName(name), GeneralName::DirectoryName(base)) => Ok(
-                presented_directory_name_matches_constraint(name, base, subtrees),
-            ),
+            // The subject field is only constrained when it isn't empty.
+            (GeneralName::DirectoryName(name), GeneralName::DirectoryName(_))
+                if name.is_empty() =>
+            {
+                continue;
+            }
+
+            (GeneralName::DirectoryName(name), GeneralName::DirectoryName(base)) => {
+                directory_name::presented_id_matches_constraint(name, base)
+            }
 
             (GeneralName::IpAddress(name), GeneralName::IpAddress(base)) => {
                 ip_address::presented_id_matches_constraint(name, base)
@@ -266,30 +486,6 @@ fn check_presented_id_conforms_to_constraints_in_subtree(
     }
 }
 
-fn presented_directory_name_matches_constraint(
-    _name: untrusted::Input,
-    _constraint: untrusted::Input,
-    subtrees: Subtrees,
-) -> bool {
-    // Reject any uses of directory name constraints; we don't implement this.
-    //
-    // Rejecting everything technically confirms to RFC5280:
-    //
-    //   "If a name constraints extension that is marked as critical imposes constraints
-    //    on a particular name form, and an instance of that name form appears in the
-    //    subject field or subjectAltName extension of a subsequent certificate, then
-    //    the application MUST either process the constraint or _reject the certificate_."
-    //
-    // TODO: rustls/webpki#19
-    //
-    // Rejection is achieved by not matching any PermittedSubtrees, and matching all
-    // ExcludedSubtrees.
-    match subtrees {
-        Subtrees::PermittedSubtrees => false,
-        Subtrees::ExcludedSubtrees => true,
-    }
-}
-
 #[derive(Clone, Copy)]
 enum NameIteration {
     KeepGoing,
@@ -317,7 +513,12 @@ fn iterate_names(
         // will be rejected either way, but checking `at_end` before
         // attempting to parse the first entry allows us to return a better
         // error code.
+        let mut name_count = 0;
         while !subject_alt_name.at_end() {
+            name_count += 1;
+            if name_count > limits::MAX_SUBJECT_ALT_NAMES {
+                return Err(Error::MaximumSubjectAltNamesExceeded);
+            }
             let name = general_name(&mut subject_alt_name)?;
             match f(name) {
                 NameIteration::Stop(result) => {
@@ -384,7 +585,13 @@ fn general_name<'a>(input: &mut untrusted::Reader<'a>) -> Result<GeneralName<'a>
     let (tag, value) = der::read_tag_and_get_value(input)?;
     let name = match tag {
         DNS_NAME_TAG => GeneralName::DnsName(value),
-        DIRECTORY_NAME_TAG => GeneralName::DirectoryName(value),
+        // Unwrap the `Name` so that, like the subject field, the value is
+        // the RDNs themselves.
+        DIRECTORY_NAME_TAG => {
+            GeneralName::DirectoryName(value.read_all(Error::BadDer, |name| {
+                der::expect_tag_and_get_value(name, der::Tag::Sequence)
+            })?)
+        }
         IP_ADDRESS_TAG => GeneralName::IpAddress(value),
 
         OTHER_NAME_TAG
@@ -399,15 +606,13 @@ fn general_name<'a>(input: &mut untrusted::Reader<'a>) -> Result<GeneralName<'a>
     Ok(name)
 }
 
-static COMMON_NAME: untrusted::Input = untrusted::Input::from(&[85, 4, 3]);
-
 fn common_name(input: untrusted::Input) -> Result<Option<untrusted::Input>, Error> {
     let inner = &mut untrusted::Reader::new(input);
     der::nested(inner, der::Tag::Set, Error::BadDer, |tagged| {
         der::nested(tagged, der::Tag::Sequence, Error::BadDer, |tagged| {
             while !tagged.at_end() {
                 let name_oid = der::expect_tag_and_get_value(tagged, der::Tag::OID)?;
-                if name_oid == COMMON_NAME {
+                if oid::COMMON_NAME.matches(name_oid) {
                     return der::expect_tag_and_get_value(tagged, der::Tag::UTF8String).map(Some);
                 } else {
                     // discard unused name value
@@ -0,0 +1,424 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Builders of throwaway certificates, and clocks, for tests.
+//!
+//! Certificates are signed with Ed25519 keys derived from seeds, and
+//! Ed25519 signatures are deterministic, so the same inputs always build
+//! the same certificates. Verify them with `ED25519` as the supported
+//! signature algorithm.
+//!
+//! Requires the `test-utils` feature.
+
+use crate::{calendar, oid, Error, KeyPurposeId, Time};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use ring::{
+    digest,
+    signature::{self, KeyPair as _},
+};
+
+/// An Ed25519 key pair that signs test certificates.
+pub struct KeyPair(signature::Ed25519KeyPair);
+
+impl KeyPair {
+    /// Derives a key pair from `seed`.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        match signature::Ed25519KeyPair::from_seed_unchecked(seed) {
+            Ok(key_pair) => Self(key_pair),
+            Err(_) => unreachable!(), // Every 32-byte seed is a valid key.
+        }
+    }
+
+    fn public_key(&self) -> &[u8] {
+        self.0.public_key().as_ref()
+    }
+}
+
+/// A certificate to build: an end-entity certificate with serial number 1,
+/// valid from 1970 to the end of 9999, unless configured otherwise.
+#[derive(Clone)]
+pub struct CertBuilder<'a> {
+    common_name: &'a str,
+    ca: bool,
+    serial: u64,
+    not_before: Time,
+    not_after: Time,
+    dns_names: Vec<&'a str>,
+    ekus: Vec<KeyPurposeId>,
+}
+
+impl<'a> CertBuilder<'a> {
+    /// A certificate whose subject has the common name `common_name`.
+    pub fn new(common_name: &'a str) -> Self {
+        Self {
+            common_name,
+            ca: false,
+            serial: 1,
+            not_before: Time::from_seconds_since_unix_epoch(0),
+            // 9999-12-31T23:59:59Z, the last time a GeneralizedTime can hold.
+            not_after: Time::from_seconds_since_unix_epoch(253_402_300_799),
+            dns_names: Vec::new(),
+            ekus: Vec::new(),
+        }
+    }
+
+    /// Makes the certificate a CA certificate, with a subject key
+    /// identifier.
+    pub fn ca(mut self) -> Self {
+        self.ca = true;
+        self
+    }
+
+    /// Sets the serial number.
+    pub fn serial(mut self, serial: u64) -> Self {
+        self.serial = serial;
+        self
+    }
+
+    /// Sets the validity period. Both times must be before the year 10000.
+    pub fn validity(mut self, not_before: Time, not_after: Time) -> Self {
+        self.not_before = not_before;
+        self.not_after = not_after;
+        self
+    }
+
+    /// Adds a dNSName to the subjectAltName extension.
+    pub fn dns_name(mut self, dns_name: &'a str) -> Self {
+        self.dns_names.push(dns_name);
+        self
+    }
+
+    /// Adds a purpose to the extended key usage extension.
+    pub fn eku(mut self, eku: KeyPurposeId) -> Self {
+        self.ekus.push(eku);
+        self
+    }
+
+    /// Builds the certificate for `key`, signed by `key` itself, as a trust
+    /// anchor is.
+    pub fn self_signed(&self, key: &KeyPair) -> Vec<u8> {
+        self.issued_by(key, self.common_name, key)
+    }
+
+    /// Builds the certificate for `subject_key`, issued by the holder of
+    /// `issuer_key`, whose certificate has the common name
+    /// `issuer_common_name`.
+    pub fn issued_by(
+        &self,
+        subject_key: &KeyPair,
+        issuer_common_name: &str,
+        issuer_key: &KeyPair,
+    ) -> Vec<u8> {
+        let algorithm = tlv(SEQUENCE, &tlv(OID, oid::ED25519.value()));
+
+        let mut extensions = Vec::new();
+        let basic_constraints = if self.ca {
+            tlv(SEQUENCE, &tlv(BOOLEAN, &[0xff]))
+        } else {
+            tlv(SEQUENCE, &[])
+        };
+        extensions.extend(extension(
+            oid::BASIC_CONSTRAINTS.oid(),
+            true,
+            &basic_constraints,
+        ));
+        if self.ca {
+            let key_id = digest::digest(&digest::SHA256, subject_key.public_key());
+            extensions.extend(extension(
+                oid::SUBJECT_KEY_IDENTIFIER.oid(),
+                false,
+                &tlv(OCTET_STRING, &key_id.as_ref()[..20]),
+            ));
+        }
+        if !self.dns_names.is_empty() {
+            let names: Vec<u8> = self
+                .dns_names
+                .iter()
+                .flat_map(|name| tlv(DNS_NAME, name.as_bytes()))
+                .collect();
+            extensions.extend(extension(
+                oid::SUBJECT_ALT_NAME.oid(),
+                false,
+                &tlv(SEQUENCE, &names),
+            ));
+        }
+        if !self.ekus.is_empty() {
+            let purposes: Vec<u8> = self
+                .ekus
+                .iter()
+                .flat_map(|eku| tlv(OID, eku.oid().value()))
+                .collect();
+            extensions.extend(extension(
+                oid::EXT_KEY_USAGE.oid(),
+                false,
+                &tlv(SEQUENCE, &purposes),
+            ));
+        }
+
+        let mut validity = tlv(GENERALIZED_TIME, &generalized_time(self.not_before));
+        validity.extend(tlv(GENERALIZED_TIME, &generalized_time(self.not_after)));
+
+        let mut spki = algorithm.clone();
+        spki.extend(bit_string(subject_key.public_key()));
+
+        let mut tbs = tlv(VERSION, &tlv(INTEGER, &[2])); // v3
+        tbs.extend(tlv(INTEGER, &unsigned_integer(self.serial)));
+        tbs.extend(&algorithm);
+        tbs.extend(name(issuer_common_name));
+        tbs.extend(tlv(SEQUENCE, &validity));
+        tbs.extend(name(self.common_name));
+        tbs.extend(tlv(SEQUENCE, &spki));
+        tbs.extend(tlv(EXTENSIONS, &tlv(SEQUENCE, &extensions)));
+        let tbs = tlv(SEQUENCE, &tbs);
+
+        let signature = issuer_key.0.sign(&tbs);
+        let mut cert = tbs;
+        cert.extend(&algorithm);
+        cert.extend(bit_string(signature.as_ref()));
+        tlv(SEQUENCE, &cert)
+    }
+}
+
+/// A source of the time to verify at.
+pub trait Clock {
+    /// Returns the current time of the clock.
+    fn now(&self) -> Time;
+}
+
+/// A clock stopped at a fixed time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock(Time);
+
+impl FixedClock {
+    /// A clock stopped at `time`.
+    pub fn new(time: Time) -> Self {
+        Self(time)
+    }
+
+    /// A clock stopped at the given UTC date and time, e.g.
+    /// `FixedClock::at_utc(2017, 4, 17, 15, 8, 36)`, so that tests needn't
+    /// compute Unix timestamps by hand.
+    ///
+    /// Fails with `Error::BadDerTime` if the date or time is invalid or
+    /// before 1970.
+    pub fn at_utc(
+        year: u64,
+        month: u64,
+        day: u64,
+        hours: u64,
+        minutes: u64,
+        seconds: u64,
+    ) -> Result<Self, Error> {
+        if year < 1970
+            || !(1..=12).contains(&month)
+            || day < 1
+            || day > calendar::days_in_month(year, month)
+            || hours > 23
+            || minutes > 59
+            || seconds > 59
+        {
+            return Err(Error::BadDerTime);
+        }
+        calendar::time_from_ymdhms_utc(year, month, day, hours, minutes, seconds).map(Self)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Time {
+        self.0
+    }
+}
+
+/// A clock that runs ahead of another clock by a number of seconds, or
+/// behind it if the number is negative, to test tolerance of clock skew.
+#[derive(Clone, Copy, Debug)]
+pub struct SkewedClock<C> {
+    clock: C,
+    skew_secs: i64,
+}
+
+impl<C: Clock> SkewedClock<C> {
+    /// A clock `skew_secs` seconds ahead of `clock`.
+    pub fn new(clock: C, skew_secs: i64) -> Self {
+        Self { clock, skew_secs }
+    }
+}
+
+impl<C: Clock> Clock for SkewedClock<C> {
+    fn now(&self) -> Time {
+        let secs = self.clock.now().as_seconds_since_unix_epoch();
+        let skewed = match u64::try_from(self.skew_secs) {
+            Ok(ahead) => secs.saturating_add(ahead),
+            Err(_) => {
+                // `-(skew + 1)` can't overflow, unlike `-skew`.
+                let behind = u64::try_from(-(self.skew_secs + 1)).unwrap_or(0);
+                secs.saturating_sub(behind).saturating_sub(1)
+            }
+        };
+        Time::from_seconds_since_unix_epoch(skewed)
+    }
+}
+
+const BOOLEAN: u8 = 0x01;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OID: u8 = 0x06;
+const UTF8_STRING: u8 = 0x0c;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const DNS_NAME: u8 = 0x82; // [2] IMPLICIT IA5String
+const VERSION: u8 = 0xa0; // [0] EXPLICIT
+const EXTENSIONS: u8 = 0xa3; // [3] EXPLICIT
+
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let len = value.len().to_be_bytes();
+    let len = &len[len.iter().take_while(|&&b| b == 0).count()..];
+    let mut encoded = Vec::with_capacity(2 + len.len() + value.len());
+    encoded.push(tag);
+    match len {
+        [] => encoded.push(0),
+        [short] if *short < 0x80 => encoded.push(*short),
+        long => {
+            #[allow(clippy::as_conversions)] // At most 8.
+            encoded.push(0x80 | long.len() as u8);
+            encoded.extend_from_slice(long);
+        }
+    }
+    encoded.extend_from_slice(value);
+    encoded
+}
+
+fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut value = Vec::with_capacity(1 + bytes.len());
+    value.push(0); // No unused bits.
+    value.extend_from_slice(bytes);
+    tlv(BIT_STRING, &value)
+}
+
+// The value of a non-negative INTEGER: minimal, with a leading zero if the
+// high bit would otherwise be set.
+fn unsigned_integer(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let significant = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+    let mut integer = Vec::with_capacity(9);
+    if bytes[significant] & 0x80 != 0 {
+        integer.push(0);
+    }
+    integer.extend_from_slice(&bytes[significant..]);
+    integer
+}
+
+// A Name with a single commonName attribute.
+fn name(common_name: &str) -> Vec<u8> {
+    let mut attribute = tlv(OID, oid::COMMON_NAME.value());
+    attribute.extend(tlv(UTF8_STRING, common_name.as_bytes()));
+    tlv(SEQUENCE, &tlv(SET, &tlv(SEQUENCE, &attribute)))
+}
+
+fn extension(id: oid::Oid, critical: bool, value: &[u8]) -> Vec<u8> {
+    let mut extension = tlv(OID, id.value());
+    if critical {
+        extension.extend(tlv(BOOLEAN, &[0xff]));
+    }
+    extension.extend(tlv(OCTET_STRING, value));
+    tlv(SEQUENCE, &extension)
+}
+
+// The value of a GeneralizedTime, YYYYMMDDHHMMSSZ.
+fn generalized_time(time: Time) -> [u8; 15] {
+    let secs = time.as_seconds_since_unix_epoch();
+    let mut days = secs / 86400;
+    let mut year = 1970;
+    loop {
+        let days_in_year = 337 + calendar::days_in_month(year, 2);
+        if days < days_in_year {
+            break;
+        }
+        days -= days_in_year;
+        year += 1;
+    }
+    let mut month = 1;
+    while days >= calendar::days_in_month(year, month) {
+        days -= calendar::days_in_month(year, month);
+        month += 1;
+    }
+    let secs_of_day = secs % 86400;
+
+    let mut value = *b"00000000000000Z";
+    let mut write_digits = |range: core::ops::Range<usize>, mut n: u64| {
+        for digit in value[range].iter_mut().rev() {
+            #[allow(clippy::as_conversions)] // Less than 10.
+            let d = (n % 10) as u8;
+            *digit = b'0' + d;
+            n /= 10;
+        }
+    };
+    write_digits(0..4, year);
+    write_digits(4..6, month);
+    write_digits(6..8, days + 1);
+    write_digits(8..10, secs_of_day / 3600);
+    write_digits(10..12, secs_of_day / 60 % 60);
+    write_digits(12..14, secs_of_day % 60);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generalized_time_test() {
+        let time = |secs| generalized_time(Time::from_seconds_since_unix_epoch(secs));
+        assert_eq!(&time(0), b"19700101000000Z");
+        assert_eq!(&time(951_825_600), b"20000229120000Z");
+        assert_eq!(&time(1_492_441_716), b"20170417150836Z");
+        assert_eq!(&time(253_402_300_799), b"99991231235959Z");
+    }
+
+    #[test]
+    fn clock_test() {
+        let clock = FixedClock::at_utc(2017, 4, 17, 15, 8, 36).unwrap();
+        assert_eq!(
+            clock.now(),
+            Time::from_seconds_since_unix_epoch(1_492_441_716)
+        );
+        assert_eq!(
+            SkewedClock::new(clock, -36).now(),
+            Time::from_seconds_since_unix_epoch(1_492_441_680)
+        );
+        assert_eq!(
+            SkewedClock::new(FixedClock::new(Time::from_seconds_since_unix_epoch(5)), -10).now(),
+            Time::from_seconds_since_unix_epoch(0)
+        );
+        assert_eq!(
+            FixedClock::at_utc(2017, 2, 29, 0, 0, 0),
+            Err(Error::BadDerTime)
+        );
+        assert_eq!(
+            FixedClock::at_utc(1969, 12, 31, 23, 59, 59),
+            Err(Error::BadDerTime)
+        );
+    }
+
+    #[test]
+    fn unsigned_integer_test() {
+        assert_eq!(unsigned_integer(0), vec![0]);
+        assert_eq!(unsigned_integer(1), vec![1]);
+        assert_eq!(unsigned_integer(0x80), vec![0, 0x80]);
+        assert_eq!(unsigned_integer(0x0102), vec![1, 2]);
+    }
+}
@@ -40,6 +40,20 @@ impl Time {
     pub fn from_seconds_since_unix_epoch(secs: u64) -> Self {
         Self(secs)
     }
+
+    pub(crate) fn as_seconds_since_unix_epoch(self) -> u64 {
+        self.0
+    }
+
+    /// Create a `webpki::Time` from the value (without tag and length) of a
+    /// DER-encoded GeneralizedTime, such as the `genTime` of an RFC 3161
+    /// time-stamp token. Fractional seconds are accepted and ignored.
+    ///
+    /// This is useful for verifying a certificate as of the time a trusted
+    /// time-stamp says a signature was made, rather than the current time.
+    pub fn from_generalized_time(value: &[u8]) -> Result<Self, crate::Error> {
+        crate::der::generalized_time_with_fraction(untrusted::Input::from(value))
+    }
 }
 
 #[cfg(feature = "std")]
@@ -0,0 +1,286 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Recording the decisions of a verification, to reproduce it later.
+
+use crate::{
+    Candidate, Counter, EkuMismatch, EndEntityCert, Error, PresentedName, Time, TrustAnchor, Usage,
+    VerificationDiagnostics, VerificationOptions, Warning,
+};
+use alloc::vec::Vec;
+use core::{cell::RefCell, convert::TryFrom};
+use ring::digest;
+
+/// A record of a verification made by
+/// `EndEntityCert::verify_for_usage_with_trace`, for auditing it later.
+///
+/// The trace holds a digest of everything the verification depended on (the
+/// certificates, trust anchors, usage and options), the time it was made
+/// for, whether it succeeded, and each candidate issuer considered, in
+/// order, with the depth of the certificate it would have issued. This is
+/// enough to tell which path was chosen, and how path building got there.
+///
+/// `to_bytes` serializes the trace, to be stored alongside the inputs.
+/// `replay` verifies those inputs again at the recorded time and checks
+/// that every decision is the same. The reasons why candidates were
+/// rejected are not stored, since they are reproduced by replaying with
+/// `VerificationDiagnostics` in the options.
+///
+/// As for `VerificationToken`, the certificate and chain checks, the
+/// revocation status provider and the verification functions of custom
+/// signature algorithms are code, which can't be digested; a replay is only
+/// faithful if they behave as they did.
+///
+/// Requires the `alloc` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationTrace {
+    digest: [u8; 32],
+    time: Time,
+    verified: bool,
+    candidates: Vec<(Candidate, usize)>,
+}
+
+// The first byte of a serialized trace, to tell future formats apart.
+const FORMAT_VERSION: u8 = 1;
+
+impl VerificationTrace {
+    /// Returns the time the verification was made for.
+    pub fn time(&self) -> Time {
+        self.time
+    }
+
+    /// Returns whether the verification succeeded.
+    pub fn verified(&self) -> bool {
+        self.verified
+    }
+
+    /// Returns each candidate issuer considered, in order, with the depth
+    /// of the certificate it would have issued, as reported to
+    /// `VerificationDiagnostics::candidate_considered`.
+    pub fn candidates(&self) -> &[(Candidate, usize)] {
+        &self.candidates
+    }
+
+    /// Returns the serialization of the trace.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + 32 + 8 + 1 + 8 + self.candidates.len() * 17);
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&self.digest);
+        bytes.extend_from_slice(&self.time.as_seconds_since_unix_epoch().to_be_bytes());
+        bytes.push(u8::from(self.verified));
+        bytes.extend_from_slice(&len(self.candidates.len()));
+        for (candidate, depth) in &self.candidates {
+            let (kind, index) = match candidate {
+                Candidate::TrustAnchor(index) => (0, index),
+                Candidate::Intermediate(index) => (1, index),
+            };
+            bytes.push(kind);
+            bytes.extend_from_slice(&len(*index));
+            bytes.extend_from_slice(&len(*depth));
+        }
+        bytes
+    }
+
+    /// Parses a trace serialized with `to_bytes`, returning `None` if
+    /// `bytes` isn't one.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        fn read<'b>(reader: &mut untrusted::Reader<'b>, len: usize) -> Option<&'b [u8]> {
+            reader
+                .read_bytes(len)
+                .ok()
+                .map(|bytes| bytes.as_slice_less_safe())
+        }
+        fn read_u64(reader: &mut untrusted::Reader) -> Option<u64> {
+            Some(u64::from_be_bytes(
+                <[u8; 8]>::try_from(read(reader, 8)?).ok()?,
+            ))
+        }
+        fn read_usize(reader: &mut untrusted::Reader) -> Option<usize> {
+            usize::try_from(read_u64(reader)?).ok()
+        }
+
+        let mut reader = untrusted::Reader::new(untrusted::Input::from(bytes));
+        if read(&mut reader, 1)? != [FORMAT_VERSION] {
+            return None;
+        }
+        let mut digest = [0; 32];
+        digest.copy_from_slice(read(&mut reader, 32)?);
+        let time = Time::from_seconds_since_unix_epoch(read_u64(&mut reader)?);
+        let verified = match read(&mut reader, 1)? {
+            [0] => false,
+            [1] => true,
+            _ => return None,
+        };
+        let count = read_usize(&mut reader)?;
+        let mut candidates = Vec::new();
+        for _ in 0..count {
+            let candidate = match read(&mut reader, 1)? {
+                [0] => Candidate::TrustAnchor(read_usize(&mut reader)?),
+                [1] => Candidate::Intermediate(read_usize(&mut reader)?),
+                _ => return None,
+            };
+            candidates.push((candidate, read_usize(&mut reader)?));
+        }
+        if !reader.at_end() {
+            return None;
+        }
+        Some(Self {
+            digest,
+            time,
+            verified,
+            candidates,
+        })
+    }
+
+    /// Verifies `cert` for `usage` as configured by `options` again, as
+    /// `EndEntityCert::verify_for_usage_with_options` does, but at the time
+    /// the trace was recorded, returning the result.
+    ///
+    /// The inputs and the options, other than the time and any
+    /// `VerificationDiagnostics`, must be those the trace was recorded with,
+    /// and the same candidate issuers must be considered in the same order
+    /// with the same outcome; otherwise this fails with
+    /// `Error::TraceMismatch`. Any `VerificationDiagnostics` in `options` is
+    /// told about the replayed verification.
+    pub fn replay(
+        &self,
+        cert: &EndEntityCert,
+        trust_anchors: &[TrustAnchor],
+        intermediate_certs: &[&[u8]],
+        options: &VerificationOptions,
+        usage: Usage,
+    ) -> Result<(), Error> {
+        let options = VerificationOptions {
+            time: self.time,
+            ..*options
+        };
+        let (result, replayed) = record(cert, trust_anchors, intermediate_certs, &options, usage);
+        if replayed != *self {
+            return Err(Error::TraceMismatch);
+        }
+        result
+    }
+}
+
+/// Verifies `cert` as `EndEntityCert::verify_for_usage_with_trace` does.
+pub(crate) fn record(
+    cert: &EndEntityCert,
+    trust_anchors: &[TrustAnchor],
+    intermediate_certs: &[&[u8]],
+    options: &VerificationOptions,
+    usage: Usage,
+) -> (Result<(), Error>, VerificationTrace) {
+    let recorder = Recorder {
+        diagnostics: options.diagnostics,
+        candidates: RefCell::new(Vec::new()),
+    };
+    let result = cert.verify_for_usage_with_options(
+        trust_anchors,
+        intermediate_certs,
+        &options.with_diagnostics(&recorder),
+        usage,
+    );
+    let trace = VerificationTrace {
+        digest: inputs_digest(cert, trust_anchors, intermediate_certs, options, usage),
+        time: options.time,
+        verified: result.is_ok(),
+        candidates: recorder.candidates.into_inner(),
+    };
+    (result, trace)
+}
+
+// Records the candidates considered, passing everything on to the
+// `VerificationDiagnostics` of the options, if any.
+struct Recorder<'a> {
+    diagnostics: Option<&'a dyn VerificationDiagnostics>,
+    candidates: RefCell<Vec<(Candidate, usize)>>,
+}
+
+impl VerificationDiagnostics for Recorder<'_> {
+    fn issuer_rejected(&self, error: Error) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.issuer_rejected(error);
+        }
+    }
+
+    fn candidate_considered(&self, candidate: Candidate, depth: usize) {
+        self.candidates.borrow_mut().push((candidate, depth));
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.candidate_considered(candidate, depth);
+        }
+    }
+
+    fn count(&self, counter: Counter) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.count(counter);
+        }
+    }
+
+    fn warning(&self, warning: Warning) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.warning(warning);
+        }
+    }
+
+    fn name_presented(&self, name: PresentedName) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.name_presented(name);
+        }
+    }
+
+    fn required_eku_not_found(&self, mismatch: EkuMismatch) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.required_eku_not_found(mismatch);
+        }
+    }
+}
+
+#[allow(clippy::as_conversions)] // usize is at most 64 bits.
+fn len(len: usize) -> [u8; 8] {
+    (len as u64).to_be_bytes()
+}
+
+// As for `VerificationToken`, every variable-length input is prefixed with
+// its length, so that distinct inputs cannot hash the same.
+fn inputs_digest(
+    cert: &EndEntityCert,
+    trust_anchors: &[TrustAnchor],
+    intermediate_certs: &[&[u8]],
+    options: &VerificationOptions,
+    usage: Usage,
+) -> [u8; 32] {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    let mut update = |bytes: &[u8]| {
+        ctx.update(&len(bytes.len()));
+        ctx.update(bytes);
+    };
+    update(cert.as_der());
+    update(&len(trust_anchors.len()));
+    for anchor in trust_anchors {
+        update(anchor.subject);
+        update(anchor.spki);
+        update(&[u8::from(anchor.name_constraints.is_some())]);
+        update(anchor.name_constraints.unwrap_or(&[]));
+    }
+    update(&len(intermediate_certs.len()));
+    for intermediate_cert in intermediate_certs {
+        update(intermediate_cert);
+    }
+    update(usage.eku().oid().value());
+    options.update_policy_digest(&mut update);
+
+    let mut digest = [0; 32];
+    digest.copy_from_slice(ctx.finish().as_ref());
+    digest
+}
@@ -1,9 +1,15 @@
 use crate::cert::{lenient_certificate_serial_number, Cert};
 use crate::{
-    cert::{parse_cert, EndEntityOrCa},
-    der, Error,
+    cert::{parse_cert, parse_cert_internal, EndEntityOrCa, V1Certs},
+    der, DnsNameRef, Error, Time,
 };
 
+#[cfg(feature = "tls-server-verify")]
+use crate::{subject_name, SubjectNameRef};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 /// A trust anchor (a.k.a. root CA).
 ///
 /// Traditionally, certificate verification libraries have represented trust
@@ -13,7 +19,7 @@ use crate::{
 /// essential elements of trust anchors. The `TrustAnchor::try_from_cert_der`
 /// function allows converting X.509 certificates to to the minimized
 /// `TrustAnchor` representation, either at runtime or in a build script.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct TrustAnchor<'a> {
     /// The value of the `subject` field of the trust anchor.
     pub subject: &'a [u8],
@@ -26,19 +32,118 @@ pub struct TrustAnchor<'a> {
     pub name_constraints: Option<&'a [u8]>,
 }
 
+/// Restricts a trust anchor, identified by its subjectPublicKeyInfo, to TLS
+/// servers whose DNS names are under one of `dns_suffixes`, e.g. a
+/// corporate TLS interception root to `corp.internal`.
+///
+/// This is the caller's policy for the anchor, independent of any name
+/// constraints in `TrustAnchor::name_constraints`, and is enforced with
+/// `VerificationOptions::with_anchor_dns_suffixes`. A name is
+/// under a suffix if it is the suffix or a subdomain of it, as for a dNSName
+/// name constraint. An anchor with several entries is trusted for the
+/// suffixes of all of them.
+#[derive(Clone, Copy, Debug)]
+pub struct AnchorDnsSuffixes<'a> {
+    /// The value of the `subjectPublicKeyInfo` field of the trust anchor, as
+    /// in `TrustAnchor::spki`.
+    pub spki: &'a [u8],
+
+    /// The DNS suffixes the trust anchor is trusted for.
+    pub dns_suffixes: &'a [DnsNameRef<'a>],
+}
+
+impl AnchorDnsSuffixes<'_> {
+    // Whether `subject_name` may be verified with a path ending at
+    // `trust_anchor`: always if no entry of `restrictions` is for it, and
+    // otherwise only if it is a DNS name under one of their suffixes.
+    #[cfg(feature = "tls-server-verify")]
+    pub(crate) fn allow(
+        restrictions: &[Self],
+        trust_anchor: &TrustAnchor,
+        subject_name: SubjectNameRef,
+    ) -> bool {
+        let mut restrictions = restrictions
+            .iter()
+            .filter(|restriction| restriction.spki == trust_anchor.spki)
+            .peekable();
+        if restrictions.peek().is_none() {
+            return true;
+        }
+        let dns_name = match subject_name {
+            SubjectNameRef::DnsName(dns_name) => dns_name,
+            SubjectNameRef::IpAddress(_) => return false,
+        };
+        restrictions
+            .flat_map(|restriction| restriction.dns_suffixes)
+            .any(|&suffix| subject_name::is_under_suffix(dns_name, suffix))
+    }
+}
+
+/// When a trust anchor, identified by its subjectPublicKeyInfo, expires, for
+/// `VerificationOptions::with_anchor_expiries`.
+///
+/// `TrustAnchor` has no validity period, since it isn't checked when
+/// verifying a path, so expiry is given separately, usually as the notAfter
+/// time of the root certificate the anchor was made from.
+#[derive(Clone, Copy, Debug)]
+pub struct AnchorExpiry<'a> {
+    /// The value of the `subjectPublicKeyInfo` field of the trust anchor, as
+    /// in `TrustAnchor::spki`.
+    pub spki: &'a [u8],
+
+    /// The last time at which the trust anchor is valid.
+    pub not_after: Time,
+}
+
+impl<'a> AnchorExpiry<'a> {
+    /// Reads the expiry of the trust anchor that
+    /// `TrustAnchor::try_from_cert_der` makes from `cert_der`: the notAfter
+    /// time of the certificate. As there, the certificate is not validated,
+    /// and v1 certificates are accepted.
+    pub fn try_from_cert_der(cert_der: &'a [u8]) -> Result<Self, Error> {
+        let cert = parse_cert_internal(
+            untrusted::Input::from(cert_der),
+            EndEntityOrCa::EndEntity,
+            V1Certs::Accept,
+        )?;
+        let not_after = cert.validity.read_all(Error::BadDer, |value| {
+            let _not_before = der::time_choice(value)?;
+            der::time_choice(value)
+        })?;
+        Ok(Self {
+            spki: cert.spki.value().as_slice_less_safe(),
+            not_after,
+        })
+    }
+}
+
 /// Trust anchors which may be used for authenticating servers.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct TlsServerTrustAnchors<'a>(pub &'a [TrustAnchor<'a>]);
 
 /// Trust anchors which may be used for authenticating clients.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct TlsClientTrustAnchors<'a>(pub &'a [TrustAnchor<'a>]);
 
+/// Trust anchors which may be used for authenticating RFC 3161 time-stamping
+/// authorities.
+#[derive(Clone, Copy, Debug)]
+pub struct TsaTrustAnchors<'a>(pub &'a [TrustAnchor<'a>]);
+
 impl<'a> TrustAnchor<'a> {
     /// Interprets the given DER-encoded certificate as a `TrustAnchor`. The
     /// certificate is not validated. In particular, there is no check that the
     /// certificate is self-signed or even that the certificate has the cA basic
     /// constraint.
+    ///
+    /// An intermediate certificate can be trusted directly this way, like
+    /// OpenSSL's `X509_V_FLAG_PARTIAL_CHAIN`: a path whose certificate is
+    /// issued by it ends there successfully, without any certificate above
+    /// it being needed. Trust anchors are tried before intermediate
+    /// certificates, so such a path is preferred over a longer path through
+    /// the same intermediate to its root. The intermediate's name constraints
+    /// still apply, but its validity period and path length constraint
+    /// don't, as for any trust anchor.
     pub fn try_from_cert_der(cert_der: &'a [u8]) -> Result<Self, Error> {
         let cert_der = untrusted::Input::from(cert_der);
 
@@ -57,6 +162,38 @@ impl<'a> TrustAnchor<'a> {
             Err(err) => Err(err),
         }
     }
+
+    /// Returns the DER encoding of the trust anchor's subject, i.e. `subject`
+    /// with its SEQUENCE tag and length, as a TLS `DistinguishedName`.
+    ///
+    /// Returns `None` if the subject is too long to be encoded with two
+    /// length octets, which no parsed certificate's subject is.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn subject_der(&self) -> Option<Vec<u8>> {
+        let header = der::Header::new(der::Tag::Sequence, self.subject.len())?;
+        let mut encoded = Vec::with_capacity(header.as_ref().len() + self.subject.len());
+        encoded.extend_from_slice(header.as_ref());
+        encoded.extend_from_slice(self.subject);
+        Some(encoded)
+    }
+}
+
+impl<'a> TlsClientTrustAnchors<'a> {
+    /// Returns the DER-encoded subjects of the trust anchors, in order, for
+    /// the `certificate_authorities` of a TLS CertificateRequest (RFC 8446
+    /// Section 4.2.4, RFC 5246 Section 7.4.4), so that the CAs a server
+    /// hints at are exactly those it accepts client certificates from.
+    ///
+    /// Anchors whose subject cannot be encoded (see
+    /// `TrustAnchor::subject_der`) are left out.
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn distinguished_names(&self) -> Vec<Vec<u8>> {
+        self.0.iter().filter_map(TrustAnchor::subject_der).collect()
+    }
 }
 
 impl<'a> From<Cert<'a>> for TrustAnchor<'a> {
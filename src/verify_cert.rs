@@ -13,37 +13,226 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use crate::{
-    cert::{self, Cert, EndEntityOrCa},
-    der, signed_data, subject_name, time, Error, SignatureAlgorithm, TrustAnchor,
+    cert::{self, BasicConstraints, Cert, EndEntityOrCa},
+    der,
+    oid::Oid,
+    signed_data, subject_name, time, Candidate, Counter, EkuMismatch, Error, TrustAnchor,
+    VerificationOptions, Warning,
 };
+use core::cell::Cell;
 
+#[cfg(feature = "alloc")]
+use {crate::fallible, alloc::vec::Vec};
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn build_chain(
     required_eku_if_present: KeyPurposeId,
-    supported_sig_algs: &[&SignatureAlgorithm],
+    precertificates: Precertificates,
+    options: &VerificationOptions,
     trust_anchors: &[TrustAnchor],
     intermediate_certs: &[&[u8]],
     cert: &Cert,
-    time: time::Time,
     sub_ca_count: usize,
+    verify_path: &dyn Fn(&TrustAnchor, &Cert) -> Result<(), Error>,
 ) -> Result<(), Error> {
+    build_chains(
+        required_eku_if_present,
+        precertificates,
+        options,
+        trust_anchors,
+        intermediate_certs,
+        cert,
+        sub_ca_count,
+        &|trust_anchor, path| verify_path(trust_anchor, path).map_err(PathError::Invalid),
+    )
+    .map_err(|err| match err {
+        PathError::Invalid(err) => err,
+        // Only paths that were already rejected in an earlier round of the
+        // shortest-path search are passed over.
+        PathError::Continue => Error::UnknownIssuer,
+    })
+}
+
+/// Why no path was accepted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PathError {
+    /// Every path was rejected; the error is reported to
+    /// `VerificationDiagnostics::issuer_rejected`.
+    Invalid(Error),
+
+    /// A valid path was passed over to go on to the next one, e.g. to find
+    /// all of them. Unlike a rejection, this isn't reported to
+    /// `VerificationDiagnostics`.
+    Continue,
+}
+
+impl From<Error> for PathError {
+    fn from(error: Error) -> Self {
+        Self::Invalid(error)
+    }
+}
+
+/// Builds paths as `build_chain` does, except that `verify_path` may return
+/// `PathError::Continue` to go on to the next path.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn build_chains(
+    required_eku_if_present: KeyPurposeId,
+    precertificates: Precertificates,
+    options: &VerificationOptions,
+    trust_anchors: &[TrustAnchor],
+    intermediate_certs: &[&[u8]],
+    cert: &Cert,
+    sub_ca_count: usize,
+    verify_path: &dyn Fn(&TrustAnchor, &Cert) -> Result<(), PathError>,
+) -> Result<(), PathError> {
+    // As in the hooks of the entry points, a path that is only rejected
+    // because of its trust anchor is remembered, so that the caller can be
+    // told why no path was found.
+    let found_restricted_path = Cell::new(false);
+    // Paths with fewer intermediate certificates than this were already
+    // verified in an earlier round of the iterative deepening below.
+    let min_sub_ca_count = Cell::new(0);
+    let verify_path = |trust_anchor: &TrustAnchor, path: &Cert| {
+        if !options.anchor_trusted_for_name(trust_anchor) {
+            found_restricted_path.set(true);
+            return Err(Error::AnchorNotTrustedForName.into());
+        }
+        if sub_ca_count_of(path) < min_sub_ca_count.get() {
+            return Err(PathError::Continue);
+        }
+        verify_path(trust_anchor, path)
+    };
+
+    // With `prefer_shortest_path`, iterative deepening: paths are looked for
+    // with no intermediate certificates at first, then with one more each
+    // time, so that the first path found is a shortest one. A search that
+    // was never stopped by the limit would find nothing more with a higher
+    // one. Each round only verifies the paths that are new in it, so that a
+    // path is only verified once even when the callers go on looking for
+    // more of them, as `EndEntityCert::enumerate_valid_paths` does.
+    let mut max_sub_ca_count = match options.prefer_shortest_path {
+        true => 0,
+        false => options.max_sub_ca_count,
+    };
+    let result = loop {
+        let depth_limited = Cell::new(false);
+        let result = build_chain_inner(
+            required_eku_if_present,
+            precertificates,
+            &VerificationOptions {
+                max_sub_ca_count,
+                ..*options
+            },
+            trust_anchors,
+            intermediate_certs,
+            cert,
+            sub_ca_count,
+            &verify_path,
+            &depth_limited,
+        );
+        if result.is_ok() || !depth_limited.get() || max_sub_ca_count >= options.max_sub_ca_count {
+            break result;
+        }
+        max_sub_ca_count += 1;
+        min_sub_ca_count.set(max_sub_ca_count);
+    };
+
+    match result {
+        Err(PathError::Invalid(Error::UnknownIssuer))
+        | Err(PathError::Invalid(Error::PathLoopDetected))
+        | Err(PathError::Continue)
+            if found_restricted_path.get() =>
+        {
+            Err(Error::AnchorNotTrustedForName.into())
+        }
+        result => result,
+    }
+}
+
+// `depth_limited` is set if a candidate issuer is rejected because the path
+// would be longer than `options.max_sub_ca_count` allows.
+#[allow(clippy::too_many_arguments)]
+fn build_chain_inner(
+    required_eku_if_present: KeyPurposeId,
+    precertificates: Precertificates,
+    options: &VerificationOptions,
+    trust_anchors: &[TrustAnchor],
+    intermediate_certs: &[&[u8]],
+    cert: &Cert,
+    sub_ca_count: usize,
+    verify_path: &dyn Fn(&TrustAnchor, &Cert) -> Result<(), PathError>,
+    depth_limited: &Cell<bool>,
+) -> Result<(), PathError> {
     let used_as_ca = used_as_ca(&cert.ee_or_ca);
 
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("build_chain", sub_ca_count).entered();
+
+    if used_as_ca == UsedAsCa::No {
+        options.check_input_sizes(cert, intermediate_certs)?;
+    }
+
+    // The position of `cert` on the path, counting from the end-entity
+    // certificate, for `VerificationDiagnostics`.
+    let depth = match used_as_ca {
+        UsedAsCa::No => 0,
+        UsedAsCa::Yes => sub_ca_count + 1,
+    };
+
     check_issuer_independent_properties(
         cert,
-        time,
+        options,
         used_as_ca,
-        sub_ca_count,
         required_eku_if_present,
-    )?;
+        precertificates,
+    )
+    .map_err(|err| {
+        if err == Error::RequiredEkuNotFound {
+            options.eku_not_found(EkuMismatch {
+                required: required_eku_if_present,
+                depth,
+                eku: cert.eku,
+            });
+        }
+        err
+    })?;
+    options.check_rsa_exponent(cert.spki.value())?;
+    if used_as_ca == UsedAsCa::No {
+        options.check_leaf_validity(cert)?;
+        options.check_leaf_serial(cert)?;
+    }
+    if let Some(profile) = options.web_pki_profile {
+        profile.check(cert, options, depth)?;
+    }
+    options.check_cert(cert)?;
+
+    if !cert.serial_is_conforming() {
+        options.warn(Warning::NonConformingSerial { depth });
+    }
+
+    if used_as_ca == UsedAsCa::Yes {
+        if cert.subject_key_id()?.is_none() {
+            if options.require_ca_subject_key_id {
+                return Err(Error::MissingSubjectKeyId.into());
+            }
+            options.warn(Warning::MissingSubjectKeyId { depth });
+        }
+
+        match cert.key_cert_sign()? {
+            Some(true) => (),
+            Some(false) => return Err(Error::KeyCertSignNotAsserted.into()),
+            None if options.require_ca_key_usage => return Err(Error::MissingKeyUsage.into()),
+            None => options.warn(Warning::MissingKeyUsage { depth }),
+        }
+    }
 
     // TODO: HPKP checks.
 
     match used_as_ca {
         UsedAsCa::Yes => {
-            const MAX_SUB_CA_COUNT: usize = 6;
-
-            if sub_ca_count >= MAX_SUB_CA_COUNT {
-                return Err(Error::UnknownIssuer);
+            if sub_ca_count >= options.max_sub_ca_count {
+                depth_limited.set(true);
+                return Err(Error::UnknownIssuer.into());
             }
         }
         UsedAsCa::No => {
@@ -61,27 +250,59 @@ pub(crate) fn build_chain(
             subject_name::SubjectCommonNameContents::Ignore
         };
 
-    // TODO: revocation.
+    // The status of `cert` is looked up by its issuer's key, so a revoked
+    // `cert` only shows up as a failed candidate issuer; it is remembered so
+    // that the caller can be told why no path was found.
+    let revocation_error = Cell::new(None);
+    // Likewise an intermediate certificate that may not sign certificates
+    // only shows up as a failed candidate issuer.
+    let key_usage_error = Cell::new(None);
+    let check_revocation = |issuer_spki: untrusted::Input| {
+        options.check_revocation(cert, issuer_spki).map_err(|err| {
+            revocation_error.set(Some(err));
+            err
+        })
+    };
 
-    let result = loop_while_non_fatal_error(trust_anchors, |trust_anchor: &TrustAnchor| {
+    // With an issuer key order, the candidates are tried in several passes;
+    // see `VerificationOptions::anchor_pass` and `issuer_pass`.
+    let candidates = (0..options.anchor_passes()).flat_map(|pass| {
+        trust_anchors
+            .iter()
+            .enumerate()
+            .map(move |(index, trust_anchor)| (pass, index, trust_anchor))
+    });
+    let result = loop_while_non_fatal_error(candidates, |(pass, index, trust_anchor)| {
         let trust_anchor_subject = untrusted::Input::from(trust_anchor.subject);
-        if cert.issuer != trust_anchor_subject {
-            return Err(Error::UnknownIssuer);
+        if !subject_name::names_match(cert.issuer, trust_anchor_subject)
+            || options.anchor_pass(trust_anchor) != pass
+        {
+            return Err(Error::UnknownIssuer.into());
         }
+        options.count(Counter::AnchorTried);
+        options.consider(Candidate::TrustAnchor(index), depth);
 
-        let name_constraints = trust_anchor.name_constraints.map(untrusted::Input::from);
+        options.report((|| {
+            let name_constraints = trust_anchor.name_constraints.map(untrusted::Input::from);
 
-        untrusted::read_all_optional(name_constraints, Error::BadDer, |value| {
-            subject_name::check_name_constraints(value, cert, subject_common_name_contents)
-        })?;
+            untrusted::read_all_optional(name_constraints, Error::BadDer, |value| {
+                subject_name::check_name_constraints(value, cert, subject_common_name_contents)
+            })?;
 
-        let trust_anchor_spki = untrusted::Input::from(trust_anchor.spki);
+            let trust_anchor_spki = untrusted::Input::from(trust_anchor.spki);
 
-        // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
+            // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
 
-        check_signatures(supported_sig_algs, cert, trust_anchor_spki)?;
+            options.check_rsa_exponent(trust_anchor_spki)?;
+            check_signatures(options, cert, depth, trust_anchor_spki)?;
+            options.check_anchor_expiry(trust_anchor, index)?;
+            options.check_not_blocked_by_issuer(cert, trust_anchor_spki)?;
+            check_revocation(trust_anchor_spki)?;
+            options.check_acceptable_ca(trust_anchor, cert)?;
+            options.check_chain(trust_anchor, cert, intermediate_certs)?;
 
-        Ok(())
+            verify_path(trust_anchor, cert)
+        })())
     });
 
     // If the error is not fatal, then keep going.
@@ -89,62 +310,213 @@ pub(crate) fn build_chain(
         return Ok(());
     }
 
-    loop_while_non_fatal_error(intermediate_certs, |cert_der| {
-        let potential_issuer =
-            cert::parse_cert(untrusted::Input::from(cert_der), EndEntityOrCa::Ca(cert))?;
-
-        if potential_issuer.subject != cert.issuer {
-            return Err(Error::UnknownIssuer);
+    // Parses the intermediate certificate at `index`, returning it if it is
+    // a candidate issuer of `cert`. The intermediates may be given in any
+    // order, and some peers send the same certificate more than once; only
+    // its first occurrence is a candidate.
+    let parse_candidate = |index: usize| {
+        let cert_der = intermediate_certs[index];
+        if intermediate_certs[..index].contains(&cert_der) {
+            return None;
+        }
+        options.count(Counter::CertParsed);
+        let potential_issuer = cert::parse_cert_internal(
+            untrusted::Input::from(cert_der),
+            EndEntityOrCa::Ca(cert),
+            options.v1_intermediates,
+        )
+        .ok()?;
+        if !subject_name::names_match(cert.issuer, potential_issuer.subject) {
+            return None;
         }
+        Some(potential_issuer)
+    };
+
+    let try_candidate = |index: usize, potential_issuer: &Cert| {
+        options.consider(Candidate::Intermediate(index), depth);
 
-        // Prevent loops; see RFC 4158 section 5.2.
-        let mut prev = cert;
-        loop {
-            if potential_issuer.spki.value() == prev.spki.value()
-                && potential_issuer.subject == prev.subject
-            {
-                return Err(Error::UnknownIssuer);
+        options.report((|| {
+            // Prevent loops; see RFC 4158 section 5.2. A key that is already
+            // on the path under another name, as when a CA's key is
+            // cross-signed under both its old and new names, is not a loop;
+            // `VerificationOptions::prefer_shortest_path` avoids the longer
+            // paths through it.
+            let mut prev = cert;
+            loop {
+                if potential_issuer.spki.value() == prev.spki.value()
+                    && potential_issuer.subject == prev.subject
+                {
+                    return Err(Error::PathLoopDetected.into());
+                }
+                match &prev.ee_or_ca {
+                    EndEntityOrCa::EndEntity => {
+                        break;
+                    }
+                    EndEntityOrCa::Ca(child_cert) => {
+                        prev = child_cert;
+                    }
+                }
             }
-            match &prev.ee_or_ca {
-                EndEntityOrCa::EndEntity => {
-                    break;
+
+            untrusted::read_all_optional(
+                potential_issuer.name_constraints,
+                Error::BadDer,
+                |value| {
+                    subject_name::check_name_constraints(value, cert, subject_common_name_contents)
+                },
+            )?;
+
+            options.check_not_blocked_by_issuer(cert, potential_issuer.spki.value())?;
+            check_revocation(potential_issuer.spki.value())?;
+
+            let next_sub_ca_count = match used_as_ca {
+                UsedAsCa::No => sub_ca_count,
+                UsedAsCa::Yes => sub_ca_count + 1,
+            };
+
+            build_chain_inner(
+                required_eku_if_present,
+                precertificates,
+                options,
+                trust_anchors,
+                intermediate_certs,
+                potential_issuer,
+                next_sub_ca_count,
+                verify_path,
+                depth_limited,
+            )
+            .map_err(|err| {
+                if let PathError::Invalid(err @ Error::KeyCertSignNotAsserted)
+                | PathError::Invalid(err @ Error::MissingKeyUsage) = err
+                {
+                    key_usage_error.set(Some(err));
                 }
-                EndEntityOrCa::Ca(child_cert) => {
-                    prev = child_cert;
+                err
+            })
+        })())
+    };
+
+    // With acceptable CAs, the candidates are tried in two passes, those
+    // leading to an acceptable CA first, each split further by an issuer key
+    // order; see `VerificationOptions::issuer_pass`.
+    let result = match options.issuer_passes() {
+        // Without them, each candidate is parsed as it is reached.
+        1 => {
+            let candidates = (0..intermediate_certs.len())
+                .filter_map(|index| Some((index, parse_candidate(index)?)));
+            loop_while_non_fatal_error(candidates, |(index, potential_issuer)| {
+                try_candidate(index, &potential_issuer)
+            })
+        }
+
+        // Each candidate is parsed once, and kept for the later passes.
+        #[cfg(feature = "alloc")]
+        passes => {
+            let mut parsed = Vec::new();
+            for index in 0..intermediate_certs.len() {
+                if let Some(potential_issuer) = parse_candidate(index) {
+                    let pass = options.issuer_pass(&potential_issuer);
+                    fallible::push(&mut parsed, (pass, index, potential_issuer))?;
                 }
             }
+            let candidates = (0..passes).flat_map(|pass| {
+                parsed
+                    .iter()
+                    .filter(move |(candidate_pass, _, _)| *candidate_pass == pass)
+            });
+            loop_while_non_fatal_error(candidates, |(_, index, potential_issuer)| {
+                try_candidate(*index, potential_issuer)
+            })
+        }
+
+        // Without a heap to keep them in, the candidates are parsed again in
+        // each pass.
+        #[cfg(not(feature = "alloc"))]
+        passes => {
+            let candidates = (0..passes)
+                .flat_map(|pass| (0..intermediate_certs.len()).map(move |index| (pass, index)));
+            loop_while_non_fatal_error(candidates, |(pass, index)| match parse_candidate(index) {
+                Some(potential_issuer) if options.issuer_pass(&potential_issuer) == pass => {
+                    try_candidate(index, &potential_issuer)
+                }
+                _ => Err(Error::UnknownIssuer.into()),
+            })
         }
+    };
 
-        untrusted::read_all_optional(potential_issuer.name_constraints, Error::BadDer, |value| {
-            subject_name::check_name_constraints(value, cert, subject_common_name_contents)
-        })?;
+    match (
+        result,
+        revocation_error.get().or_else(|| key_usage_error.get()),
+    ) {
+        (Err(PathError::Invalid(Error::UnknownIssuer)), Some(err))
+        | (Err(PathError::Invalid(Error::PathLoopDetected)), Some(err)) => Err(err.into()),
+        (result, _) => result,
+    }
+}
 
-        let next_sub_ca_count = match used_as_ca {
-            UsedAsCa::No => sub_ca_count,
-            UsedAsCa::Yes => sub_ca_count + 1,
-        };
+// The number of intermediate certificates on `path`.
+fn sub_ca_count_of(path: &Cert) -> usize {
+    let mut sub_ca_count = 0;
+    let mut cert = path;
+    while let EndEntityOrCa::Ca(child_cert) = &cert.ee_or_ca {
+        sub_ca_count += 1;
+        cert = child_cert;
+    }
+    sub_ca_count
+}
 
-        build_chain(
-            required_eku_if_present,
-            supported_sig_algs,
-            trust_anchors,
-            intermediate_certs,
-            &potential_issuer,
-            time,
-            next_sub_ca_count,
-        )
-    })
+/// Returns the value of the `subjectPublicKeyInfo` of the end-entity
+/// certificate's issuer on the path from `trust_anchor` to `path`.
+#[cfg(feature = "tls-server-verify")]
+pub(crate) fn leaf_issuer_spki<'a>(
+    trust_anchor: &TrustAnchor<'a>,
+    path: &Cert<'a>,
+) -> untrusted::Input<'a> {
+    let mut issuer_spki = untrusted::Input::from(trust_anchor.spki);
+    let mut cert = path;
+    loop {
+        match &cert.ee_or_ca {
+            EndEntityOrCa::Ca(child_cert) => {
+                issuer_spki = cert.spki.value();
+                cert = child_cert;
+            }
+            EndEntityOrCa::EndEntity => {
+                return issuer_spki;
+            }
+        }
+    }
 }
 
+// `depth` is the position of `cert_chain` on the path.
 fn check_signatures(
-    supported_sig_algs: &[&SignatureAlgorithm],
+    options: &VerificationOptions,
     cert_chain: &Cert,
+    mut depth: usize,
     trust_anchor_key: untrusted::Input,
 ) -> Result<(), Error> {
+    options.count(Counter::PathExplored);
     let mut spki_value = trust_anchor_key;
     let mut cert = cert_chain;
     loop {
-        signed_data::verify_signed_data(supported_sig_algs, spki_value, &cert.signed_data)?;
+        if !options.signature_prevalidated(cert, spki_value) {
+            options.count(Counter::SignatureVerified);
+            signed_data::verify_signed_data(
+                options.supported_sig_algs,
+                spki_value,
+                &cert.signed_data,
+                options.ecdsa_signatures,
+                options.algorithm_parameters,
+            )?;
+        }
+        if options.algorithm_parameters == signed_data::AlgorithmParameters::Compatible
+            && !signed_data::has_strict_algorithm_parameters(
+                options.supported_sig_algs,
+                spki_value,
+                &cert.signed_data,
+            )
+        {
+            options.warn(Warning::NonstandardAlgorithmParameters { depth });
+        }
 
         // TODO: check revocation
 
@@ -152,6 +524,7 @@ fn check_signatures(
             EndEntityOrCa::Ca(child_cert) => {
                 spki_value = cert.spki.value();
                 cert = child_cert;
+                depth = depth.saturating_sub(1);
             }
             EndEntityOrCa::EndEntity => {
                 break;
@@ -162,35 +535,73 @@ fn check_signatures(
     Ok(())
 }
 
+/// Whether a Certificate Transparency precertificate is acceptable as the
+/// end-entity certificate.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Precertificates {
+    Reject,
+    #[cfg(feature = "tls-server-verify")]
+    Accept,
+}
+
 fn check_issuer_independent_properties(
     cert: &Cert,
-    time: time::Time,
+    options: &VerificationOptions,
     used_as_ca: UsedAsCa,
-    sub_ca_count: usize,
     required_eku_if_present: KeyPurposeId,
+    precertificates: Precertificates,
 ) -> Result<(), Error> {
     // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
     // TODO: Check signature algorithm like mozilla::pkix.
     // TODO: Check SPKI like mozilla::pkix.
-    // TODO: check for active distrust like mozilla::pkix.
 
-    // See the comment in `remember_extension` for why we don't check the
-    // KeyUsage extension.
+    options.check_not_blocked(cert)?;
+
+    // A poisoned CA certificate is never acceptable; see RFC 6962 Section 3.1.
+    if cert.ct_poison && (used_as_ca == UsedAsCa::Yes || precertificates == Precertificates::Reject)
+    {
+        return Err(Error::PoisonedPrecertificate);
+    }
+
+    // Proxy certificates are only acceptable in a proxy chain.
+    #[cfg(feature = "proxy-certs")]
+    if cert.proxy_cert_info.is_some() {
+        return Err(Error::InvalidProxyCert);
+    }
 
     cert.validity
-        .read_all(Error::BadDer, |value| check_validity(value, time))?;
-    untrusted::read_all_optional(cert.basic_constraints, Error::BadDer, |value| {
-        check_basic_constraints(value, used_as_ca, sub_ca_count)
-    })?;
+        .read_all(Error::BadDer, |value| check_validity(value, options.time))?;
+    // A v1 certificate, only ever parsed as an intermediate and only with
+    // `VerificationOptions::accept_v1_intermediates`, is implicitly a CA.
+    if !cert.v1 {
+        check_basic_constraints(cert.basic_constraints()?, used_as_ca, path_len(cert))?;
+    }
+    if cert.eku.is_none() && used_as_ca == UsedAsCa::No && options.require_eku_extension {
+        return Err(Error::RequiredEkuNotFound);
+    }
+    // A Precertificate Signing Certificate, which only issues precertificates
+    // (RFC 6962 Section 3.1), needn't have the purpose they are verified for.
+    // A poisoned end-entity certificate has already been rejected unless
+    // precertificates are accepted.
+    let issues_precert = match cert.ee_or_ca {
+        EndEntityOrCa::Ca(child) => child.ct_poison,
+        EndEntityOrCa::EndEntity => false,
+    };
     untrusted::read_all_optional(cert.eku, Error::BadDer, |value| {
         check_eku(value, required_eku_if_present)
+    })
+    .or_else(|err| match cert.eku {
+        Some(eku) if issues_precert => eku.read_all(Error::BadDer, |value| {
+            check_eku(Some(value), EKU_PRECERT_SIGNING)
+        }),
+        _ => Err(err),
     })?;
 
     Ok(())
 }
 
 // https://tools.ietf.org/html/rfc5280#section-4.1.2.5
-fn check_validity(input: &mut untrusted::Reader, time: time::Time) -> Result<(), Error> {
+pub(crate) fn check_validity(input: &mut untrusted::Reader, time: time::Time) -> Result<(), Error> {
     let not_before = der::time_choice(input)?;
     let not_after = der::time_choice(input)?;
 
@@ -224,68 +635,132 @@ fn used_as_ca(ee_or_ca: &EndEntityOrCa) -> UsedAsCa {
     }
 }
 
+// The number of intermediate certificates between `cert` and the end-entity
+// certificate that count toward `cert`'s path length constraint; self-issued
+// ones don't. See https://tools.ietf.org/html/rfc5280#section-4.2.1.9.
+fn path_len(cert: &Cert) -> usize {
+    let mut path_len = 0;
+    let mut child = cert;
+    while let EndEntityOrCa::Ca(next) = child.ee_or_ca {
+        if used_as_ca(&next.ee_or_ca) == UsedAsCa::Yes && !next.is_self_issued() {
+            path_len += 1;
+        }
+        child = next;
+    }
+    path_len
+}
+
 // https://tools.ietf.org/html/rfc5280#section-4.2.1.9
 fn check_basic_constraints(
-    input: Option<&mut untrusted::Reader>,
+    basic_constraints: BasicConstraints,
     used_as_ca: UsedAsCa,
-    sub_ca_count: usize,
+    path_len: usize,
 ) -> Result<(), Error> {
-    let (is_ca, path_len_constraint) = match input {
-        Some(input) => {
-            let is_ca = der::optional_boolean(input)?;
-
-            // https://bugzilla.mozilla.org/show_bug.cgi?id=985025: RFC 5280
-            // says that a certificate must not have pathLenConstraint unless
-            // it is a CA certificate, but some real-world end-entity
-            // certificates have pathLenConstraint.
-            let path_len_constraint = if !input.at_end() {
-                let value = der::small_nonnegative_integer(input)?;
-                Some(usize::from(value))
-            } else {
-                None
-            };
-
-            (is_ca, path_len_constraint)
-        }
-        None => (false, None),
-    };
-
-    match (used_as_ca, is_ca, path_len_constraint) {
+    match (
+        used_as_ca,
+        basic_constraints.is_ca,
+        basic_constraints.path_len_constraint,
+    ) {
         (UsedAsCa::No, true, _) => Err(Error::CaUsedAsEndEntity),
         (UsedAsCa::Yes, false, _) => Err(Error::EndEntityUsedAsCa),
-        (UsedAsCa::Yes, true, Some(len)) if sub_ca_count > len => {
-            Err(Error::PathLenConstraintViolated)
-        }
+        (UsedAsCa::Yes, true, Some(len)) if path_len > len => Err(Error::PathLenConstraintViolated),
         _ => Ok(()),
     }
 }
 
+/// An extended key usage purpose.
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub(crate) struct KeyPurposeId {
+pub struct KeyPurposeId {
     oid_value: untrusted::Input<'static>,
 }
 
+impl KeyPurposeId {
+    /// Constructs a purpose from the encoded value (without tag and length)
+    /// of its object identifier.
+    pub const fn new(oid_value: &'static [u8]) -> Self {
+        Self {
+            oid_value: untrusted::Input::from(oid_value),
+        }
+    }
+
+    /// The object identifier of the purpose.
+    pub fn oid(&self) -> Oid {
+        Oid::new(self.oid_value.as_slice_less_safe())
+    }
+}
+
+/// What an end-entity certificate is being verified for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Usage {
+    /// A TLS server certificate, with the purpose `EKU_SERVER_AUTH`.
+    ///
+    /// Requires the `tls-server-verify` feature.
+    #[cfg(feature = "tls-server-verify")]
+    TlsServer,
+
+    /// A TLS client certificate, with the purpose `EKU_CLIENT_AUTH`.
+    ///
+    /// Requires the `tls-client-verify` feature.
+    #[cfg(feature = "tls-client-verify")]
+    TlsClient,
+
+    /// A certificate with some other extended key usage purpose.
+    Custom(KeyPurposeId),
+}
+
+impl Usage {
+    pub(crate) fn eku(self) -> KeyPurposeId {
+        match self {
+            #[cfg(feature = "tls-server-verify")]
+            Self::TlsServer => EKU_SERVER_AUTH,
+            #[cfg(feature = "tls-client-verify")]
+            Self::TlsClient => EKU_CLIENT_AUTH,
+            Self::Custom(eku) => eku,
+        }
+    }
+}
+
 // id-pkix            OBJECT IDENTIFIER ::= { 1 3 6 1 5 5 7 }
 // id-kp              OBJECT IDENTIFIER ::= { id-pkix 3 }
 
 // id-kp-serverAuth   OBJECT IDENTIFIER ::= { id-kp 1 }
+/// The purpose of a TLS server certificate.
 #[allow(clippy::identity_op)] // TODO: Make this clearer
-pub(crate) static EKU_SERVER_AUTH: KeyPurposeId = KeyPurposeId {
+pub static EKU_SERVER_AUTH: KeyPurposeId = KeyPurposeId {
     oid_value: untrusted::Input::from(&[(40 * 1) + 3, 6, 1, 5, 5, 7, 3, 1]),
 };
 
 // id-kp-clientAuth   OBJECT IDENTIFIER ::= { id-kp 2 }
+/// The purpose of a TLS client certificate.
 #[allow(clippy::identity_op)] // TODO: Make this clearer
-pub(crate) static EKU_CLIENT_AUTH: KeyPurposeId = KeyPurposeId {
+pub static EKU_CLIENT_AUTH: KeyPurposeId = KeyPurposeId {
     oid_value: untrusted::Input::from(&[(40 * 1) + 3, 6, 1, 5, 5, 7, 3, 2]),
 };
 
+// id-kp-timeStamping OBJECT IDENTIFIER ::= { id-kp 8 }
+/// The purpose of an RFC 3161 time-stamping authority's certificate.
+#[allow(clippy::identity_op)] // TODO: Make this clearer
+pub static EKU_TIME_STAMPING: KeyPurposeId = KeyPurposeId {
+    oid_value: untrusted::Input::from(&[(40 * 1) + 3, 6, 1, 5, 5, 7, 3, 8]),
+};
+
 // id-kp-OCSPSigning  OBJECT IDENTIFIER ::= { id-kp 9 }
+/// The purpose of a delegated OCSP responder's certificate.
 #[allow(clippy::identity_op)] // TODO: Make this clearer
-pub(crate) static EKU_OCSP_SIGNING: KeyPurposeId = KeyPurposeId {
+pub static EKU_OCSP_SIGNING: KeyPurposeId = KeyPurposeId {
     oid_value: untrusted::Input::from(&[(40 * 1) + 3, 6, 1, 5, 5, 7, 3, 9]),
 };
 
+// The Precertificate Signing Certificate purpose 1.3.6.1.4.1.11129.2.4.4,
+// RFC 6962 Section 3.1.
+/// The purpose of a Certificate Transparency Precertificate Signing
+/// Certificate, which issues precertificates in the place of its issuer.
+#[allow(clippy::identity_op)] // TODO: Make this clearer
+pub static EKU_PRECERT_SIGNING: KeyPurposeId = KeyPurposeId {
+    oid_value: untrusted::Input::from(&[(40 * 1) + 3, 6, 1, 4, 1, 0xd6, 0x79, 2, 4, 4]),
+};
+
 // https://tools.ietf.org/html/rfc5280#section-4.2.1.12
 //
 // Notable Differences from RFC 5280:
@@ -302,7 +777,7 @@ pub(crate) static EKU_OCSP_SIGNING: KeyPurposeId = KeyPurposeId {
 // * We treat id-Netscape-stepUp as being equivalent to id-kp-serverAuth in CA
 //   certificates (only). Comodo has issued certificates that require this
 //   behavior that don't expire until June 2020. See https://bugzilla.mozilla.org/show_bug.cgi?id=982292.
-fn check_eku(
+pub(crate) fn check_eku(
     input: Option<&mut untrusted::Reader>,
     required_eku_if_present: KeyPurposeId,
 ) -> Result<(), Error> {
@@ -339,18 +814,48 @@ fn check_eku(
     }
 }
 
+/// Checks the requirements of RFC 3161 Section 2.3 on the extended key
+/// usage of a time-stamping authority's certificate: the extension must be
+/// critical, and id-kp-timeStamping its only purpose.
+pub(crate) fn check_tsa_eku(cert: &Cert) -> Result<(), Error> {
+    let eku = cert.eku.ok_or(Error::InvalidTsaCert)?;
+    let only_time_stamping = eku.read_all(Error::BadDer, |eku| {
+        let purpose = der::expect_tag_and_get_value(eku, der::Tag::OID)?;
+        Ok(eku.at_end() && purpose == EKU_TIME_STAMPING.oid_value)
+    })?;
+    if !cert.eku_critical || !only_time_stamping {
+        return Err(Error::InvalidTsaCert);
+    }
+    Ok(())
+}
+
+// Once a path has been passed over with `PathError::Continue`, that is the
+// result, so that the candidates it goes through aren't reported as
+// rejected.
 fn loop_while_non_fatal_error<V>(
     values: V,
-    f: impl Fn(V::Item) -> Result<(), Error>,
-) -> Result<(), Error>
+    f: impl Fn(V::Item) -> Result<(), PathError>,
+) -> Result<(), PathError>
 where
     V: IntoIterator,
 {
+    let mut loop_detected = false;
+    let mut continued = false;
     for v in values {
         // If the error is not fatal, then keep going.
-        if f(v).is_ok() {
-            return Ok(());
+        match f(v) {
+            Ok(()) => return Ok(()),
+            Err(PathError::Continue) => continued = true,
+            Err(PathError::Invalid(Error::PathLoopDetected)) => loop_detected = true,
+            Err(PathError::Invalid(Error::OutOfMemory)) => return Err(Error::OutOfMemory.into()),
+            Err(PathError::Invalid(_)) => {}
         }
     }
-    Err(Error::UnknownIssuer)
+    if continued {
+        return Err(PathError::Continue);
+    }
+    if loop_detected {
+        return Err(Error::PathLoopDetected.into());
+    }
+    Err(Error::UnknownIssuer.into())
 }
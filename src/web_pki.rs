@@ -0,0 +1,127 @@
+// Copyright 2015 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Date-dependent requirements of the CA/Browser Forum Baseline
+//! Requirements, for verifying publicly-trusted TLS certificates.
+
+use crate::{
+    cert::{Cert, EndEntityOrCa},
+    der, oid, Error, Time, VerificationOptions, Warning,
+};
+
+/// A set of requirements on publicly-trusted TLS certificates that depend on
+/// when they were issued, enforced in addition to those of RFC 5280 with
+/// `VerificationOptions::with_web_pki_profile`.
+///
+/// A certificate is taken to have been issued at its notBefore time. Private
+/// PKIs, which only need RFC 5280, shouldn't use a profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WebPkiProfile {
+    /// The requirements of the CA/Browser Forum Baseline Requirements:
+    ///
+    /// * Certificates issued from 2016-01-01 must not be signed with SHA-1
+    ///   (`Error::Sha1SignatureNotAllowed`).
+    /// * Certificates issued from 2016-09-30 must have a serial number with
+    ///   at least 64 bits of entropy. A serial number of fewer than 8
+    ///   octets, not counting a leading zero octet, is only reported as
+    ///   `Warning::LowEntropySerial`, since a compliant one can be that
+    ///   short when its random octets start with zero;
+    ///   `VerificationOptions::with_leaf_serial_policy` can reject it.
+    /// * End-entity certificates issued from 2018-03-01 must be valid for at
+    ///   most 825 days, and those issued from 2020-09-01 for at most 398
+    ///   days (`Error::CertValidityTooLong`).
+    BaselineRequirements,
+}
+
+// 2016-01-01T00:00:00Z.
+const SHA1_CUTOFF: u64 = 1_451_606_400;
+
+// 2016-09-30T00:00:00Z.
+const SERIAL_ENTROPY_EFFECTIVE: u64 = 1_475_193_600;
+
+const MIN_SERIAL_LEN: usize = 8;
+
+// The maximum validity periods of end-entity certificates, with the dates
+// from which they apply, latest first.
+const MAX_LEAF_VALIDITY_DAYS: &[(u64, u64)] = &[
+    (1_598_918_400, 398), // 2020-09-01T00:00:00Z
+    (1_519_862_400, 825), // 2018-03-01T00:00:00Z
+];
+
+impl WebPkiProfile {
+    pub(crate) fn check(
+        self,
+        cert: &Cert,
+        options: &VerificationOptions,
+        depth: usize,
+    ) -> Result<(), Error> {
+        let (not_before, not_after) = cert.validity.read_all(Error::BadDer, |value| {
+            let not_before = der::time_choice(value)?;
+            let not_after = der::time_choice(value)?;
+            Ok((not_before, not_after))
+        })?;
+        let issued_from = |secs| not_before >= Time::from_seconds_since_unix_epoch(secs);
+
+        if issued_from(SHA1_CUTOFF) && is_sha1_signature(cert)? {
+            return Err(Error::Sha1SignatureNotAllowed);
+        }
+
+        if issued_from(SERIAL_ENTROPY_EFFECTIVE) && !has_serial_entropy(cert) {
+            options.warn(Warning::LowEntropySerial { depth });
+        }
+
+        if let EndEntityOrCa::EndEntity = cert.ee_or_ca {
+            let max_validity_days = MAX_LEAF_VALIDITY_DAYS
+                .iter()
+                .find(|(effective, _)| issued_from(*effective))
+                .map(|(_, days)| *days);
+            if let Some(days) = max_validity_days {
+                // The validity period includes both the notBefore and the
+                // notAfter second.
+                let validity_secs = not_after
+                    .as_seconds_since_unix_epoch()
+                    .saturating_sub(not_before.as_seconds_since_unix_epoch());
+                if validity_secs >= days * 24 * 60 * 60 {
+                    return Err(Error::CertValidityTooLong);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_sha1_signature(cert: &Cert) -> Result<bool, Error> {
+    let algorithm = cert
+        .signed_data
+        .algorithm
+        .read_all(Error::BadDer, |value| {
+            let algorithm = der::expect_tag_and_get_value(value, der::Tag::OID)?;
+            value.skip_to_end();
+            Ok(algorithm)
+        })?;
+    Ok(oid::SHA1_WITH_RSA_ENCRYPTION.matches(algorithm) || oid::ECDSA_WITH_SHA1.matches(algorithm))
+}
+
+// Whether the serial number of `cert` is long enough to have the 64 bits of
+// entropy that the Baseline Requirements require, i.e. is at least 8 octets
+// long, not counting a leading zero octet.
+pub(crate) fn has_serial_entropy(cert: &Cert) -> bool {
+    let serial = match cert.serial() {
+        [0, rest @ ..] => rest,
+        serial => serial,
+    };
+    serial.len() >= MIN_SERIAL_LEN
+}
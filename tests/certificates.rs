@@ -0,0 +1,308 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::ALL_SIGALGS;
+
+#[cfg(feature = "std")]
+#[test]
+pub fn netflix_owned() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let owned = webpki::OwnedEndEntityCert::try_from(ee.to_vec()).unwrap();
+    assert_eq!(
+        owned,
+        webpki::EndEntityCert::try_from(ee).unwrap().to_owned_cert()
+    );
+
+    let result = std::thread::spawn(move || {
+        let inter = include_bytes!("netflix/inter.der");
+        let ca = include_bytes!("netflix/ca.der");
+
+        let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+        let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+        owned.as_ref().verify_for_usage(
+            ALL_SIGALGS,
+            &anchors,
+            &[inter],
+            time,
+            webpki::Usage::TlsServer,
+        )
+    })
+    .join()
+    .unwrap();
+    assert_eq!(Ok(()), result);
+
+    assert_eq!(
+        webpki::OwnedEndEntityCert::try_from(ee[1..].to_vec()),
+        Err(webpki::Error::BadDer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_clone_debug() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap().clone();
+    assert!(format!("{:?}", cert).starts_with("EndEntityCert { inner: Cert { der: [48, 130,"));
+    assert_eq!(
+        Ok(()),
+        cert.verify_for_usage(
+            ALL_SIGALGS,
+            &[anchors[0].clone()],
+            &[inter],
+            time,
+            webpki::Usage::TlsServer
+        )
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_as_der() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    assert_eq!(webpki::EndEntityCert::try_from(ee).unwrap().as_der(), ee);
+
+    let ee = ee.to_vec();
+    assert_eq!(
+        webpki::EndEntityCert::try_from(&ee).unwrap().as_der(),
+        &ee[..]
+    );
+
+    let owned = webpki::OwnedEndEntityCert::try_from(ee.clone()).unwrap();
+    assert_eq!(webpki::EndEntityCert::from(&owned).as_der(), &ee[..]);
+}
+
+#[test]
+pub fn netflix_cert_accessors() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let cert = cert.as_cert();
+
+    assert_eq!(cert.der(), ee);
+    assert_eq!(
+        cert.serial(),
+        &[
+            0x4c, 0xbd, 0x64, 0x2e, 0x23, 0x4c, 0x69, 0x27, 0x92, 0xa1, 0x50, 0xb9, 0x8c, 0x69,
+            0xe1, 0x29,
+        ]
+    );
+    assert_eq!(
+        cert.serial_hex().to_string(),
+        "4CBD642E234C692792A150B98C69E129"
+    );
+    assert!(ee.windows(cert.issuer().len()).any(|w| w == cert.issuer()));
+    assert!(ee
+        .windows(cert.subject().len())
+        .any(|w| w == cert.subject()));
+    assert_eq!(cert.spki_der()[0], 0x30);
+    assert!(matches!(cert.ee_or_ca(), webpki::EndEntityOrCa::EndEntity));
+}
+
+#[test]
+fn idevid() {
+    let ca = include_bytes!("devid/ca.der");
+    let ee: &[u8] = include_bytes!("devid/idevid.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let name = cert.hardware_module_name().unwrap().unwrap();
+    assert_eq!(
+        name.hw_type,
+        &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0xc9, 0x1f, 0x01, 0x01]
+    );
+    assert_eq!(name.hw_serial_num, b"SN-0001");
+    assert!(cert.has_no_well_defined_expiration());
+
+    // The last second of 9999-12-31.
+    let time = webpki::Time::from_seconds_since_unix_epoch(253_402_300_799);
+    assert_eq!(
+        cert.verify_for_usage(
+            &[&webpki::ECDSA_P256_SHA256],
+            &anchors,
+            &[],
+            time,
+            webpki::Usage::TlsClient
+        ),
+        Ok(())
+    );
+
+    let netflix = webpki::EndEntityCert::try_from(&include_bytes!("netflix/ee.der")[..]).unwrap();
+    assert!(netflix.hardware_module_name().unwrap().is_none());
+    assert!(!netflix.has_no_well_defined_expiration());
+}
+
+#[test]
+fn basic_constraints() {
+    let basic_constraints = |der: &[u8]| {
+        webpki::EndEntityCert::try_from(der)
+            .unwrap()
+            .as_cert()
+            .basic_constraints()
+    };
+    let constraints = |is_ca, path_len_constraint| {
+        Ok(webpki::BasicConstraints {
+            is_ca,
+            path_len_constraint,
+        })
+    };
+    assert_eq!(
+        basic_constraints(include_bytes!("self_issued/inter.der")),
+        constraints(true, Some(0))
+    );
+    assert_eq!(
+        basic_constraints(include_bytes!("self_issued/rollover.der")),
+        constraints(true, None)
+    );
+    assert_eq!(
+        basic_constraints(include_bytes!("self_issued/ee.der")),
+        constraints(false, None)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn anchor_diff() {
+    let verisign =
+        webpki::TrustAnchor::try_from_cert_der(include_bytes!("netflix/ca.der")).unwrap();
+    let test_ca =
+        webpki::TrustAnchor::try_from_cert_der(include_bytes!("ca_key_usage/ca.der")).unwrap();
+    let name_constraints_ca =
+        webpki::TrustAnchor::try_from_cert_der(include_bytes!("directory_name_constraints/ca.der"))
+            .unwrap();
+    let rekeyed_test_ca = webpki::TrustAnchor {
+        spki: verisign.spki,
+        ..test_ca
+    };
+
+    let diff = webpki::AnchorDiff::new(
+        &[verisign, test_ca],
+        &[rekeyed_test_ca, name_constraints_ca],
+    );
+    let subjects = |anchors: &[webpki::TrustAnchor]| -> Vec<_> {
+        anchors.iter().map(|anchor| anchor.subject).collect()
+    };
+    assert_eq!(subjects(&diff.added), [name_constraints_ca.subject]);
+    assert_eq!(subjects(&diff.removed), [verisign.subject]);
+    assert_eq!(diff.rekeyed.len(), 1);
+    assert_eq!(diff.rekeyed[0].0.spki, test_ca.spki);
+    assert_eq!(diff.rekeyed[0].1.spki, verisign.spki);
+
+    let diff = webpki::AnchorDiff::new(&[verisign, test_ca], &[test_ca, verisign]);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert!(diff.rekeyed.is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn anchor_store_summary() {
+    let verisign: &[u8] = include_bytes!("netflix/ca.der");
+    let test_ca: &[u8] = include_bytes!("ca_key_usage/ca.der");
+    let inter: &[u8] = include_bytes!("ca_key_usage/inter_key_cert_sign.der");
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+
+    // VeriSign's root certificate, a v1 certificate, expires in 2036.
+    let summary =
+        webpki::AnchorStoreSummary::new(&[verisign, test_ca, inter], time, 20 * 365 * 86_400)
+            .unwrap();
+    assert_eq!(
+        summary.key_kinds,
+        [(webpki::KeyKind::Rsa, 1), (webpki::KeyKind::EcdsaP256, 2)]
+    );
+    assert_eq!(summary.expiring.len(), 1);
+    assert_eq!(
+        summary.expiring[0].0.subject,
+        webpki::TrustAnchor::try_from_cert_der(verisign)
+            .unwrap()
+            .subject
+    );
+    assert_eq!(
+        summary.expiring[0].1,
+        webpki::Time::from_seconds_since_unix_epoch(2_099_865_599)
+    );
+
+    let summary =
+        webpki::AnchorStoreSummary::new(&[verisign, test_ca], time, 10 * 365 * 86_400).unwrap();
+    assert!(summary.expiring.is_empty());
+
+    assert!(webpki::AnchorStoreSummary::new(&[&verisign[1..]], time, 0).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn client_trust_anchor_distinguished_names() {
+    let a: &[u8] = include_bytes!("cross_certified/a.der");
+    let ca: &[u8] = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+
+    let anchors = [
+        webpki::TrustAnchor::try_from_cert_der(a).unwrap(),
+        webpki::TrustAnchor::try_from_cert_der(ca).unwrap(),
+    ];
+    let names = webpki::TlsClientTrustAnchors(&anchors).distinguished_names();
+    assert_eq!(names.len(), 2);
+    for (name, anchor) in names.iter().zip(&anchors) {
+        assert_eq!(name[0], 0x30);
+        assert!(name.ends_with(anchor.subject));
+    }
+    // The name is the one that certificates issued by the anchor carry as
+    // their issuer, tag and length included.
+    assert!(inter.windows(names[1].len()).any(|w| w == &names[1][..]));
+    assert!(!inter.windows(names[0].len()).any(|w| w == &names[0][..]));
+}
+
+#[test]
+fn end_entity_key_kind() {
+    for (der, kind, bits) in &[
+        (
+            &include_bytes!("ed25519/ee.der")[..],
+            webpki::KeyKind::Ed25519,
+            Some(256),
+        ),
+        (
+            &include_bytes!("self_issued/ee.der")[..],
+            webpki::KeyKind::EcdsaP256,
+            Some(256),
+        ),
+        (
+            &include_bytes!("netflix/ee.der")[..],
+            webpki::KeyKind::Rsa,
+            Some(2048),
+        ),
+        (
+            &include_bytes!("key_agreement/x25519_ee.der")[..],
+            webpki::KeyKind::Other,
+            None,
+        ),
+    ] {
+        let cert = webpki::EndEntityCert::try_from(*der).unwrap();
+        assert_eq!(cert.key_kind(), *kind);
+        assert_eq!(cert.key_bits(), *bits);
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Helpers shared by the integration tests. Each test file uses only some of
+//! them.
+
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+
+pub static ALL_SIGALGS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    #[cfg(feature = "alloc")]
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    #[cfg(feature = "alloc")]
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    #[cfg(feature = "alloc")]
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    #[cfg(feature = "alloc")]
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+/// A `VerificationDiagnostics` that records everything it is told, in order.
+#[derive(Default)]
+pub struct Recorder {
+    pub rejections: RefCell<Vec<webpki::Error>>,
+    pub candidates: RefCell<Vec<(webpki::Candidate, usize)>>,
+    pub warnings: RefCell<Vec<webpki::Warning>>,
+    pub dns_names: RefCell<Vec<Vec<u8>>>,
+    pub ip_addresses: RefCell<Vec<Vec<u8>>>,
+    /// The required purpose, the depth and the purposes found of each
+    /// `EkuMismatch`.
+    pub eku_mismatches: RefCell<Vec<(Vec<u8>, usize, Vec<Vec<u8>>)>>,
+}
+
+impl webpki::VerificationDiagnostics for Recorder {
+    fn issuer_rejected(&self, error: webpki::Error) {
+        self.rejections.borrow_mut().push(error);
+    }
+
+    fn candidate_considered(&self, candidate: webpki::Candidate, depth: usize) {
+        self.candidates.borrow_mut().push((candidate, depth));
+    }
+
+    fn warning(&self, warning: webpki::Warning) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    fn name_presented(&self, name: webpki::PresentedName) {
+        match name {
+            webpki::PresentedName::DnsName(name) => self.dns_names.borrow_mut().push(name.to_vec()),
+            webpki::PresentedName::IpAddress(octets) => {
+                self.ip_addresses.borrow_mut().push(octets.to_vec())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn required_eku_not_found(&self, mismatch: webpki::EkuMismatch) {
+        self.eku_mismatches.borrow_mut().push((
+            mismatch.required.oid().value().to_vec(),
+            mismatch.depth,
+            mismatch.found().map(|purpose| purpose.to_vec()).collect(),
+        ));
+    }
+}
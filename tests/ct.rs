@@ -0,0 +1,180 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::ALL_SIGALGS;
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_scts() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    let scts = cert
+        .signed_certificate_timestamps()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(scts.len(), 3);
+    assert_eq!(scts[0].version, 0);
+    assert_eq!(&scts[0].log_id[..4], &[0xdd, 0xeb, 0x1d, 0x2b]);
+    assert_eq!(scts[0].timestamp, 1_478_631_219_836); // 2016-11-08T18:53:39.836Z
+    assert!(scts[0].extensions.is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_ct_policy() {
+    struct MinimumScts(usize);
+
+    impl webpki::CtPolicy for MinimumScts {
+        fn check(
+            &self,
+            cert: &webpki::EndEntityCert,
+            issuer_spki: &[u8],
+            _: webpki::Time,
+        ) -> Result<(), webpki::Error> {
+            assert!(!issuer_spki.is_empty());
+            match cert.signed_certificate_timestamps().count() >= self.0 {
+                true => Ok(()),
+                false => Err(webpki::Error::CtPolicyNotSatisfied),
+            }
+        }
+    }
+
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TlsServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        Ok(()),
+        cert.verify_is_valid_tls_server_cert_with_ct_policy(
+            ALL_SIGALGS,
+            &anchors,
+            &[inter],
+            time,
+            &MinimumScts(3)
+        )
+    );
+    assert_eq!(
+        Err(webpki::Error::CtPolicyNotSatisfied),
+        cert.verify_is_valid_tls_server_cert_with_ct_policy(
+            ALL_SIGALGS,
+            &anchors,
+            &[inter],
+            time,
+            &MinimumScts(4)
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn ct_precertificate() {
+    let ca = include_bytes!("misc/precert.ca.der");
+    let ee: &[u8] = include_bytes!("misc/precert.ee.der");
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d);
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TlsServerTrustAnchors(&anchors);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage(ALL_SIGALGS, anchors.0, &[], time, webpki::Usage::TlsServer),
+        Err(webpki::Error::PoisonedPrecertificate)
+    );
+    assert_eq!(
+        cert.verify_is_valid_tls_server_precert(ALL_SIGALGS, &anchors, &[], time),
+        Ok(())
+    );
+
+    // The same certificate, issued without the poison extension.
+    let expected_tbs: &[u8] = include_bytes!("misc/precert_tbs.der");
+    let tbs = cert.precert_tbs_certificate().unwrap();
+    assert_eq!(tbs.len(), expected_tbs.len());
+    assert_eq!(tbs.chunks().collect::<Vec<_>>().concat(), expected_tbs);
+
+    let ca = webpki::EndEntityCert::try_from(&ca[..]).unwrap();
+    assert!(ca.precert_tbs_certificate().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn ct_precert_signing_cert() {
+    let ca = include_bytes!("precert_signing/ca.der");
+    let signer: &[u8] = include_bytes!("precert_signing/signer.der");
+    let precert: &[u8] = include_bytes!("precert_signing/precert.der");
+    let not_precert: &[u8] = include_bytes!("precert_signing/not_precert.der");
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TlsServerTrustAnchors(&anchors);
+
+    // The signing certificate only has the purpose `EKU_PRECERT_SIGNING`,
+    // so it can issue precertificates but not TLS server certificates.
+    let cert = webpki::EndEntityCert::try_from(precert).unwrap();
+    assert_eq!(
+        cert.verify_is_valid_tls_server_precert(ALL_SIGALGS, &anchors, &[signer], time),
+        Ok(())
+    );
+    let cert = webpki::EndEntityCert::try_from(not_precert).unwrap();
+    assert_eq!(
+        cert.verify_is_valid_tls_server_precert(ALL_SIGALGS, &anchors, &[signer], time),
+        Err(webpki::Error::UnknownIssuer)
+    );
+
+    let signer_cert = webpki::EndEntityCert::try_from(signer).unwrap();
+    assert!(signer_cert.as_cert().is_precert_signing_cert());
+    let precert_cert = webpki::EndEntityCert::try_from(precert).unwrap();
+    assert!(!precert_cert.as_cert().is_precert_signing_cert());
+
+    // The logged `TBSCertificate` is that of the final certificate, issued by
+    // the CA itself.
+    let expected_tbs: &[u8] = include_bytes!("precert_signing/final_tbs.der");
+    let tbs = precert_cert
+        .precert_tbs_certificate_for_issuer(signer)
+        .unwrap()
+        .unwrap();
+    assert_eq!(tbs.len(), expected_tbs.len());
+    assert_eq!(tbs.chunks().collect::<Vec<_>>().concat(), expected_tbs);
+
+    // Issued directly by the CA, the issuer and authorityKeyIdentifier are
+    // kept.
+    let tbs = precert_cert
+        .precert_tbs_certificate_for_issuer(ca)
+        .unwrap()
+        .unwrap();
+    let unchanged = precert_cert.precert_tbs_certificate().unwrap();
+    assert_eq!(
+        tbs.chunks().collect::<Vec<_>>().concat(),
+        unchanged.chunks().collect::<Vec<_>>().concat()
+    );
+    assert_ne!(tbs.chunks().collect::<Vec<_>>().concat(), expected_tbs);
+
+    let not_precert_cert = webpki::EndEntityCert::try_from(not_precert).unwrap();
+    assert!(not_precert_cert
+        .precert_tbs_certificate_for_issuer(signer)
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,156 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "alloc", feature = "tls-server-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+static ALL_SIGALGS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+// SHA-256 of the netflix end-entity certificate's subjectPublicKeyInfo.
+const EE_SPKI_SHA256: &str = "f0a7cdaa4a45e92da94aaaa460295119aba317e5d743a69bd47f42da3bfbf8ed";
+
+// SHA-256 of the netflix intermediate certificate.
+const INTER_CERT_SHA256: &str = "564dbc6a76550fd2d4f4bac6448a1157b33cbd8e0b3fd76d72107540964a85b5";
+
+// SHA-512 of the netflix intermediate certificate's subjectPublicKeyInfo.
+const INTER_SPKI_SHA512: &str = "18f1d378969f462c3150a9c66d353e84df8ef00efbbd589fa35fdd04cf3e4642\
+                                 5f7b12a205b3d5cb8b2e708ae650d34e2c9c6b4b8c02d950805ddab77c901767";
+
+// SHA-256 of the netflix root certificate's subjectPublicKeyInfo.
+const ROOT_SPKI_SHA256: &str = "495a96ba6bad782407bd521a00bace657bb355555e4bb7f8146c71bba57e7ace";
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn check(usage: u8, selector: u8, matching_type: u8, data: &[u8]) -> Result<(), webpki::Error> {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TlsServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let record = webpki::TlsaRecord::from_rdata(usage, selector, matching_type, data).unwrap();
+    webpki::verify_tlsa_records(&cert, &[record], ALL_SIGALGS, &anchors, &[inter], time)
+}
+
+#[test]
+fn association_data() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    let full = cert.tlsa_association_data(
+        webpki::TlsaSelector::FullCert,
+        webpki::TlsaMatchingType::Full,
+    );
+    assert_eq!(full.as_ref(), ee);
+
+    let spki_sha256 =
+        cert.tlsa_association_data(webpki::TlsaSelector::Spki, webpki::TlsaMatchingType::Sha256);
+    assert_eq!(spki_sha256.as_ref(), &unhex(EE_SPKI_SHA256)[..]);
+}
+
+#[test]
+fn dane_ee() {
+    assert_eq!(check(3, 1, 1, &unhex(EE_SPKI_SHA256)), Ok(()));
+    assert_eq!(
+        check(3, 1, 1, &unhex(ROOT_SPKI_SHA256)),
+        Err(webpki::Error::NoMatchingTlsaRecord)
+    );
+}
+
+#[test]
+fn dane_ta() {
+    assert_eq!(check(2, 1, 2, &unhex(INTER_SPKI_SHA512)), Ok(()));
+    assert_eq!(check(2, 0, 1, &unhex(INTER_CERT_SHA256)), Ok(()));
+}
+
+#[test]
+fn pkix_ee() {
+    assert_eq!(check(1, 1, 1, &unhex(EE_SPKI_SHA256)), Ok(()));
+}
+
+#[test]
+fn pkix_ta() {
+    assert_eq!(check(0, 0, 1, &unhex(INTER_CERT_SHA256)), Ok(()));
+    assert_eq!(check(0, 1, 1, &unhex(ROOT_SPKI_SHA256)), Ok(()));
+    assert_eq!(
+        check(0, 1, 1, &unhex(EE_SPKI_SHA256)),
+        Err(webpki::Error::NoMatchingTlsaRecord)
+    );
+}
+
+#[test]
+fn path_errors() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    // The record matches, but there is no path to a trust anchor.
+    let ee_spki = unhex(EE_SPKI_SHA256);
+    let pkix_ee = webpki::TlsaRecord::from_rdata(1, 1, 1, &ee_spki).unwrap();
+    assert_eq!(
+        webpki::verify_tlsa_records(
+            &cert,
+            &[pkix_ee],
+            ALL_SIGALGS,
+            &webpki::TlsServerTrustAnchors(&[]),
+            &[inter],
+            time
+        ),
+        Err(webpki::Error::UnknownIssuer)
+    );
+
+    // The record matches the intermediate, but the certificate has expired.
+    let inter_spki = unhex(INTER_SPKI_SHA512);
+    let dane_ta = webpki::TlsaRecord::from_rdata(2, 1, 2, &inter_spki).unwrap();
+    assert_eq!(
+        webpki::verify_tlsa_records(
+            &cert,
+            &[dane_ta],
+            ALL_SIGALGS,
+            &webpki::TlsServerTrustAnchors(&[]),
+            &[inter],
+            webpki::Time::from_seconds_since_unix_epoch(2_000_000_000)
+        ),
+        Err(webpki::Error::CertExpired)
+    );
+}
+
+#[test]
+fn unknown_fields_are_unusable() {
+    assert!(webpki::TlsaRecord::from_rdata(4, 0, 0, &[]).is_none());
+    assert!(webpki::TlsaRecord::from_rdata(3, 2, 0, &[]).is_none());
+    assert!(webpki::TlsaRecord::from_rdata(3, 0, 3, &[]).is_none());
+}
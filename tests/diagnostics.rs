@@ -0,0 +1,218 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::{Recorder, ALL_SIGALGS};
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_diagnostics() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let rejections = Recorder::default();
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+        .with_diagnostics(&rejections);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        Err(webpki::Error::UnknownIssuer),
+        cert.verify_for_usage_with_options(&anchors, &[inter], &options, webpki::Usage::TlsServer)
+    );
+    assert!(rejections
+        .rejections
+        .borrow()
+        .contains(&webpki::Error::UnsupportedSignatureAlgorithm));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_counters() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let counters = webpki::VerificationCounters::default();
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time).with_diagnostics(&counters);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        Ok(()),
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[ee, inter],
+            &options,
+            webpki::Usage::TlsServer
+        )
+    );
+    assert_eq!(counters.certs_parsed(), 2);
+    assert_eq!(counters.anchors_tried(), 1);
+    assert_eq!(counters.paths_explored(), 1);
+    assert_eq!(counters.signatures_verified(), 2);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_trace() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let trace = || {
+        let trace = Recorder::default();
+        let options = webpki::VerificationOptions::new(ALL_SIGALGS, time).with_diagnostics(&trace);
+        assert_eq!(
+            Ok(()),
+            cert.verify_for_usage_with_options(
+                &anchors,
+                &[ee, inter],
+                &options,
+                webpki::Usage::TlsServer
+            )
+        );
+        trace.candidates.into_inner()
+    };
+
+    let expected = vec![
+        (webpki::Candidate::Intermediate(1), 0),
+        (webpki::Candidate::TrustAnchor(0), 1),
+    ];
+    assert_eq!(trace(), expected);
+    assert_eq!(trace(), expected);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_trace_replay() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let (result, trace) = cert.verify_for_usage_with_trace(
+        &anchors,
+        &[ee, inter],
+        &options,
+        webpki::Usage::TlsServer,
+    );
+    assert_eq!(result, Ok(()));
+    assert!(trace.verified());
+    assert_eq!(trace.time(), time);
+    assert_eq!(
+        trace.candidates(),
+        &[
+            (webpki::Candidate::Intermediate(1), 0),
+            (webpki::Candidate::TrustAnchor(0), 1),
+        ]
+    );
+
+    let bytes = trace.to_bytes();
+    let trace = webpki::VerificationTrace::from_bytes(&bytes).unwrap();
+    assert_eq!(webpki::VerificationTrace::from_bytes(&bytes[1..]), None);
+    assert_eq!(
+        webpki::VerificationTrace::from_bytes(&bytes[..bytes.len() - 1]),
+        None
+    );
+
+    // The replay runs at the recorded time, whatever the options say.
+    let later = webpki::Time::from_seconds_since_unix_epoch(2_000_000_000);
+    let replay_options = webpki::VerificationOptions::new(ALL_SIGALGS, later);
+    assert_eq!(
+        trace.replay(
+            &cert,
+            &anchors,
+            &[ee, inter],
+            &replay_options,
+            webpki::Usage::TlsServer
+        ),
+        Ok(())
+    );
+
+    assert_eq!(
+        trace.replay(
+            &cert,
+            &anchors,
+            &[inter],
+            &options,
+            webpki::Usage::TlsServer
+        ),
+        Err(webpki::Error::TraceMismatch)
+    );
+    assert_eq!(
+        trace.replay(
+            &cert,
+            &anchors,
+            &[ee, inter],
+            &options.with_max_sub_ca_count(0),
+            webpki::Usage::TlsServer
+        ),
+        Err(webpki::Error::TraceMismatch)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_eku_mismatch() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let mismatches = Recorder::default();
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time).with_diagnostics(&mismatches);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options,
+            webpki::Usage::Custom(webpki::EKU_TIME_STAMPING)
+        ),
+        Err(webpki::Error::RequiredEkuNotFound)
+    );
+    assert_eq!(
+        *mismatches.eku_mismatches.borrow(),
+        [(
+            webpki::EKU_TIME_STAMPING.oid().value().to_vec(),
+            0,
+            vec![
+                webpki::EKU_SERVER_AUTH.oid().value().to_vec(),
+                webpki::EKU_CLIENT_AUTH.oid().value().to_vec(),
+            ]
+        )]
+    );
+}
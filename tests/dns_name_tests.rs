@@ -406,3 +406,109 @@ fn dns_name_ref_try_from_ascii_test() {
         );
     }
 }
+
+#[test]
+fn dns_name_ref_try_from_ascii_error_test() {
+    assert_eq!(
+        webpki::DnsNameRef::try_from_ascii(b"\xe4\xbe\x8b.example").err(),
+        Some(webpki::InvalidDnsNameError::NonAscii)
+    );
+    assert_eq!(
+        webpki::DnsNameRef::try_from_ascii(b"\xff").err(),
+        Some(webpki::InvalidDnsNameError::NonAscii)
+    );
+    assert_eq!(
+        webpki::DnsNameRef::try_from_ascii(b"a..example").err(),
+        Some(webpki::InvalidDnsNameError::InvalidSyntax)
+    );
+    assert_eq!(
+        webpki::DnsNameRef::try_from_ascii(b"a\x00.example").err(),
+        Some(webpki::InvalidDnsNameError::InvalidSyntax)
+    );
+    assert_eq!(
+        webpki::DnsNameRef::try_from_ascii(b"*.example.com").err(),
+        Some(webpki::InvalidDnsNameError::Wildcard)
+    );
+    assert_eq!(
+        webpki::DnsNameRef::try_from_ascii(b"www*.example.com").err(),
+        Some(webpki::InvalidDnsNameError::Wildcard)
+    );
+}
+
+#[test]
+fn validate_presented_dns_name_test() {
+    use webpki::InvalidPresentedDnsNameError::*;
+
+    let cases: &[(&[u8], Result<(), webpki::InvalidPresentedDnsNameError>)] = &[
+        (b"www.example.com", Ok(())),
+        (b"*.example.com", Ok(())),
+        (b"xn--nxasmq6b.example", Ok(())),
+        (b"\xe4\xbe\x8b.example", Err(NonAscii)),
+        (b"www.example.com\0.evil.example", Err(EmbeddedNul)),
+        (b"{invalid}.example.com", Err(InvalidCharacter)),
+        (b"www.example.com:443", Err(InvalidCharacter)),
+        (b"www.example.com.", Err(Absolute)),
+        (b"*.example.com.", Err(Absolute)),
+        (b"www.*.example.com", Err(InvalidWildcard)),
+        (b"w*.example.com", Err(InvalidWildcard)),
+        (b"*.*.example.com", Err(InvalidWildcard)),
+        (b"*.com", Err(WildcardTooFewLabels)),
+        (b"*.", Err(InvalidSyntax)),
+        (b"", Err(InvalidSyntax)),
+        (b"a..example", Err(InvalidSyntax)),
+        (b"-a.example", Err(InvalidSyntax)),
+    ];
+    for &(presented, expected) in cases {
+        assert_eq!(
+            webpki::validate_presented_dns_name(presented),
+            expected,
+            "validate_presented_dns_name({:?})",
+            presented
+        );
+    }
+}
+
+#[test]
+fn verify_dns_names_match_test() {
+    let reference = webpki::DnsNameRef::try_from_ascii_str("www.example.com").unwrap();
+    let matches = |presented: &[u8]| webpki::verify_dns_names_match(presented, reference);
+
+    assert_eq!(matches(b"www.example.com"), Ok(()));
+    assert_eq!(matches(b"WWW.Example.COM"), Ok(()));
+    assert_eq!(matches(b"*.example.com"), Ok(()));
+    assert_eq!(matches(b"*.com"), Err(webpki::Error::CertNotValidForName));
+    assert_eq!(
+        matches(b"example.com"),
+        Err(webpki::Error::CertNotValidForName)
+    );
+    assert_eq!(
+        matches(b"www..example.com"),
+        Err(webpki::Error::CertNotValidForName)
+    );
+}
+
+#[cfg(feature = "idna")]
+#[test]
+fn dns_name_unicode_test() {
+    let name = webpki::DnsName::from_unicode("B\u{fc}cher.example").unwrap();
+    assert_eq!(AsRef::<str>::as_ref(&name), "xn--bcher-kva.example");
+    assert_eq!(name.to_unicode(), "b\u{fc}cher.example");
+
+    let name = webpki::DnsName::from_unicode("www.example.com").unwrap();
+    assert_eq!(name.to_unicode(), "www.example.com");
+
+    // An invalid A-label isn't decoded.
+    let name = webpki::DnsNameRef::try_from_ascii_str("xn--zz.example")
+        .unwrap()
+        .to_owned();
+    assert_eq!(name.to_unicode(), "xn--zz.example");
+
+    assert_eq!(
+        webpki::DnsName::from_unicode("*.b\u{fc}cher.example").err(),
+        Some(webpki::InvalidDnsNameError::Wildcard)
+    );
+    assert_eq!(
+        webpki::DnsName::from_unicode("a..example").err(),
+        Some(webpki::InvalidDnsNameError::InvalidSyntax)
+    );
+}
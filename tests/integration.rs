@@ -12,29 +12,19 @@
 // ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
 use core::convert::TryFrom;
 extern crate webpki;
 
-static ALL_SIGALGS: &[&webpki::SignatureAlgorithm] = &[
-    &webpki::ECDSA_P256_SHA256,
-    &webpki::ECDSA_P256_SHA384,
-    &webpki::ECDSA_P384_SHA256,
-    &webpki::ECDSA_P384_SHA384,
-    &webpki::ED25519,
-    #[cfg(feature = "alloc")]
-    &webpki::RSA_PKCS1_2048_8192_SHA256,
-    #[cfg(feature = "alloc")]
-    &webpki::RSA_PKCS1_2048_8192_SHA384,
-    #[cfg(feature = "alloc")]
-    &webpki::RSA_PKCS1_2048_8192_SHA512,
-    #[cfg(feature = "alloc")]
-    &webpki::RSA_PKCS1_3072_8192_SHA384,
-];
+mod common;
+use common::ALL_SIGALGS;
 
 /* Checks we can verify netflix's cert chain.  This is notable
  * because they're rooted at a Verisign v1 root. */
 #[cfg(feature = "alloc")]
 #[test]
+#[allow(deprecated)] // The pre-`Usage` API, kept for compatibility.
 pub fn netflix() {
     let ee: &[u8] = include_bytes!("netflix/ee.der");
     let inter = include_bytes!("netflix/inter.der");
@@ -52,6 +42,48 @@ pub fn netflix() {
     );
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_options() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        Ok(()),
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options.with_max_sub_ca_count(1).require_eku_extension(),
+            webpki::Usage::TlsServer
+        )
+    );
+    assert_eq!(
+        Err(webpki::Error::UnknownIssuer),
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options.with_max_sub_ca_count(0),
+            webpki::Usage::TlsServer
+        )
+    );
+    assert_eq!(
+        Err(webpki::Error::RequiredEkuNotFound),
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options,
+            webpki::Usage::Custom(webpki::EKU_OCSP_SIGNING)
+        )
+    );
+}
+
 /* This is notable because it is a popular use of IP address subjectAltNames. */
 #[cfg(feature = "alloc")]
 #[test]
@@ -61,14 +93,19 @@ pub fn cloudflare_dns() {
     let ca = include_bytes!("cloudflare_dns/ca.der");
 
     let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
-    let anchors = webpki::TlsServerTrustAnchors(&anchors);
 
     let time = webpki::Time::from_seconds_since_unix_epoch(1_663_495_771);
 
     let cert = webpki::EndEntityCert::try_from(ee).unwrap();
     assert_eq!(
         Ok(()),
-        cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &[inter], time)
+        cert.verify_for_usage(
+            ALL_SIGALGS,
+            &anchors,
+            &[inter],
+            time,
+            webpki::Usage::TlsServer
+        )
     );
 
     let check_name = |name: &str| {
@@ -109,14 +146,13 @@ pub fn wpt() {
     let ca = include_bytes!("wpt/ca.der");
 
     let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
-    let anchors = webpki::TlsServerTrustAnchors(&anchors);
 
     let time = webpki::Time::from_seconds_since_unix_epoch(1_619_256_684); // 2021-04-24T09:31:24Z
 
     let cert = webpki::EndEntityCert::try_from(ee).unwrap();
     assert_eq!(
         Ok(()),
-        cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &[], time)
+        cert.verify_for_usage(ALL_SIGALGS, &anchors, &[], time, webpki::Usage::TlsServer)
     );
 }
 
@@ -126,14 +162,13 @@ pub fn ed25519() {
     let ca = include_bytes!("ed25519/ca.der");
 
     let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
-    let anchors = webpki::TlsServerTrustAnchors(&anchors);
 
     let time = webpki::Time::from_seconds_since_unix_epoch(1_547_363_522); // 2019-01-13T07:12:02Z
 
     let cert = webpki::EndEntityCert::try_from(ee).unwrap();
     assert_eq!(
         Ok(()),
-        cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &[], time)
+        cert.verify_for_usage(ALL_SIGALGS, &anchors, &[], time, webpki::Usage::TlsServer)
     );
 }
 
@@ -145,16 +180,17 @@ fn critical_extensions() {
 
     let time = webpki::Time::from_seconds_since_unix_epoch(1_670_779_098);
     let anchors = [webpki::TrustAnchor::try_from_cert_der(root).unwrap()];
-    let anchors = webpki::TlsServerTrustAnchors(&anchors);
 
     let ee = include_bytes!("critical_extensions/ee-cert-noncrit-unknown-ext.der");
-    let res = webpki::EndEntityCert::try_from(&ee[..])
-        .and_then(|cert| cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &[ca], time));
+    let res = webpki::EndEntityCert::try_from(&ee[..]).and_then(|cert| {
+        cert.verify_for_usage(ALL_SIGALGS, &anchors, &[ca], time, webpki::Usage::TlsServer)
+    });
     assert_eq!(res, Ok(()), "accept non-critical unknown extension");
 
     let ee = include_bytes!("critical_extensions/ee-cert-crit-unknown-ext.der");
-    let res = webpki::EndEntityCert::try_from(&ee[..])
-        .and_then(|cert| cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &[ca], time));
+    let res = webpki::EndEntityCert::try_from(&ee[..]).and_then(|cert| {
+        cert.verify_for_usage(ALL_SIGALGS, &anchors, &[ca], time, webpki::Usage::TlsServer)
+    });
     assert_eq!(
         res,
         Err(webpki::Error::UnsupportedCriticalExtension),
@@ -182,14 +218,13 @@ fn read_ee_with_neg_serial() {
     let ee: &[u8] = include_bytes!("misc/serial_neg_ee.der");
 
     let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
-    let anchors = webpki::TlsServerTrustAnchors(&anchors);
 
     let time = webpki::Time::from_seconds_since_unix_epoch(1_667_401_500); // 2022-11-02T15:05:00Z
 
     let cert = webpki::EndEntityCert::try_from(ee).unwrap();
     assert_eq!(
         Ok(()),
-        cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &[], time)
+        cert.verify_for_usage(ALL_SIGALGS, &anchors, &[], time, webpki::Usage::TlsServer)
     );
 }
 
@@ -0,0 +1,96 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(
+    feature = "lint",
+    feature = "tls-server-verify",
+    feature = "tls-client-verify"
+))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+#[test]
+fn lint() {
+    use webpki::lint::{check, Finding, Profile};
+
+    let lint = |der: &[u8], profile| {
+        check(
+            webpki::EndEntityCert::try_from(der).unwrap().as_cert(),
+            profile,
+        )
+    };
+    let netflix_ee: &[u8] = include_bytes!("netflix/ee.der");
+    let netflix_inter: &[u8] = include_bytes!("netflix/inter.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    assert!(lint(netflix_ee, Profile::Rfc5280).is_empty());
+    assert_eq!(
+        lint(netflix_ee, Profile::CabfServerLeaf),
+        [Finding::ValidityTooLong]
+    );
+    assert!(lint(netflix_inter, Profile::CabfIntermediate).is_empty());
+
+    assert_eq!(
+        lint(inter, Profile::Rfc5280),
+        [Finding::MissingSubjectKeyId]
+    );
+    assert_eq!(
+        lint(ee, Profile::CabfServerLeaf),
+        [
+            Finding::MissingSubjectAltName,
+            Finding::MissingExtendedKeyUsage,
+            Finding::ValidityTooLong,
+        ]
+    );
+    assert_eq!(lint(ee, Profile::CabfIntermediate), [Finding::NotCaCert]);
+}
+
+#[test]
+fn lint_chain() {
+    use webpki::lint::{check_chain, Finding, Profile};
+
+    let netflix_ee: &[u8] = include_bytes!("netflix/ee.der");
+    let netflix_inter: &[u8] = include_bytes!("netflix/inter.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    assert_eq!(
+        check_chain(
+            &[netflix_ee, netflix_inter],
+            Profile::CabfServerLeaf,
+            Profile::CabfIntermediate
+        ),
+        Ok(vec![(0, Finding::ValidityTooLong)])
+    );
+    assert_eq!(
+        check_chain(
+            &[ee, inter, ee],
+            Profile::CabfServerLeaf,
+            Profile::CabfIntermediate
+        ),
+        Ok(vec![
+            (0, Finding::MissingSubjectAltName),
+            (0, Finding::MissingExtendedKeyUsage),
+            (0, Finding::ValidityTooLong),
+            (1, Finding::MissingSubjectKeyId),
+            (2, Finding::NotCaCert),
+        ])
+    );
+    assert_eq!(
+        check_chain(&[ee, &[0x30, 0x00]], Profile::Rfc5280, Profile::Rfc5280),
+        Err(webpki::Error::BadDer)
+    );
+}
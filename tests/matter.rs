@@ -0,0 +1,102 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "tls-client-verify")]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+static RCAC: &[u8] = include_bytes!("matter/rcac.der");
+static ICAC: &[u8] = include_bytes!("matter/icac.der");
+
+fn verify(noc: &[u8]) -> Result<webpki::MatterIdentity, webpki::Error> {
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(RCAC).unwrap()];
+    let anchors = webpki::TlsClientTrustAnchors(&anchors);
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d);
+
+    let cert = webpki::EndEntityCert::try_from(noc)?;
+    cert.verify_is_valid_matter_noc(&anchors, &[ICAC], time)
+}
+
+#[test]
+fn noc() {
+    let identity = verify(include_bytes!("matter/noc.der")).unwrap();
+    assert_eq!(
+        identity,
+        webpki::MatterIdentity {
+            node_id: Some(0xdede_dede_0001_0001),
+            fabric_id: Some(0xfab0_0000_0000_001d),
+            case_authenticated_tags: [Some(0xabcd_0002), None, None],
+            ..webpki::MatterIdentity::default()
+        }
+    );
+}
+
+#[test]
+fn noc_with_options() {
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(RCAC).unwrap()];
+    let anchors = webpki::TlsClientTrustAnchors(&anchors);
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(&include_bytes!("matter/noc.der")[..]).unwrap();
+
+    // The signature algorithms of the options are replaced with Matter's.
+    let options = webpki::VerificationOptions::new(&[], time);
+    assert!(cert
+        .verify_is_valid_matter_noc_with_options(&anchors, &[ICAC], &options)
+        .is_ok());
+
+    let options = options.with_max_sub_ca_count(0);
+    assert_eq!(
+        cert.verify_is_valid_matter_noc_with_options(&anchors, &[ICAC], &options),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[test]
+fn noc_wrong_fabric() {
+    assert_eq!(
+        verify(include_bytes!("matter/noc_wrong_fabric.der")),
+        Err(webpki::Error::InvalidMatterCert)
+    );
+}
+
+#[test]
+fn noc_no_node_id() {
+    assert_eq!(
+        verify(include_bytes!("matter/noc_no_node_id.der")),
+        Err(webpki::Error::InvalidMatterCert)
+    );
+}
+
+#[test]
+fn ca_identities() {
+    let rcac = webpki::EndEntityCert::try_from(RCAC).unwrap();
+    assert_eq!(
+        rcac.matter_identity(),
+        Ok(webpki::MatterIdentity {
+            rcac_id: Some(0xcaca_caca_0000_0001),
+            ..webpki::MatterIdentity::default()
+        })
+    );
+
+    let icac = webpki::EndEntityCert::try_from(ICAC).unwrap();
+    assert_eq!(
+        icac.matter_identity(),
+        Ok(webpki::MatterIdentity {
+            icac_id: Some(0xcaca_caca_0000_0003),
+            fabric_id: Some(0xfab0_0000_0000_001d),
+            ..webpki::MatterIdentity::default()
+        })
+    );
+}
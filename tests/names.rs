@@ -0,0 +1,373 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::{Recorder, ALL_SIGALGS};
+
+#[test]
+pub fn netflix_subject_dn() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let subject = include_bytes!("netflix/ee-subject.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    assert_eq!(cert.verify_is_valid_for_subject_dn(subject), Ok(()));
+    assert_eq!(
+        cert.verify_is_valid_for_subject_dn(&subject[..subject.len() - 1]),
+        Err(webpki::Error::BadDer)
+    );
+
+    let mut other = subject.to_vec();
+    *other.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        cert.verify_is_valid_for_subject_dn(&other),
+        Err(webpki::Error::CertNotValidForName)
+    );
+}
+
+#[cfg(all(feature = "alloc", feature = "tls-server-verify"))]
+#[test]
+pub fn netflix_anchor_dns_suffixes() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchor_spki = anchors[0].spki;
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    let netflix = [webpki::DnsNameRef::try_from_ascii_str("netflix.com").unwrap()];
+    let corp = [webpki::DnsNameRef::try_from_ascii_str("corp.internal").unwrap()];
+    let www_netflix = webpki::DnsNameRef::try_from_ascii_str("www.netflix.com").unwrap();
+    let ip = webpki::IpAddrRef::try_from_ascii_str("192.0.2.1").unwrap();
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |subject_name: webpki::SubjectNameRef,
+                  anchor_dns_suffixes: &[webpki::AnchorDnsSuffixes]| {
+        let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+            .with_anchor_dns_suffixes(subject_name, anchor_dns_suffixes);
+        cert.verify_for_usage_with_options(&anchors, &[inter], &options, webpki::Usage::TlsServer)
+            .and_then(|()| cert.verify_is_valid_for_subject_name(subject_name))
+    };
+    let restricted_to = |dns_suffixes| webpki::AnchorDnsSuffixes {
+        spki: anchor_spki,
+        dns_suffixes,
+    };
+
+    assert_eq!(verify(www_netflix.into(), &[]), Ok(()));
+    assert_eq!(
+        verify(www_netflix.into(), &[restricted_to(&netflix)]),
+        Ok(())
+    );
+    assert_eq!(
+        verify(
+            www_netflix.into(),
+            &[restricted_to(&corp), restricted_to(&netflix)]
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        verify(www_netflix.into(), &[restricted_to(&corp)]),
+        Err(webpki::Error::AnchorNotTrustedForName)
+    );
+    assert_eq!(
+        verify(ip.into(), &[restricted_to(&netflix)]),
+        Err(webpki::Error::AnchorNotTrustedForName)
+    );
+
+    // Restrictions of other anchors don't apply.
+    let other = webpki::AnchorDnsSuffixes {
+        spki: &[],
+        dns_suffixes: &corp,
+    };
+    assert_eq!(verify(www_netflix.into(), &[other]), Ok(()));
+
+    // The restrictions are part of the options, so they apply to every
+    // kind of verification.
+    let corp_only = [restricted_to(&corp)];
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+        .with_anchor_dns_suffixes(www_netflix.into(), &corp_only);
+    assert_eq!(
+        cert.verify_tls_server_cert_chain_with_options(
+            &webpki::TlsServerTrustAnchors(&anchors),
+            &[inter],
+            &options
+        )
+        .err(),
+        Some(webpki::Error::AnchorNotTrustedForName)
+    );
+
+    // The name is still checked against the certificate.
+    assert_eq!(
+        verify(
+            webpki::DnsNameRef::try_from_ascii_str("mail.netflix.com")
+                .unwrap()
+                .into(),
+            &[restricted_to(&netflix)]
+        ),
+        Err(webpki::Error::CertNotValidForName)
+    );
+}
+
+#[test]
+pub fn cloudflare_dns_presented_names() {
+    let ee: &[u8] = include_bytes!("cloudflare_dns/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    let names = Recorder::default();
+    let subject_name_ref = webpki::SubjectNameRef::try_from_ascii_str("one.one.one.one").unwrap();
+    assert_eq!(
+        Ok(()),
+        cert.verify_is_valid_for_subject_name_with_diagnostics(subject_name_ref, &names)
+    );
+    assert!(names.dns_names.borrow().is_empty());
+    assert!(names.ip_addresses.borrow().is_empty());
+
+    let subject_name_ref = webpki::SubjectNameRef::try_from_ascii_str("8.8.8.8").unwrap();
+    assert_eq!(
+        Err(webpki::Error::CertNotValidForName),
+        cert.verify_is_valid_for_subject_name_with_diagnostics(subject_name_ref, &names)
+    );
+    assert_eq!(
+        *names.dns_names.borrow(),
+        vec![
+            b"cloudflare-dns.com".to_vec(),
+            b"*.cloudflare-dns.com".to_vec(),
+            b"one.one.one.one".to_vec(),
+        ]
+    );
+    let ip_addresses = names.ip_addresses.borrow();
+    assert_eq!(ip_addresses.len(), 8);
+    assert_eq!(ip_addresses[0], [1, 1, 1, 1]);
+    assert_eq!(
+        ip_addresses[7],
+        [0x26, 0x06, 0x47, 0, 0x47, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x64, 0]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_covers_dns_name() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let matcher = cert.dns_name_matcher().unwrap();
+
+    for &(name, covered) in &[
+        ("netflix.com", true),
+        ("WWW.Netflix.COM", true),
+        ("signup.netflix.com", true),
+        ("www.netflix.ca", true),
+        ("netflix.net", false),
+        ("other.netflix.com", false),
+        ("www.netflix.com.evil", false),
+    ] {
+        let dns_name = webpki::DnsNameRef::try_from_ascii_str(name).unwrap();
+        assert_eq!(cert.covers_dns_name(dns_name), covered, "{}", name);
+        assert_eq!(matcher.matches(dns_name), covered, "{}", name);
+    }
+}
+
+#[test]
+pub fn invalid_characters_in_dns_name() {
+    use webpki::InvalidPresentedDnsNameError::*;
+
+    let check = |ee: &[u8], name: &str| {
+        let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+        let subject_name_ref = webpki::SubjectNameRef::try_from_ascii_str(name).unwrap();
+        cert.verify_is_valid_for_subject_name(subject_name_ref)
+    };
+
+    // The subjectAltName is "DNS:good.example.com,
+    // DNS:www.example.com\0.evil.example".
+    let ee: &[u8] = include_bytes!("san_dns_chars/ee_nul.der");
+    for name in &["good.example.com", "www.example.com", "192.0.2.1"] {
+        assert_eq!(
+            check(ee, name),
+            Err(webpki::Error::InvalidDnsNameInSubjectAltName {
+                index: 1,
+                reason: EmbeddedNul,
+            }),
+            "{}",
+            name
+        );
+    }
+
+    // The subjectAltName is "DNS:{invalid}.example.com, DNS:good.example.com".
+    let ee: &[u8] = include_bytes!("san_dns_chars/ee_invalid_char.der");
+    assert_eq!(
+        check(ee, "good.example.com"),
+        Err(webpki::Error::InvalidDnsNameInSubjectAltName {
+            index: 0,
+            reason: InvalidCharacter,
+        })
+    );
+}
+
+#[test]
+pub fn ip_address_in_dns_name() {
+    use webpki::IpAddressInDnsName::*;
+
+    // The subjectAltName is "DNS:192.0.2.1, DNS:www.example.com".
+    let ee: &[u8] = include_bytes!("ip_in_dns_name/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    let check = |name: &str, policy| {
+        let subject_name_ref = webpki::SubjectNameRef::try_from_ascii_str(name).unwrap();
+        cert.verify_is_valid_for_subject_name_with_policy(subject_name_ref, policy)
+    };
+
+    assert_eq!(check("www.example.com", Ignore), Ok(()));
+    assert_eq!(
+        check("192.0.2.1", Ignore),
+        Err(webpki::Error::CertNotValidForName)
+    );
+
+    assert_eq!(check("www.example.com", MatchAsIpAddress), Ok(()));
+    assert_eq!(check("192.0.2.1", MatchAsIpAddress), Ok(()));
+    assert_eq!(
+        check("192.0.2.2", MatchAsIpAddress),
+        Err(webpki::Error::CertNotValidForName)
+    );
+
+    assert_eq!(
+        check("www.example.com", Reject),
+        Err(webpki::Error::IpAddressInDnsName)
+    );
+    assert_eq!(
+        check("192.0.2.1", Reject),
+        Err(webpki::Error::IpAddressInDnsName)
+    );
+
+    let subject_name_ref = webpki::SubjectNameRef::try_from_ascii_str("www.example.com").unwrap();
+    assert_eq!(
+        cert.verify_is_valid_for_subject_name(subject_name_ref),
+        Ok(())
+    );
+}
+
+#[test]
+fn directory_name_constraints() {
+    // The CA permits subjects under "C=US, O=Example Corp", except those
+    // under "C=US, O=Example Corp, OU=Evil", all encoded as PrintableStrings.
+    let ca = include_bytes!("directory_name_constraints/ca.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+
+    // A violation only rejects the anchor as an issuer, so it is reported as
+    // the reason for the rejection.
+    let verify = |ee: &[u8]| {
+        let rejections = Recorder::default();
+        let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+            .with_diagnostics(&rejections);
+        let result = webpki::EndEntityCert::try_from(ee)
+            .unwrap()
+            .verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsServer);
+        (result, rejections.rejections.into_inner())
+    };
+    let violation = (
+        Err(webpki::Error::UnknownIssuer),
+        vec![webpki::Error::NameConstraintViolation],
+    );
+
+    // "C=US, O=example  CORP", with a UTF8String organization.
+    let permitted = include_bytes!("directory_name_constraints/permitted.ee.der");
+    assert_eq!(verify(permitted), (Ok(()), vec![]));
+
+    // "C=US, O=Other Corp"
+    let not_permitted = include_bytes!("directory_name_constraints/not_permitted.ee.der");
+    assert_eq!(verify(not_permitted), violation.clone());
+
+    // "C=US, O=Example Corp, OU= evil ", with a UTF8String unit.
+    let excluded = include_bytes!("directory_name_constraints/excluded.ee.der");
+    assert_eq!(verify(excluded), violation);
+}
+
+#[test]
+fn subject_alt_name_limit() {
+    // Each certificate's last name is www.example.com.
+    let verify = |ee: &[u8]| {
+        let subject_name_ref =
+            webpki::SubjectNameRef::try_from_ascii_str("www.example.com").unwrap();
+        webpki::EndEntityCert::try_from(ee)
+            .unwrap()
+            .verify_is_valid_for_subject_name(subject_name_ref)
+    };
+    assert_eq!(webpki::MAX_SUBJECT_ALT_NAMES, 1024);
+
+    let at_limit = include_bytes!("name_limits/sans_at_limit.ee.der");
+    assert_eq!(verify(at_limit), Ok(()));
+
+    let over_limit = include_bytes!("name_limits/sans_over_limit.ee.der");
+    assert_eq!(
+        verify(over_limit),
+        Err(webpki::Error::MaximumSubjectAltNamesExceeded)
+    );
+}
+
+#[test]
+fn name_constraint_subtree_limit() {
+    // Each CA's last permitted subtree is example.com.
+    let verify = |ca: &[u8], ee: &[u8]| {
+        let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+        let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+        let rejections = Recorder::default();
+        let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+            .with_diagnostics(&rejections);
+        let result = webpki::EndEntityCert::try_from(ee)
+            .unwrap()
+            .verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsServer);
+        (result, rejections.rejections.into_inner())
+    };
+    assert_eq!(webpki::MAX_NAME_CONSTRAINT_SUBTREES, 1024);
+
+    let ca = include_bytes!("name_limits/subtrees_at_limit.ca.der");
+    let ee = include_bytes!("name_limits/subtrees_at_limit.ee.der");
+    assert_eq!(verify(ca, ee), (Ok(()), vec![]));
+
+    let ca = include_bytes!("name_limits/subtrees_over_limit.ca.der");
+    let ee = include_bytes!("name_limits/subtrees_over_limit.ee.der");
+    assert_eq!(
+        verify(ca, ee),
+        (
+            Err(webpki::Error::UnknownIssuer),
+            vec![webpki::Error::MaximumNameConstraintSubtreesExceeded]
+        )
+    );
+}
+
+#[test]
+fn distinguished_name() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ee = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter = webpki::EndEntityCert::try_from(inter).unwrap();
+
+    let subject = ee.as_cert().subject_name().unwrap();
+    assert_eq!(
+        subject.to_string(),
+        "CN=www.netflix.com,OU=Operations,O=Netflix\\, Inc.,L=los gatos,ST=California,C=US"
+    );
+    let common_name = subject.rdns().last().unwrap().attributes().next().unwrap();
+    assert_eq!(common_name.oid(), webpki::oid::COMMON_NAME.value());
+    assert_eq!(common_name.value_str(), Some("www.netflix.com"));
+
+    let issuer = ee.as_cert().issuer_name().unwrap();
+    assert!(issuer.eq_rfc5280(&inter.as_cert().subject_name().unwrap()));
+    assert!(!issuer.eq_rfc5280(&subject));
+}
@@ -0,0 +1,894 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::{Recorder, ALL_SIGALGS};
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_chain() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TlsServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let chain = cert
+        .verify_tls_server_cert_chain(ALL_SIGALGS, &anchors, &[ee, ca, inter, inter], time)
+        .unwrap();
+    assert_eq!(chain.certs(), &[ee, inter]);
+    assert_eq!(chain.end_entity(), ee);
+    assert_eq!(chain.intermediates(), &[inter]);
+
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time).with_max_sub_ca_count(0);
+    assert_eq!(
+        cert.verify_tls_server_cert_chain_with_options(&anchors, &[inter], &options)
+            .err(),
+        Some(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_partial_chain() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+
+    // The intermediate is trusted directly, without its root.
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(inter).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        Ok(()),
+        cert.verify_for_usage(ALL_SIGALGS, &anchors, &[], time, webpki::Usage::TlsServer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_prevalidated_intermediates() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+    let unrelated: &[u8] = include_bytes!("acceptable_cas/root1.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let intermediates = [unrelated, inter];
+    let validated = webpki::prevalidate_intermediates(&intermediates, &anchors, &options);
+    assert_eq!(validated.len(), 1);
+    assert!(validated.contains(inter));
+    assert!(!validated.contains(unrelated));
+    assert_eq!(validated.errors(), &[(0, webpki::Error::UnknownIssuer)]);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: &webpki::VerificationOptions| {
+        let counters = webpki::VerificationCounters::default();
+        let result = cert.verify_for_usage_with_options(
+            &anchors,
+            &intermediates,
+            &options.with_diagnostics(&counters),
+            webpki::Usage::TlsServer,
+        );
+        (result, counters.signatures_verified())
+    };
+    assert_eq!(verify(&options), (Ok(()), 2));
+    assert_eq!(
+        verify(&options.with_validated_intermediates(&validated)),
+        (Ok(()), 1)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_shadow_options() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter_cert = webpki::EndEntityCert::try_from(inter).unwrap();
+    let blocklist = [webpki::BlockedCert::CertSha256(
+        inter_cert.sha256_fingerprint(),
+    )];
+    let stricter = options.with_blocklist(&blocklist);
+    let verify = |options, shadow_options| {
+        cert.verify_for_usage_with_shadow_options(
+            &anchors,
+            &[inter],
+            options,
+            shadow_options,
+            webpki::Usage::TlsServer,
+        )
+    };
+
+    let same = verify(&options, &options);
+    assert_eq!(same.result, Ok(()));
+    assert_eq!(same.shadow_result, Ok(()));
+    assert!(!same.diverged());
+
+    let rollout = verify(&options, &stricter);
+    assert_eq!(rollout.result, Ok(()));
+    assert_eq!(rollout.shadow_result, Err(webpki::Error::UnknownIssuer));
+    assert!(rollout.diverged());
+
+    let rollback = verify(&stricter, &options);
+    assert_eq!(rollback.result, Err(webpki::Error::UnknownIssuer));
+    assert_eq!(rollback.shadow_result, Ok(()));
+    assert!(rollback.diverged());
+
+    // Rejecting the certificate for another reason is a divergence too.
+    let expired = webpki::VerificationOptions::new(
+        ALL_SIGALGS,
+        webpki::Time::from_seconds_since_unix_epoch(2_000_000_000), // 2033-05-18T03:33:20Z
+    );
+    let rejected = verify(&stricter, &expired);
+    assert_eq!(rejected.result, Err(webpki::Error::UnknownIssuer));
+    assert_eq!(rejected.shadow_result, Err(webpki::Error::CertExpired));
+    assert!(rejected.diverged());
+
+    // The shadow verification searches the same inputs on its own.
+    let trace = Recorder::default();
+    assert!(!verify(&options, &options.with_diagnostics(&trace)).diverged());
+    assert_eq!(
+        trace.candidates.into_inner(),
+        [
+            (webpki::Candidate::Intermediate(0), 0),
+            (webpki::Candidate::TrustAnchor(0), 1),
+        ]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_stages() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let chain = cert
+        .build_path(webpki::EKU_CLIENT_AUTH, &anchors, &[inter], &options)
+        .unwrap();
+    assert_eq!(chain.chain().certs(), &[ee, inter]);
+    assert_eq!(
+        cert.build_path(webpki::EKU_TIME_STAMPING, &anchors, &[inter], &options)
+            .map(|_| ()),
+        Err(webpki::Error::RequiredEkuNotFound)
+    );
+
+    assert_eq!(cert.check_validity(time), Ok(()));
+    assert_eq!(
+        cert.check_validity(webpki::Time::from_seconds_since_unix_epoch(0)),
+        Err(webpki::Error::CertNotValidYet)
+    );
+    assert_eq!(cert.check_eku(webpki::EKU_SERVER_AUTH), Ok(()));
+    assert_eq!(
+        cert.check_eku(webpki::EKU_OCSP_SIGNING),
+        Err(webpki::Error::RequiredEkuNotFound)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_verified_chain() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let chain = cert
+        .build_path(webpki::EKU_SERVER_AUTH, &anchors, &[inter], &options)
+        .unwrap();
+    assert_eq!(chain.leaf(), ee);
+    assert_eq!(chain.intermediates(), &[inter]);
+    assert_eq!(chain.anchor().subject, anchors[0].subject);
+
+    let ca_pin =
+        webpki::SpkiPin::from_base64("SVqWumuteCQHvVIaALrOZXuzVVVeS7f4FGxxu6V+es4=").unwrap();
+    assert!(chain.contains_spki(&ca_pin));
+    assert!(chain.contains_spki(&webpki::SpkiPin::from_sha256(cert.spki_sha256())));
+    assert!(!chain.contains_spki(&webpki::SpkiPin::from_sha256([0; 32])));
+
+    // The end-entity certificate expires first, at 2018-11-02T23:59:59Z.
+    assert_eq!(
+        chain.expires_at(),
+        webpki::Time::from_seconds_since_unix_epoch(1_541_203_199)
+    );
+    assert_eq!(chain.weakest_key_bits(), Some(2048));
+}
+
+#[test]
+fn v1_intermediate() {
+    let ca = include_bytes!("v1_intermediate/ca.der");
+    let inter: &[u8] = include_bytes!("v1_intermediate/inter.der");
+    let ee: &[u8] = include_bytes!("v1_intermediate/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: &webpki::VerificationOptions| {
+        cert.verify_for_usage_with_options(&anchors, &[inter], options, webpki::Usage::TlsClient)
+    };
+    assert_eq!(verify(&options), Err(webpki::Error::UnknownIssuer));
+    assert_eq!(verify(&options.accept_v1_intermediates()), Ok(()));
+
+    // v1 certificates are still not acceptable as end-entity certificates.
+    assert_eq!(
+        webpki::EndEntityCert::try_from(inter).err(),
+        Some(webpki::Error::UnsupportedCertVersion)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn enumerate_valid_paths() {
+    let ca = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    // `inter` is also trusted directly, so there are two paths.
+    let anchors = [
+        webpki::TrustAnchor::try_from_cert_der(ca).unwrap(),
+        webpki::TrustAnchor::try_from_cert_der(inter).unwrap(),
+    ];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let paths = |max_paths| {
+        cert.enumerate_valid_paths(
+            webpki::EKU_CLIENT_AUTH,
+            &anchors,
+            &[inter, rollover],
+            &options,
+            max_paths,
+        )
+        .map(|paths| {
+            paths
+                .iter()
+                .map(|path| (path.anchor().subject, path.intermediates().to_vec()))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let shortest = (anchors[1].subject, vec![rollover]);
+    let longest = (anchors[0].subject, vec![rollover, inter]);
+    assert_eq!(paths(usize::MAX), Ok(vec![shortest.clone(), longest]));
+    assert_eq!(paths(1), Ok(vec![shortest]));
+    assert_eq!(paths(0), Ok(vec![]));
+    assert_eq!(
+        cert.enumerate_valid_paths(webpki::EKU_CLIENT_AUTH, &anchors, &[inter], &options, 2)
+            .map(|paths| paths.len()),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[test]
+fn intermediates_in_any_order() {
+    let ca = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    // The path is ca -> inter -> rollover -> ee.
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    for intermediates in &[
+        &[rollover, inter][..],
+        &[inter, rollover][..],
+        &[inter, ee, inter, rollover, rollover][..],
+    ] {
+        assert_eq!(
+            cert.verify_for_usage_with_options(
+                &anchors,
+                intermediates,
+                &options,
+                webpki::Usage::TlsClient
+            ),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn verify_all() {
+    let ca = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+    let other_ee: &[u8] = include_bytes!("cross_certified/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    let certs = [
+        webpki::EndEntityCert::try_from(ee).unwrap(),
+        webpki::EndEntityCert::try_from(other_ee).unwrap(),
+        webpki::EndEntityCert::try_from(ee).unwrap(),
+    ];
+    let intermediates = [inter, rollover];
+    let mut results = webpki::EndEntityCert::verify_all_for_usage_with_options(
+        &certs,
+        &anchors,
+        &intermediates,
+        &options,
+        webpki::Usage::TlsClient,
+    );
+    assert_eq!(results.next(), Some(Ok(())));
+    assert_eq!(results.next(), Some(Err(webpki::Error::UnknownIssuer)));
+    assert_eq!(results.next(), Some(Ok(())));
+    assert_eq!(results.next(), None);
+}
+
+#[test]
+fn verification_token() {
+    let ca = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let chain = cert
+        .build_path(
+            webpki::EKU_CLIENT_AUTH,
+            &anchors,
+            &[inter, rollover],
+            &options,
+        )
+        .unwrap();
+    let token = webpki::VerificationToken::from_bytes(
+        &chain
+            .verification_token(&options, webpki::EKU_CLIENT_AUTH)
+            .to_bytes(),
+    );
+    let certs = chain.chain().certs();
+    assert!(token.is_still_valid(certs, &anchors[0], &options, webpki::EKU_CLIENT_AUTH));
+
+    // Later, but while the certificates are still valid.
+    let later = webpki::Time::from_seconds_since_unix_epoch(0x6fed_f00d);
+    let later_options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], later);
+    assert!(token.is_still_valid(certs, &anchors[0], &later_options, webpki::EKU_CLIENT_AUTH));
+
+    // Before the certificates were valid.
+    let earlier = webpki::Time::from_seconds_since_unix_epoch(0);
+    let earlier_options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], earlier);
+    assert!(!token.is_still_valid(
+        certs,
+        &anchors[0],
+        &earlier_options,
+        webpki::EKU_CLIENT_AUTH
+    ));
+
+    // Different options, usage or path.
+    assert!(!token.is_still_valid(
+        certs,
+        &anchors[0],
+        &options.require_eku_extension(),
+        webpki::EKU_CLIENT_AUTH
+    ));
+    assert!(!token.is_still_valid(
+        certs,
+        &anchors[0],
+        &webpki::VerificationOptions::new(webpki::ALL_SIGNATURE_ALGORITHMS, time),
+        webpki::EKU_CLIENT_AUTH
+    ));
+    assert!(!token.is_still_valid(certs, &anchors[0], &options, webpki::EKU_SERVER_AUTH));
+    assert!(!token.is_still_valid(&certs[..2], &anchors[0], &options, webpki::EKU_CLIENT_AUTH));
+
+    // An algorithm with the same name, accepting different identifiers.
+    let impostor = webpki::SignatureAlgorithm::custom(
+        "ECDSA_P256_SHA256",
+        &[0x06, 0x01, 0x00],
+        &[0x06, 0x01, 0x01],
+        |_, _, _| Err(webpki::Error::InvalidSignatureForPublicKey),
+    );
+    assert!(!token.is_still_valid(
+        certs,
+        &anchors[0],
+        &webpki::VerificationOptions::new(&[&impostor], time),
+        webpki::EKU_CLIENT_AUTH
+    ));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn chain_diff() {
+    let old: &[u8] = include_bytes!("ip_in_dns_name/ee.der");
+    let new: &[u8] = include_bytes!("directory_name_constraints/permitted.ee.der");
+    let new_ca: &[u8] = include_bytes!("directory_name_constraints/ca.der");
+
+    let diff = webpki::ChainDiff::new(&[old], &[new, new_ca]).unwrap();
+    assert!(diff.subject_changed);
+    assert!(diff.issuer_changed);
+    assert!(diff.key_rotated);
+    assert!(diff.intermediates_changed);
+    assert!(diff.names_added.is_empty());
+    assert_eq!(
+        diff.names_removed,
+        [webpki::PresentedName::DnsName(b"192.0.2.1")]
+    );
+
+    let diff = webpki::ChainDiff::new(&[new, new_ca], &[new, new_ca]).unwrap();
+    assert!(!diff.subject_changed);
+    assert!(!diff.issuer_changed);
+    assert!(!diff.key_rotated);
+    assert!(!diff.intermediates_changed);
+    assert!(diff.names_added.is_empty());
+    assert!(diff.names_removed.is_empty());
+
+    assert_eq!(
+        webpki::ChainDiff::new(&[], &[new]),
+        Err(webpki::Error::BadDer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn acceptable_cas() {
+    // The intermediate is cross-signed by both roots.
+    let root1 = include_bytes!("acceptable_cas/root1.der");
+    let root2 = include_bytes!("acceptable_cas/root2.der");
+    let inter_by_root1: &[u8] = include_bytes!("acceptable_cas/inter_by_root1.der");
+    let inter_by_root2: &[u8] = include_bytes!("acceptable_cas/inter_by_root2.der");
+    let ee: &[u8] = include_bytes!("acceptable_cas/ee.der");
+
+    let anchors = [
+        webpki::TrustAnchor::try_from_cert_der(root1).unwrap(),
+        webpki::TrustAnchor::try_from_cert_der(root2).unwrap(),
+    ];
+    let root1_name = anchors[0].subject_der().unwrap();
+    let root2_name = anchors[1].subject_der().unwrap();
+    let inter_name = webpki::TrustAnchor::try_from_cert_der(inter_by_root1)
+        .unwrap()
+        .subject_der()
+        .unwrap();
+
+    let sig_algs = &[&webpki::ECDSA_P256_SHA256];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |intermediates: &[&[u8]], acceptable_cas: &[&[u8]], policy| {
+        let trace = Recorder::default();
+        let options = webpki::VerificationOptions::new(sig_algs, time)
+            .with_diagnostics(&trace)
+            .with_acceptable_cas(acceptable_cas, policy);
+        let result = cert.verify_for_usage_with_options(
+            &anchors,
+            intermediates,
+            &options,
+            webpki::Usage::TlsClient,
+        );
+        let trace = trace.candidates.into_inner();
+        (result, trace)
+    };
+
+    let both: &[&[u8]] = &[inter_by_root1, inter_by_root2];
+    let path_through = |intermediate| {
+        vec![
+            (webpki::Candidate::Intermediate(intermediate), 0),
+            (webpki::Candidate::TrustAnchor(intermediate), 1),
+        ]
+    };
+
+    // Without an acceptable CA among them, the intermediates are tried in
+    // order.
+    assert_eq!(
+        verify(both, &[], webpki::CaHintPolicy::Prefer),
+        (Ok(()), path_through(0))
+    );
+    assert_eq!(
+        verify(both, &[&root2_name[..]], webpki::CaHintPolicy::Prefer),
+        (Ok(()), path_through(1))
+    );
+    assert_eq!(
+        verify(
+            &[inter_by_root1],
+            &[&root2_name[..]],
+            webpki::CaHintPolicy::Prefer
+        )
+        .0,
+        Ok(())
+    );
+
+    assert_eq!(
+        verify(both, &[&root2_name[..]], webpki::CaHintPolicy::Restrict),
+        (Ok(()), path_through(1))
+    );
+    assert_eq!(
+        verify(
+            &[inter_by_root1],
+            &[&root2_name[..]],
+            webpki::CaHintPolicy::Restrict
+        )
+        .0,
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert_eq!(
+        verify(
+            &[inter_by_root1],
+            &[&root2_name[..], &root1_name[..]],
+            webpki::CaHintPolicy::Restrict
+        )
+        .0,
+        Ok(())
+    );
+    // An intermediate certificate can be the acceptable CA too.
+    assert_eq!(
+        verify(
+            &[inter_by_root2],
+            &[&inter_name[..]],
+            webpki::CaHintPolicy::Restrict
+        )
+        .0,
+        Ok(())
+    );
+    assert_eq!(
+        verify(
+            &[inter_by_root2],
+            &[&inter_name[1..]],
+            webpki::CaHintPolicy::Restrict
+        )
+        .0,
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn issuer_key_order() {
+    use webpki::{Candidate, KeyKind};
+
+    // The intermediate CA has an RSA key and an ECDSA key; the end-entity
+    // certificate is signed with the ECDSA one.
+    let root = include_bytes!("issuer_key_order/root.der");
+    let inter_rsa: &[u8] = include_bytes!("issuer_key_order/inter_rsa.der");
+    let inter_ec: &[u8] = include_bytes!("issuer_key_order/inter_ec.der");
+    let ee: &[u8] = include_bytes!("issuer_key_order/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(root).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |issuer_key_order: &[KeyKind]| {
+        let trace = Recorder::default();
+        let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+            .with_diagnostics(&trace)
+            .with_issuer_key_order(issuer_key_order);
+        let result = cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter_rsa, inter_ec],
+            &options,
+            webpki::Usage::TlsClient,
+        );
+        (result, trace.candidates.into_inner())
+    };
+
+    assert_eq!(
+        verify(&[]),
+        (
+            Ok(()),
+            vec![
+                (Candidate::Intermediate(0), 0),
+                (Candidate::TrustAnchor(0), 1),
+                (Candidate::Intermediate(1), 0),
+                (Candidate::TrustAnchor(0), 1),
+            ]
+        )
+    );
+    assert_eq!(
+        verify(&[KeyKind::Ed25519, KeyKind::EcdsaP256]),
+        (
+            Ok(()),
+            vec![
+                (Candidate::Intermediate(1), 0),
+                (Candidate::TrustAnchor(0), 1),
+            ]
+        )
+    );
+    // Candidates with keys of other kinds are still tried.
+    assert_eq!(verify(&[KeyKind::Rsa]).0, Ok(()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn issuer_key_order_parses_once() {
+    use webpki::KeyKind;
+
+    let root = include_bytes!("issuer_key_order/root.der");
+    let inter_rsa: &[u8] = include_bytes!("issuer_key_order/inter_rsa.der");
+    let inter_ec: &[u8] = include_bytes!("issuer_key_order/inter_ec.der");
+    let ee: &[u8] = include_bytes!("issuer_key_order/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(root).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    // The search for the issuer of `ee` takes three passes, but each
+    // intermediate certificate is only parsed in the first.
+    let counters = webpki::VerificationCounters::default();
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+        .with_diagnostics(&counters)
+        .with_issuer_key_order(&[KeyKind::Ed25519, KeyKind::EcdsaP256]);
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter_rsa, inter_ec],
+            &options,
+            webpki::Usage::TlsClient,
+        ),
+        Ok(())
+    );
+    assert_eq!(counters.certs_parsed(), 2);
+}
+
+#[test]
+fn mutual_cross_certification() {
+    let a: &[u8] = include_bytes!("cross_certified/a.der");
+    let b: &[u8] = include_bytes!("cross_certified/b.der");
+    let ee: &[u8] = include_bytes!("cross_certified/ee.der");
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage(
+            &[&webpki::ECDSA_P256_SHA256],
+            &[],
+            &[a, b],
+            time,
+            webpki::Usage::TlsClient
+        ),
+        Err(webpki::Error::PathLoopDetected)
+    );
+}
+
+#[test]
+fn cross_signed_key() {
+    // The same CA key under an old and a new name, each certifying the other.
+    let old = include_bytes!("cross_signed_key/old.der");
+    let new_by_old: &[u8] = include_bytes!("cross_signed_key/new_by_old.der");
+    let old_by_new: &[u8] = include_bytes!("cross_signed_key/old_by_new.der");
+    let ee: &[u8] = include_bytes!("cross_signed_key/ee.der");
+
+    let sig_algs = &[&webpki::ECDSA_P256_SHA256];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(old).unwrap()];
+    assert_eq!(
+        cert.verify_for_usage(
+            sig_algs,
+            &anchors,
+            &[old_by_new, new_by_old],
+            time,
+            webpki::Usage::TlsClient
+        ),
+        Ok(())
+    );
+
+    // Without a trust anchor, path building goes on from the key under its
+    // new name to the same key under its old name, which is not a loop, and
+    // reports the loop when it comes back to the key under its new name.
+    let trace = Recorder::default();
+    let options = webpki::VerificationOptions::new(sig_algs, time).with_diagnostics(&trace);
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &[],
+            &[new_by_old, old_by_new],
+            &options,
+            webpki::Usage::TlsClient
+        ),
+        Err(webpki::Error::PathLoopDetected)
+    );
+    assert_eq!(
+        *trace.candidates.borrow(),
+        [
+            (webpki::Candidate::Intermediate(0), 0),
+            (webpki::Candidate::Intermediate(1), 1),
+            (webpki::Candidate::Intermediate(0), 2),
+        ]
+    );
+}
+
+#[test]
+fn shortest_path() {
+    // The issuing CA is certified both by X1, the trust anchor, and by X2,
+    // which X1 cross-signs.
+    let x1 = include_bytes!("shortest_path/x1.der");
+    let x2_by_x1: &[u8] = include_bytes!("shortest_path/x2_by_x1.der");
+    let inter_by_x2: &[u8] = include_bytes!("shortest_path/inter_by_x2.der");
+    let inter_by_x1: &[u8] = include_bytes!("shortest_path/inter_by_x1.der");
+    let ee: &[u8] = include_bytes!("shortest_path/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(x1).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: webpki::VerificationOptions| {
+        let trace = Recorder::default();
+        let result = cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter_by_x2, x2_by_x1, inter_by_x1],
+            &options.with_diagnostics(&trace),
+            webpki::Usage::TlsClient,
+        );
+        (result, trace.candidates.into_inner())
+    };
+
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+    assert_eq!(
+        verify(options),
+        (
+            Ok(()),
+            vec![
+                (webpki::Candidate::Intermediate(0), 0),
+                (webpki::Candidate::Intermediate(1), 1),
+                (webpki::Candidate::TrustAnchor(0), 2),
+            ]
+        )
+    );
+
+    // The path through X2 is first tried with room for only one
+    // intermediate certificate, before the path without it is found.
+    assert_eq!(
+        verify(options.prefer_shortest_path()),
+        (
+            Ok(()),
+            vec![
+                (webpki::Candidate::Intermediate(0), 0),
+                (webpki::Candidate::Intermediate(2), 0),
+                (webpki::Candidate::Intermediate(0), 0),
+                (webpki::Candidate::Intermediate(1), 1),
+                (webpki::Candidate::Intermediate(2), 0),
+                (webpki::Candidate::TrustAnchor(0), 1),
+            ]
+        )
+    );
+
+    // The search stops at the limit when no path is found.
+    assert_eq!(
+        verify(options.prefer_shortest_path().with_max_sub_ca_count(0)),
+        (
+            Err(webpki::Error::UnknownIssuer),
+            vec![
+                (webpki::Candidate::Intermediate(0), 0),
+                (webpki::Candidate::Intermediate(2), 0),
+            ]
+        )
+    );
+
+    // Each path is enumerated once, in the round that first allows its
+    // length. The paths enumerated aren't reported as rejected.
+    #[cfg(feature = "alloc")]
+    {
+        let enumerate = |options: webpki::VerificationOptions| {
+            let trace = Recorder::default();
+            let intermediates = cert
+                .enumerate_valid_paths(
+                    webpki::EKU_CLIENT_AUTH,
+                    &anchors,
+                    &[inter_by_x2, x2_by_x1, inter_by_x1],
+                    &options.with_diagnostics(&trace),
+                    usize::MAX,
+                )
+                .unwrap()
+                .iter()
+                .map(|path| path.intermediates().to_vec())
+                .collect::<Vec<_>>();
+            (intermediates, trace.rejections.into_inner())
+        };
+        let longest = vec![inter_by_x2, x2_by_x1];
+        let shortest = vec![inter_by_x1];
+        assert_eq!(
+            enumerate(options),
+            (vec![longest.clone(), shortest.clone()], vec![])
+        );
+        assert_eq!(
+            enumerate(options.prefer_shortest_path()).0,
+            vec![shortest, longest]
+        );
+    }
+}
+
+#[test]
+fn self_issued_intermediate() {
+    let ca = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    let sig_algs = &[&webpki::ECDSA_P256_SHA256];
+    let self_issued = |der| {
+        let cert = webpki::EndEntityCert::try_from(der).unwrap();
+        let cert = cert.as_cert();
+        (cert.is_self_issued(), cert.is_self_signed(sig_algs))
+    };
+    assert_eq!(self_issued(&ca[..]), (true, true));
+    assert_eq!(self_issued(inter), (false, false));
+    assert_eq!(self_issued(rollover), (true, false));
+    assert_eq!(self_issued(ee), (false, false));
+
+    // `inter` has a path length constraint of zero, which the self-issued
+    // `rollover` doesn't count toward.
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage(
+            sig_algs,
+            &anchors,
+            &[inter, rollover],
+            time,
+            webpki::Usage::TlsClient
+        ),
+        Ok(())
+    );
+
+    // The issuer name of this rollover certificate differs from its subject
+    // name only in case, which doesn't keep it from being self-issued.
+    let ca = include_bytes!("self_issued/folded_ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/folded_inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/folded_rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/folded_ee.der");
+    assert_eq!(self_issued(rollover), (true, false));
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage(
+            sig_algs,
+            &anchors,
+            &[inter, rollover],
+            time,
+            webpki::Usage::TlsClient
+        ),
+        Ok(())
+    );
+}
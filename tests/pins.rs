@@ -0,0 +1,98 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::ALL_SIGALGS;
+
+#[test]
+pub fn netflix_fingerprints() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    assert_eq!(
+        cert.sha256_fingerprint(),
+        [
+            0xd5, 0x8b, 0x0d, 0xcb, 0xd1, 0x55, 0x94, 0x69, 0x30, 0x41, 0x63, 0x3b, 0xbc, 0x78,
+            0x8a, 0xf7, 0x00, 0xdf, 0x3f, 0x8f, 0x24, 0x3a, 0x1e, 0x09, 0xaf, 0xe8, 0xcc, 0x58,
+            0x50, 0x10, 0x32, 0x2c,
+        ]
+    );
+    assert_eq!(
+        cert.spki_sha256(),
+        [
+            0xf0, 0xa7, 0xcd, 0xaa, 0x4a, 0x45, 0xe9, 0x2d, 0xa9, 0x4a, 0xaa, 0xa4, 0x60, 0x29,
+            0x51, 0x19, 0xab, 0xa3, 0x17, 0xe5, 0xd7, 0x43, 0xa6, 0x9b, 0xd4, 0x7f, 0x42, 0xda,
+            0x3b, 0xfb, 0xf8, 0xed,
+        ]
+    );
+    assert_eq!(
+        webpki::SpkiPin::from_sha256(cert.spki_sha256()),
+        webpki::SpkiPin::from_base64("8KfNqkpF6S2pSqqkYClRGaujF+XXQ6ab1H9C2jv7+O0=").unwrap()
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_pins() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TlsServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+
+    let ee_pin =
+        webpki::SpkiPin::from_base64("8KfNqkpF6S2pSqqkYClRGaujF+XXQ6ab1H9C2jv7+O0=").unwrap();
+    let inter_pin =
+        webpki::SpkiPin::from_base64("9n0izTnSRF+W4W4JTq51avSXkWhQB8duS2bxVLfzXsY=").unwrap();
+    let anchor_pin =
+        webpki::SpkiPin::from_base64("SVqWumuteCQHvVIaALrOZXuzVVVeS7f4FGxxu6V+es4=").unwrap();
+    let unrelated_pin = webpki::SpkiPin::from_sha256([0; 32]);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    for pins in &[
+        &[ee_pin][..],
+        &[inter_pin],
+        &[anchor_pin],
+        &[unrelated_pin, inter_pin],
+    ] {
+        assert_eq!(
+            Ok(()),
+            cert.verify_is_valid_tls_server_cert_with_pins(
+                ALL_SIGALGS,
+                &anchors,
+                &[inter],
+                time,
+                pins
+            )
+        );
+    }
+    assert_eq!(
+        Err(webpki::Error::PinMismatch),
+        cert.verify_is_valid_tls_server_cert_with_pins(
+            ALL_SIGALGS,
+            &anchors,
+            &[inter],
+            time,
+            &[unrelated_pin]
+        )
+    );
+}
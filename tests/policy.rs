@@ -0,0 +1,542 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::{Recorder, ALL_SIGALGS};
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_custom_checks() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: &webpki::VerificationOptions| {
+        cert.verify_for_usage_with_options(&anchors, &[inter], options, webpki::Usage::TlsServer)
+    };
+
+    let long_serials_only = |cert: &webpki::Cert| match cert.serial().len() {
+        16..=20 => Ok(()),
+        _ => Err(webpki::Error::CertBlocked),
+    };
+    assert_eq!(verify(&options.with_cert_check(&long_serials_only)), Ok(()));
+
+    let no_end_entities = |cert: &webpki::Cert| match cert.ee_or_ca() {
+        webpki::EndEntityOrCa::EndEntity => Err(webpki::Error::CertBlocked),
+        webpki::EndEntityOrCa::Ca(_) => Ok(()),
+    };
+    assert_eq!(
+        verify(&options.with_cert_check(&no_end_entities)),
+        Err(webpki::Error::CertBlocked)
+    );
+
+    let no_intermediates = |cert: &webpki::Cert| match cert.ee_or_ca() {
+        webpki::EndEntityOrCa::EndEntity => Ok(()),
+        webpki::EndEntityOrCa::Ca(_) => Err(webpki::Error::CertBlocked),
+    };
+    assert_eq!(
+        verify(&options.with_cert_check(&no_intermediates)),
+        Err(webpki::Error::UnknownIssuer)
+    );
+
+    let checked_chains = std::cell::Cell::new(0);
+    let verisign_only = |chain: &webpki::VerifiedChain| {
+        checked_chains.set(checked_chains.get() + 1);
+        assert_eq!(chain.leaf(), ee);
+        assert_eq!(chain.intermediates(), [inter]);
+        let subject = webpki::DistinguishedName::try_from(chain.anchor().subject)?;
+        match subject.to_string().contains("O=VeriSign\\, Inc.") {
+            true => Ok(()),
+            false => Err(webpki::Error::UnknownIssuer),
+        }
+    };
+    assert_eq!(verify(&options.with_chain_check(&verisign_only)), Ok(()));
+    assert_eq!(checked_chains.get(), 1);
+
+    let reject_all = |_: &webpki::VerifiedChain| -> Result<(), webpki::Error> {
+        Err(webpki::Error::CertBlocked)
+    };
+    assert_eq!(
+        verify(&options.with_chain_check(&reject_all)),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_input_sizes() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: &webpki::VerificationOptions| {
+        cert.verify_for_usage_with_options(&anchors, &[inter], options, webpki::Usage::TlsServer)
+    };
+
+    let max_len = ee.len().max(inter.len());
+    assert_eq!(
+        verify(&options.with_max_cert_len(max_len).with_max_intermediates(1)),
+        Ok(())
+    );
+    assert_eq!(
+        verify(&options.with_max_cert_len(max_len - 1)),
+        Err(webpki::Error::InputTooLarge)
+    );
+    assert_eq!(
+        verify(&options.with_max_intermediates(0)),
+        Err(webpki::Error::InputTooLarge)
+    );
+}
+
+#[test]
+pub fn require_eku_extension() {
+    let ee: &[u8] = include_bytes!("tsa/tsa_no_eku.der");
+    let ca = include_bytes!("tsa/ca.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        Ok(()),
+        cert.verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsClient)
+    );
+    assert_eq!(
+        Err(webpki::Error::RequiredEkuNotFound),
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[],
+            &options.require_eku_extension(),
+            webpki::Usage::TlsClient
+        )
+    );
+}
+
+#[test]
+fn nonconforming_serial() {
+    let ca = include_bytes!("nonconforming_serial/ca.der");
+    let ee: &[u8] = include_bytes!("nonconforming_serial/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(cert.as_cert().serial(), &[0xfe, 0xdc, 0xba]);
+    assert!(!cert.as_cert().serial_is_conforming());
+
+    let warnings = Recorder::default();
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+        .with_diagnostics(&warnings);
+    assert_eq!(
+        cert.verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsClient),
+        Ok(())
+    );
+    assert_eq!(
+        *warnings.warnings.borrow(),
+        [webpki::Warning::NonConformingSerial { depth: 0 }]
+    );
+
+    // The serial number is matched as the raw bytes of the INTEGER.
+    let blocklist = [webpki::BlockedCert::IssuerAndSerial {
+        issuer: cert.as_cert().issuer(),
+        serial: &[0xfe, 0xdc, 0xba],
+    }];
+    let options = options.with_blocklist(&blocklist);
+    assert_eq!(
+        cert.verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsClient),
+        Err(webpki::Error::CertBlocked)
+    );
+}
+
+#[test]
+fn ca_subject_key_id() {
+    let netflix_inter: &[u8] = include_bytes!("netflix/inter.der");
+    assert_eq!(
+        webpki::EndEntityCert::try_from(netflix_inter)
+            .unwrap()
+            .as_cert()
+            .subject_key_id(),
+        Ok(Some(
+            &[
+                0x5f, 0x60, 0xcf, 0x61, 0x90, 0x55, 0xdf, 0x84, 0x43, 0x14, 0x8a, 0x60, 0x2a, 0xb2,
+                0xf5, 0x7a, 0xf4, 0x43, 0x18, 0xef
+            ][..]
+        ))
+    );
+
+    // None of these certificates has a subject key identifier.
+    let ca = include_bytes!("self_issued/ca.der");
+    let inter: &[u8] = include_bytes!("self_issued/inter.der");
+    let rollover: &[u8] = include_bytes!("self_issued/rollover.der");
+    let ee: &[u8] = include_bytes!("self_issued/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let warnings = Recorder::default();
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+        .with_diagnostics(&warnings);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: &webpki::VerificationOptions| {
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[rollover, inter],
+            options,
+            webpki::Usage::TlsClient,
+        )
+    };
+    assert_eq!(verify(&options), Ok(()));
+    assert_eq!(
+        *warnings.warnings.borrow(),
+        [
+            webpki::Warning::MissingSubjectKeyId { depth: 1 },
+            webpki::Warning::MissingKeyUsage { depth: 1 },
+            webpki::Warning::MissingSubjectKeyId { depth: 2 },
+            webpki::Warning::MissingKeyUsage { depth: 2 },
+        ]
+    );
+    assert_eq!(
+        verify(&options.require_ca_subject_key_id()),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[test]
+fn web_pki_profile() {
+    let ca = include_bytes!("web_pki_profile/ca.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_612_137_600); // 2021-02-01T00:00:00Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+    let verify = |ee: &[u8], options: &webpki::VerificationOptions| {
+        webpki::EndEntityCert::try_from(ee)
+            .unwrap()
+            .verify_for_usage_with_options(&anchors, &[], options, webpki::Usage::TlsServer)
+    };
+    let verify_profile = |ee: &[u8]| {
+        verify(
+            ee,
+            &options.with_web_pki_profile(webpki::WebPkiProfile::BaselineRequirements),
+        )
+    };
+
+    let ee_398_days = include_bytes!("web_pki_profile/ee_398_days.der");
+    let ee_399_days = include_bytes!("web_pki_profile/ee_399_days.der");
+    let ee_2019_825_days = include_bytes!("web_pki_profile/ee_2019_825_days.der");
+    let ee_2019_826_days = include_bytes!("web_pki_profile/ee_2019_826_days.der");
+    let ee_short_serial = include_bytes!("web_pki_profile/ee_short_serial.der");
+    let ee_2015 = include_bytes!("web_pki_profile/ee_2015.der");
+
+    // Without a profile, only RFC 5280 applies.
+    for ee in &[
+        &ee_398_days[..],
+        ee_399_days,
+        ee_2019_825_days,
+        ee_2019_826_days,
+        ee_short_serial,
+        ee_2015,
+    ] {
+        assert_eq!(verify(ee, &options), Ok(()));
+    }
+
+    assert_eq!(verify_profile(ee_398_days), Ok(()));
+    assert_eq!(
+        verify_profile(ee_399_days),
+        Err(webpki::Error::CertValidityTooLong)
+    );
+    assert_eq!(verify_profile(ee_2019_825_days), Ok(()));
+    assert_eq!(
+        verify_profile(ee_2019_826_days),
+        Err(webpki::Error::CertValidityTooLong)
+    );
+    // A short serial number is only a warning, since one with enough
+    // entropy can be short too.
+    let warnings = Recorder::default();
+    assert_eq!(
+        verify(
+            ee_short_serial,
+            &options
+                .with_web_pki_profile(webpki::WebPkiProfile::BaselineRequirements)
+                .with_diagnostics(&warnings)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        warnings.warnings.into_inner(),
+        [webpki::Warning::LowEntropySerial { depth: 0 }]
+    );
+    // Issued before any of the requirements took effect.
+    assert_eq!(verify_profile(ee_2015), Ok(()));
+
+    // The SHA-1 signature isn't even verified.
+    let ee_sha1 = include_bytes!("web_pki_profile/ee_sha1.der");
+    assert_eq!(
+        verify_profile(ee_sha1),
+        Err(webpki::Error::Sha1SignatureNotAllowed)
+    );
+}
+
+#[test]
+fn leaf_serial_policy() {
+    let ca = include_bytes!("web_pki_profile/ca.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_612_137_600); // 2021-02-01T00:00:00Z
+    let verify = |ee: &[u8], policy| {
+        let warnings = Recorder::default();
+        let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+            .with_leaf_serial_policy(policy)
+            .with_diagnostics(&warnings);
+        let result = webpki::EndEntityCert::try_from(ee)
+            .unwrap()
+            .verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsServer);
+        (result, warnings.warnings.into_inner())
+    };
+
+    let ee_398_days = include_bytes!("web_pki_profile/ee_398_days.der");
+    for policy in &[
+        webpki::LeafSerialPolicy::Warn,
+        webpki::LeafSerialPolicy::Reject,
+    ] {
+        assert_eq!(verify(ee_398_days, *policy), (Ok(()), vec![]));
+    }
+
+    // Unlike with the Baseline Requirements profile, the issuance date
+    // doesn't matter.
+    let ee_short_serial = include_bytes!("web_pki_profile/ee_short_serial.der");
+    let ee_2015 = include_bytes!("web_pki_profile/ee_2015.der");
+    for ee in &[&ee_short_serial[..], ee_2015] {
+        assert_eq!(
+            verify(ee, webpki::LeafSerialPolicy::Warn),
+            (Ok(()), vec![webpki::Warning::LowEntropyLeafSerial])
+        );
+        assert_eq!(
+            verify(ee, webpki::LeafSerialPolicy::Reject),
+            (Err(webpki::Error::LowEntropySerial), vec![])
+        );
+    }
+}
+
+#[test]
+fn max_leaf_validity() {
+    let ca = include_bytes!("web_pki_profile/ca.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_612_137_600); // 2021-02-01T00:00:00Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+    let verify = |ee: &[u8], max_validity_secs| {
+        webpki::EndEntityCert::try_from(ee)
+            .unwrap()
+            .verify_for_usage_with_options(
+                &anchors,
+                &[],
+                &options.with_max_leaf_validity(max_validity_secs),
+                webpki::Usage::TlsServer,
+            )
+    };
+
+    const DAY: u64 = 24 * 60 * 60;
+    let ee_90_days = include_bytes!("web_pki_profile/ee_short_serial.der");
+    let ee_398_days = include_bytes!("web_pki_profile/ee_398_days.der");
+
+    assert_eq!(verify(ee_90_days, 90 * DAY), Ok(()));
+    assert_eq!(
+        verify(ee_90_days, 90 * DAY - 1),
+        Err(webpki::Error::LeafValidityTooLong {
+            validity_secs: 90 * DAY,
+            max_validity_secs: 90 * DAY - 1,
+        })
+    );
+    assert_eq!(
+        verify(ee_398_days, 90 * DAY),
+        Err(webpki::Error::LeafValidityTooLong {
+            validity_secs: 398 * DAY,
+            max_validity_secs: 90 * DAY,
+        })
+    );
+    assert_eq!(verify(ee_398_days, 398 * DAY), Ok(()));
+}
+
+#[test]
+fn expired_trust_anchor() {
+    let ca = include_bytes!("web_pki_profile/ca.der");
+    let ee: &[u8] = include_bytes!("web_pki_profile/ee_398_days.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_612_137_600); // 2021-02-01T00:00:00Z
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |options: &webpki::VerificationOptions| {
+        cert.verify_for_usage_with_options(&anchors, &[], options, webpki::Usage::TlsServer)
+    };
+
+    // The anchor's certificate is valid until the year 3000.
+    let expiry = webpki::AnchorExpiry::try_from_cert_der(ca).unwrap();
+    assert_eq!(expiry.spki, anchors[0].spki);
+    assert_eq!(
+        expiry.not_after,
+        webpki::Time::from_seconds_since_unix_epoch(32_503_680_000)
+    );
+    let diagnostics = Recorder::default();
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+        .with_diagnostics(&diagnostics)
+        .with_anchor_expiries(core::slice::from_ref(&expiry))
+        .reject_expired_anchors();
+    assert_eq!(verify(&options), Ok(()));
+    assert!(diagnostics.warnings.borrow().is_empty());
+
+    let expired = [webpki::AnchorExpiry {
+        spki: anchors[0].spki,
+        not_after: webpki::Time::from_seconds_since_unix_epoch(1_612_137_599),
+    }];
+    let diagnostics = Recorder::default();
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time)
+        .with_diagnostics(&diagnostics)
+        .with_anchor_expiries(&expired);
+    assert_eq!(verify(&options), Ok(()));
+    assert_eq!(
+        *diagnostics.warnings.borrow(),
+        [webpki::Warning::ExpiredTrustAnchor { index: 0 }]
+    );
+
+    let diagnostics = Recorder::default();
+    assert_eq!(
+        verify(
+            &options
+                .with_diagnostics(&diagnostics)
+                .reject_expired_anchors()
+        ),
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert_eq!(
+        *diagnostics.rejections.borrow(),
+        [webpki::Error::TrustAnchorExpired]
+    );
+}
+
+// The KeyUsage extension of end-entity certificates is not checked, so a
+// certificate for RSA key exchange only, without digitalSignature, is accepted
+// like any other.
+#[test]
+fn key_encipherment_only() {
+    let ca = include_bytes!("key_usage/ca.der");
+    let ee: &[u8] = include_bytes!("key_usage/key_encipherment_ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsClient),
+        Ok(())
+    );
+}
+
+#[test]
+fn ca_key_usage() {
+    let ca = include_bytes!("ca_key_usage/ca.der");
+    let key_cert_sign: &[u8] = include_bytes!("ca_key_usage/inter_key_cert_sign.der");
+    let crl_sign_only: &[u8] = include_bytes!("ca_key_usage/inter_crl_sign_only.der");
+    let no_key_usage: &[u8] = include_bytes!("ca_key_usage/inter_no_key_usage.der");
+    let ee: &[u8] = include_bytes!("ca_key_usage/ee.der");
+
+    let key_cert_sign_of = |der| {
+        webpki::EndEntityCert::try_from(der)
+            .unwrap()
+            .as_cert()
+            .key_cert_sign()
+    };
+    assert_eq!(key_cert_sign_of(key_cert_sign), Ok(Some(true)));
+    assert_eq!(key_cert_sign_of(crl_sign_only), Ok(Some(false)));
+    assert_eq!(key_cert_sign_of(no_key_usage), Ok(None));
+    assert_eq!(key_cert_sign_of(ee), Ok(Some(false)));
+
+    // The keyUsage extension is only parsed when it is checked, so a
+    // malformed one only matters for CA certificates.
+    let mut malformed = ee.to_vec();
+    let key_usage = [0x03, 0x02, 0x07, 0x80];
+    let pos = malformed
+        .windows(key_usage.len())
+        .position(|window| window == &key_usage[..])
+        .unwrap();
+    malformed[pos] = 0x04;
+    assert_eq!(key_cert_sign_of(&malformed[..]), Err(webpki::Error::BadDer));
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let diagnostics = Recorder::default();
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+        .with_diagnostics(&diagnostics);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let verify = |intermediate: &[u8], options: &webpki::VerificationOptions| {
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[intermediate],
+            options,
+            webpki::Usage::TlsClient,
+        )
+    };
+
+    assert_eq!(verify(key_cert_sign, &options), Ok(()));
+    assert_eq!(
+        verify(key_cert_sign, &options.require_ca_key_usage()),
+        Ok(())
+    );
+    assert!(diagnostics.rejections.borrow().is_empty());
+    assert!(diagnostics.warnings.borrow().is_empty());
+
+    assert_eq!(
+        verify(crl_sign_only, &options),
+        Err(webpki::Error::KeyCertSignNotAsserted)
+    );
+    assert_eq!(
+        *diagnostics.rejections.borrow(),
+        [webpki::Error::KeyCertSignNotAsserted]
+    );
+    diagnostics.rejections.borrow_mut().clear();
+
+    assert_eq!(verify(no_key_usage, &options), Ok(()));
+    assert_eq!(
+        *diagnostics.warnings.borrow(),
+        [webpki::Warning::MissingKeyUsage { depth: 1 }]
+    );
+    assert_eq!(
+        verify(no_key_usage, &options.require_ca_key_usage()),
+        Err(webpki::Error::MissingKeyUsage)
+    );
+    assert_eq!(
+        *diagnostics.rejections.borrow(),
+        [webpki::Error::MissingKeyUsage]
+    );
+}
@@ -0,0 +1,103 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "proxy-certs", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+static CA: &[u8] = include_bytes!("proxy_certs/ca.der");
+static EEC: &[u8] = include_bytes!("proxy_certs/eec.der");
+static PROXY: &[u8] = include_bytes!("proxy_certs/proxy.der");
+static PROXY_PATHLEN_0: &[u8] = include_bytes!("proxy_certs/proxy_pathlen_0.der");
+static PROXY_OF_PROXY: &[u8] = include_bytes!("proxy_certs/proxy_of_proxy.der");
+
+fn check(ee: &[u8], proxy_issuers: &[&[u8]]) -> Result<(), webpki::Error> {
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(CA).unwrap()];
+    let anchors = webpki::TlsClientTrustAnchors(&anchors);
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d);
+
+    let cert = webpki::EndEntityCert::try_from(ee)?;
+    cert.verify_is_valid_tls_client_proxy_cert(
+        &[&webpki::ECDSA_P256_SHA256],
+        &anchors,
+        proxy_issuers,
+        &[],
+        time,
+    )
+}
+
+#[test]
+fn proxy() {
+    assert_eq!(check(PROXY, &[EEC]), Ok(()));
+}
+
+#[test]
+fn proxy_of_proxy() {
+    assert_eq!(check(PROXY_OF_PROXY, &[PROXY, EEC]), Ok(()));
+    assert_eq!(
+        check(PROXY_OF_PROXY, &[PROXY_PATHLEN_0, EEC]),
+        Err(webpki::Error::ProxyPathLenConstraintViolated)
+    );
+}
+
+#[test]
+fn end_entity_without_proxies() {
+    assert_eq!(check(EEC, &[]), Ok(()));
+}
+
+#[test]
+fn proxy_chain_must_end_with_end_entity() {
+    assert_eq!(check(PROXY, &[]), Err(webpki::Error::InvalidProxyCert));
+    assert_eq!(
+        check(PROXY_OF_PROXY, &[PROXY]),
+        Err(webpki::Error::InvalidProxyCert)
+    );
+}
+
+#[test]
+fn proxy_issued_by_ca() {
+    let proxy = include_bytes!("proxy_certs/proxy_from_ca.der");
+    assert_eq!(check(proxy, &[CA]), Err(webpki::Error::InvalidProxyCert));
+}
+
+#[test]
+fn proxy_subject_must_extend_issuer() {
+    let proxy = include_bytes!("proxy_certs/proxy_bad_subject.der");
+    assert_eq!(check(proxy, &[EEC]), Err(webpki::Error::InvalidProxyCert));
+}
+
+#[test]
+fn proxy_with_subject_alt_name() {
+    let proxy = include_bytes!("proxy_certs/proxy_with_san.der");
+    assert_eq!(check(proxy, &[EEC]), Err(webpki::Error::InvalidProxyCert));
+}
+
+#[test]
+fn proxy_rejected_by_normal_verification() {
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(CA).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d);
+
+    let cert = webpki::EndEntityCert::try_from(PROXY).unwrap();
+    assert_eq!(
+        cert.verify_for_usage(
+            &[&webpki::ECDSA_P256_SHA256],
+            &anchors,
+            &[EEC],
+            time,
+            webpki::Usage::TlsClient
+        ),
+        Err(webpki::Error::InvalidProxyCert)
+    );
+}
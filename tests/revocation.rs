@@ -0,0 +1,389 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::ALL_SIGALGS;
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_blocklist() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter_cert = webpki::EndEntityCert::try_from(inter).unwrap();
+    let verify = |blocklist: &[webpki::BlockedCert]| {
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options.with_blocklist(blocklist),
+            webpki::Usage::TlsServer,
+        )
+    };
+
+    assert_eq!(verify(&[]), Ok(()));
+    assert_eq!(verify(&[webpki::BlockedCert::CertSha256([0; 32])]), Ok(()));
+    assert_eq!(
+        verify(&[webpki::BlockedCert::CertSha256(cert.sha256_fingerprint())]),
+        Err(webpki::Error::CertBlocked)
+    );
+    assert_eq!(
+        verify(&[webpki::BlockedCert::IssuerAndSerial {
+            issuer: cert.as_cert().issuer(),
+            serial: cert.serial(),
+        }]),
+        Err(webpki::Error::CertBlocked)
+    );
+    assert_eq!(
+        verify(&[webpki::BlockedCert::IssuerSpkiAndSerial {
+            issuer_spki_sha256: inter_cert.spki_sha256(),
+            serial: cert.serial(),
+        }]),
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert_eq!(
+        verify(&[webpki::BlockedCert::CertSha256(
+            inter_cert.sha256_fingerprint()
+        )]),
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert_eq!(
+        verify(&[webpki::BlockedCert::CertId(
+            cert.cert_id(inter_cert.as_cert().spki_der())
+        )]),
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert_eq!(
+        verify(&[webpki::BlockedCert::CertId(
+            cert.cert_id(cert.as_cert().spki_der())
+        )]),
+        Ok(())
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_revocation() {
+    use webpki::{RevocationReason, RevocationStatus, UnknownStatusPolicy};
+
+    struct Statuses<'a>(&'a [(webpki::CertId<'a>, RevocationStatus)]);
+
+    impl webpki::RevocationStatusProvider for Statuses<'_> {
+        fn status(&self, cert_id: &webpki::CertId) -> RevocationStatus {
+            self.0
+                .iter()
+                .find(|(id, _)| id == cert_id)
+                .map_or(RevocationStatus::Unknown, |(_, status)| *status)
+        }
+    }
+
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter_cert = webpki::EndEntityCert::try_from(inter).unwrap();
+    let verify = |status: RevocationStatus, unknown_status_policy| {
+        let statuses = [(cert.cert_id(inter_cert.as_cert().spki_der()), status)];
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options.with_revocation_status_provider(&Statuses(&statuses), unknown_status_policy),
+            webpki::Usage::TlsServer,
+        )
+    };
+
+    let revoked_at = |seconds| {
+        RevocationStatus::Revoked(
+            RevocationReason::KeyCompromise,
+            webpki::Time::from_seconds_since_unix_epoch(seconds),
+        )
+    };
+
+    assert_eq!(
+        verify(RevocationStatus::Good, UnknownStatusPolicy::Allow),
+        Ok(())
+    );
+    assert_eq!(
+        verify(revoked_at(1_492_441_716), UnknownStatusPolicy::Allow),
+        Err(webpki::Error::CertRevoked)
+    );
+    assert_eq!(
+        verify(revoked_at(1_492_441_717), UnknownStatusPolicy::Allow),
+        Ok(())
+    );
+    assert_eq!(
+        verify(RevocationStatus::Unknown, UnknownStatusPolicy::Allow),
+        Ok(())
+    );
+    assert_eq!(
+        verify(RevocationStatus::Unknown, UnknownStatusPolicy::Deny),
+        Err(webpki::Error::UnknownRevocationStatus)
+    );
+    // The status of the intermediate certificate is unknown, so it can't be
+    // used as the end-entity certificate's issuer.
+    assert_eq!(
+        verify(RevocationStatus::Good, UnknownStatusPolicy::Deny),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_revocation_next_update() {
+    use webpki::{CertId, RevocationStatus, Time};
+
+    struct NextUpdates<'a>(&'a [(CertId<'a>, Time)]);
+
+    impl webpki::RevocationStatusProvider for NextUpdates<'_> {
+        fn status(&self, _: &CertId) -> RevocationStatus {
+            RevocationStatus::Good
+        }
+
+        fn next_update(&self, cert_id: &CertId) -> Option<Time> {
+            self.0
+                .iter()
+                .find(|(id, _)| id == cert_id)
+                .map(|(_, next_update)| *next_update)
+        }
+    }
+
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter_cert = webpki::EndEntityCert::try_from(inter).unwrap();
+    let chain = cert
+        .build_path(webpki::EKU_SERVER_AUTH, &anchors, &[inter], &options)
+        .unwrap();
+
+    let ee_id = cert.cert_id(inter_cert.as_cert().spki_der());
+    let inter_id = webpki::CertId::issued_by_anchor(inter_cert.as_cert(), &anchors[0]).unwrap();
+    let ee_next_update = Time::from_seconds_since_unix_epoch(1_492_500_000);
+    let inter_next_update = Time::from_seconds_since_unix_epoch(1_492_450_000);
+
+    assert_eq!(chain.revocation_next_update(&NextUpdates(&[])), None);
+    assert_eq!(
+        chain.revocation_next_update(&NextUpdates(&[(ee_id, ee_next_update)])),
+        Some(ee_next_update)
+    );
+    assert_eq!(
+        chain.revocation_next_update(&NextUpdates(&[
+            (ee_id, ee_next_update),
+            (inter_id, inter_next_update),
+        ])),
+        Some(inter_next_update)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_filter_cascade() {
+    use webpki::{
+        BloomFilter, FilterCascade, RevocationReason, RevocationStatus, RevocationStatusProvider,
+        Time, UnknownStatusPolicy,
+    };
+
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter_cert = webpki::EndEntityCert::try_from(inter).unwrap();
+    let ee_id = cert.cert_id(inter_cert.as_cert().spki_der());
+
+    // A filter with every bit set contains every certificate, and one with
+    // none set contains none.
+    let all = BloomFilter::new(&[0xff; 16], 3).unwrap();
+    let none = BloomFilter::new(&[0; 16], 3).unwrap();
+    assert!(BloomFilter::new(&[0xff; 16], 0).is_none());
+    let enrolled = [ee_id.issuer_spki_sha256];
+    let cascade = |layers: &[BloomFilter], cert_id| {
+        let cascade = FilterCascade {
+            salt: b"salt",
+            layers,
+            enrolled_issuers: &enrolled,
+        };
+        (cascade.contains(cert_id), cascade.status(cert_id))
+    };
+    let revoked = RevocationStatus::Revoked(
+        RevocationReason::Unspecified,
+        Time::from_seconds_since_unix_epoch(0),
+    );
+
+    assert_eq!(cascade(&[], &ee_id), (false, RevocationStatus::Good));
+    assert_eq!(cascade(&[none], &ee_id), (false, RevocationStatus::Good));
+    assert_eq!(cascade(&[all], &ee_id), (true, revoked));
+    assert_eq!(cascade(&[all, none], &ee_id), (true, revoked));
+    assert_eq!(
+        cascade(&[all, all], &ee_id),
+        (false, RevocationStatus::Good)
+    );
+    assert_eq!(
+        cascade(&[all, all, none], &ee_id),
+        (false, RevocationStatus::Good)
+    );
+    assert_eq!(cascade(&[all, all, all], &ee_id), (true, revoked));
+
+    // The intermediate certificate's issuer isn't enrolled, so its status
+    // is unknown.
+    let inter_id = webpki::CertId::issued_by_anchor(inter_cert.as_cert(), &anchors[0]).unwrap();
+    assert_eq!(
+        cascade(&[all], &inter_id),
+        (true, RevocationStatus::Unknown)
+    );
+
+    let verify = |layers: &[BloomFilter], unknown_status_policy| {
+        let cascade = FilterCascade {
+            salt: b"salt",
+            layers,
+            enrolled_issuers: &enrolled,
+        };
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[inter],
+            &options.with_revocation_status_provider(&cascade, unknown_status_policy),
+            webpki::Usage::TlsServer,
+        )
+    };
+    assert_eq!(verify(&[none], UnknownStatusPolicy::Allow), Ok(()));
+    assert_eq!(
+        verify(&[all], UnknownStatusPolicy::Allow),
+        Err(webpki::Error::CertRevoked)
+    );
+    assert_eq!(
+        verify(&[none], UnknownStatusPolicy::Deny),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn netflix_chain_revocation() {
+    use webpki::{CertId, RevocationReason, RevocationStatus, Time, UnknownStatusPolicy};
+
+    struct Statuses<'a>(&'a [(CertId<'a>, RevocationStatus)]);
+
+    impl webpki::RevocationStatusProvider for Statuses<'_> {
+        fn status(&self, cert_id: &CertId) -> RevocationStatus {
+            self.0
+                .iter()
+                .find(|(id, _)| id == cert_id)
+                .map_or(RevocationStatus::Unknown, |(_, status)| *status)
+        }
+    }
+
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter: &[u8] = include_bytes!("netflix/inter.der");
+    let ca = include_bytes!("netflix/ca.der");
+
+    let anchors = vec![webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+
+    let time = Time::from_seconds_since_unix_epoch(1_492_441_716); // 2017-04-17T15:08:36Z
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+
+    // The chain is verified without revocation checking, which is done
+    // afterwards.
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+    let inter_cert = webpki::EndEntityCert::try_from(inter).unwrap();
+    let chain = cert
+        .build_path(webpki::EKU_SERVER_AUTH, &anchors, &[inter], &options)
+        .unwrap();
+    assert_eq!(chain.check_revocation(&options), Ok(()));
+
+    let ee_id = cert.cert_id(inter_cert.as_cert().spki_der());
+    let inter_id = CertId::issued_by_anchor(inter_cert.as_cert(), &anchors[0]).unwrap();
+    let revoked = RevocationStatus::Revoked(RevocationReason::KeyCompromise, time);
+    let check = |statuses: &[(CertId, RevocationStatus)], unknown_status_policy| {
+        chain.check_revocation(
+            &options.with_revocation_status_provider(&Statuses(statuses), unknown_status_policy),
+        )
+    };
+
+    assert_eq!(
+        check(
+            &[
+                (ee_id, RevocationStatus::Good),
+                (inter_id, RevocationStatus::Good)
+            ],
+            UnknownStatusPolicy::Deny
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check(&[(ee_id, revoked)], UnknownStatusPolicy::Allow),
+        Err(webpki::Error::CertRevoked)
+    );
+    assert_eq!(
+        check(&[(inter_id, revoked)], UnknownStatusPolicy::Allow),
+        Err(webpki::Error::CertRevoked)
+    );
+    assert_eq!(
+        check(
+            &[(ee_id, RevocationStatus::Good)],
+            UnknownStatusPolicy::Deny
+        ),
+        Err(webpki::Error::UnknownRevocationStatus)
+    );
+
+    // The identities kept from the verification are looked up again when
+    // new revocation data arrives.
+    let cert_ids: Vec<CertId> = chain.cert_ids().collect();
+    assert_eq!(cert_ids, [ee_id, inter_id]);
+    let revoked_ids = |statuses: &[(CertId, RevocationStatus)], time| {
+        webpki::revoked_cert_ids(&cert_ids, &Statuses(statuses), time)
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        revoked_ids(
+            &[(ee_id, RevocationStatus::Good), (inter_id, revoked)],
+            time
+        ),
+        [inter_id]
+    );
+    assert_eq!(revoked_ids(&[(ee_id, revoked)], time), [ee_id]);
+    assert!(revoked_ids(
+        &[(ee_id, revoked)],
+        Time::from_seconds_since_unix_epoch(1_492_441_715)
+    )
+    .is_empty());
+}
@@ -0,0 +1,235 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tls-server-verify", feature = "tls-client-verify"))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+mod common;
+use common::{Recorder, ALL_SIGALGS};
+
+#[test]
+fn lenient_ecdsa_signature() {
+    // The r integer of the end-entity certificate's signature has a
+    // superfluous leading zero.
+    let ca = include_bytes!("lenient_ecdsa/ca.der");
+    let ee: &[u8] = include_bytes!("lenient_ecdsa/ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    assert_eq!(
+        cert.verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsClient),
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[],
+            &options.accept_lenient_ecdsa_signatures(),
+            webpki::Usage::TlsClient
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn nonstandard_algorithm_parameters() {
+    // The end-entity certificate is signed with ecdsa-with-SHA256 with NULL
+    // parameters, which RFC 5758 forbids.
+    let ca = include_bytes!("algorithm_parameters/ca.der");
+    let ee: &[u8] = include_bytes!("algorithm_parameters/ee_null_parameters.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let warnings = Recorder::default();
+    let options = webpki::VerificationOptions::new(&[&webpki::ECDSA_P256_SHA256], time)
+        .with_diagnostics(&warnings);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    assert_eq!(
+        cert.verify_for_usage_with_options(&anchors, &[], &options, webpki::Usage::TlsClient),
+        Err(webpki::Error::UnknownIssuer)
+    );
+    assert!(warnings.warnings.borrow().is_empty());
+
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[],
+            &options.accept_nonstandard_algorithm_parameters(),
+            webpki::Usage::TlsClient
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        *warnings.warnings.borrow(),
+        [webpki::Warning::NonstandardAlgorithmParameters { depth: 0 }]
+    );
+}
+
+#[test]
+fn custom_signature_algorithm() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), webpki::Error> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        ring::signature::UnparsedPublicKey::new(
+            &ring::signature::ECDSA_P256_SHA256_ASN1,
+            public_key,
+        )
+        .verify(message, signature)
+        .map_err(|_| webpki::Error::InvalidSignatureForPublicKey)
+    }
+
+    fn reject(_: &[u8], _: &[u8], _: &[u8]) -> Result<(), webpki::Error> {
+        Err(webpki::Error::InvalidSignatureForPublicKey)
+    }
+
+    let public_key_alg_id = include_bytes!("../src/data/alg-ecdsa-p256.der");
+    let signature_alg_id = include_bytes!("../src/data/alg-ecdsa-sha256.der");
+    let custom = webpki::SignatureAlgorithm::custom(
+        "CUSTOM_ECDSA_P256_SHA256",
+        public_key_alg_id,
+        signature_alg_id,
+        verify,
+    );
+    assert_eq!(custom.name(), "CUSTOM_ECDSA_P256_SHA256");
+    assert_eq!(custom.key_kind(), webpki::KeyKind::Other);
+    assert_eq!(custom.digest(), None);
+
+    let ca = include_bytes!("directory_name_constraints/ca.der");
+    let ee: &[u8] = include_bytes!("directory_name_constraints/permitted.ee.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    assert_eq!(
+        cert.verify_for_usage(&[&custom], &anchors, &[], time, webpki::Usage::TlsServer),
+        Ok(())
+    );
+    assert!(CALLS.load(Ordering::SeqCst) > 0);
+
+    let rejecting = webpki::SignatureAlgorithm::custom(
+        "REJECTING",
+        public_key_alg_id,
+        signature_alg_id,
+        reject,
+    );
+    assert_eq!(
+        cert.verify_for_usage(&[&rejecting], &anchors, &[], time, webpki::Usage::TlsServer),
+        Err(webpki::Error::UnknownIssuer)
+    );
+}
+
+#[test]
+fn key_agreement_key_not_usable_for_signatures() {
+    let ee: &[u8] = include_bytes!("key_agreement/x25519_ee.der");
+    let cert = webpki::EndEntityCert::try_from(ee).unwrap();
+
+    for alg in webpki::ALL_SIGNATURE_ALGORITHMS {
+        assert_eq!(
+            cert.verify_signature(alg, b"message", b"signature"),
+            Err(webpki::Error::KeyTypeNotUsableForSignatures)
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn rsa_exponent_policy() {
+    // The CA and `rsa_ee.der` have RSA keys with a public exponent of 3.
+    let ca = include_bytes!("rsa_exponent/ca.der");
+    let rsa_ee: &[u8] = include_bytes!("rsa_exponent/rsa_ee.der");
+    let ecdsa_ee: &[u8] = include_bytes!("rsa_exponent/ecdsa_ee.der");
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let time = webpki::Time::from_seconds_since_unix_epoch(0x5fed_f00d);
+    let options = webpki::VerificationOptions::new(ALL_SIGALGS, time);
+    let cert = webpki::EndEntityCert::try_from(ecdsa_ee).unwrap();
+
+    for &policy in &[
+        webpki::RsaExponentPolicy::Any,
+        webpki::RsaExponentPolicy::OddAtLeast3,
+    ] {
+        assert_eq!(
+            cert.verify_for_usage_with_options(
+                &anchors,
+                &[],
+                &options.with_rsa_exponent_policy(policy),
+                webpki::Usage::TlsClient
+            ),
+            Ok(())
+        );
+    }
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[],
+            &options.with_rsa_exponent_policy(webpki::RsaExponentPolicy::Only65537),
+            webpki::Usage::TlsClient
+        ),
+        Err(webpki::Error::UnknownIssuer)
+    );
+
+    let cert = webpki::EndEntityCert::try_from(rsa_ee).unwrap();
+    assert_eq!(
+        cert.verify_for_usage_with_options(
+            &anchors,
+            &[],
+            &options.with_rsa_exponent_policy(webpki::RsaExponentPolicy::Only65537),
+            webpki::Usage::TlsClient
+        ),
+        Err(webpki::Error::RsaExponentNotAllowed)
+    );
+}
+
+#[test]
+fn signature_algorithm_mismatch() {
+    // The tbsCertificate's signature field is ecdsa-with-SHA384, but the
+    // certificate's signatureAlgorithm is ecdsa-with-SHA256.
+    let ee: &[u8] = include_bytes!("misc/signature_algorithm_mismatch.der");
+    assert_eq!(
+        webpki::EndEntityCert::try_from(ee).err(),
+        Some(webpki::Error::SignatureAlgorithmMismatch)
+    );
+}
+
+#[test]
+fn all_signature_algorithms() {
+    for alg in ALL_SIGALGS {
+        assert!(webpki::ALL_SIGNATURE_ALGORITHMS
+            .iter()
+            .any(|supported| supported.name() == alg.name()));
+    }
+    for (i, alg) in webpki::ALL_SIGNATURE_ALGORITHMS.iter().enumerate() {
+        assert!(webpki::ALL_SIGNATURE_ALGORITHMS[i + 1..]
+            .iter()
+            .all(|other| other.name() != alg.name()));
+    }
+
+    let alg = webpki::ALL_SIGNATURE_ALGORITHMS
+        .iter()
+        .find(|alg| alg.name() == "ECDSA_P384_SHA256")
+        .unwrap();
+    assert_eq!(alg.key_kind(), webpki::KeyKind::EcdsaP384);
+    assert_eq!(alg.digest(), Some(webpki::Digest::Sha256));
+    assert_eq!(webpki::ED25519.key_kind(), webpki::KeyKind::Ed25519);
+    assert_eq!(webpki::ED25519.digest(), None);
+}
@@ -0,0 +1,86 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(
+    feature = "test-utils",
+    feature = "tls-server-verify",
+    feature = "tls-client-verify"
+))]
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+#[test]
+fn test_utils_certs() {
+    use webpki::testing::{CertBuilder, Clock, FixedClock, KeyPair};
+
+    let ca_key = KeyPair::from_seed(&[1; 32]);
+    let inter_key = KeyPair::from_seed(&[2; 32]);
+    let ee_key = KeyPair::from_seed(&[3; 32]);
+
+    let ca = CertBuilder::new("test CA").ca().self_signed(&ca_key);
+    let inter = CertBuilder::new("test intermediate")
+        .ca()
+        .serial(2)
+        .issued_by(&inter_key, "test CA", &ca_key);
+    let ee_builder = CertBuilder::new("test end-entity")
+        .serial(3)
+        .dns_name("example.com")
+        .eku(webpki::EKU_SERVER_AUTH);
+    let ee = ee_builder.issued_by(&ee_key, "test intermediate", &inter_key);
+    assert_eq!(
+        ee,
+        ee_builder.issued_by(&ee_key, "test intermediate", &inter_key)
+    );
+
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(&ca).unwrap()];
+    let time = FixedClock::at_utc(2017, 4, 17, 15, 8, 36).unwrap().now();
+    let cert = webpki::EndEntityCert::try_from(&ee[..]).unwrap();
+    assert_eq!(
+        cert.verify_for_usage(
+            &[&webpki::ED25519],
+            &anchors,
+            &[&inter[..]],
+            time,
+            webpki::Usage::TlsServer
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        cert.verify_is_valid_for_subject_name(
+            webpki::SubjectNameRef::try_from_ascii_str("example.com").unwrap()
+        ),
+        Ok(())
+    );
+
+    let expired = ee_builder
+        .clone()
+        .validity(
+            webpki::Time::from_seconds_since_unix_epoch(0),
+            webpki::Time::from_seconds_since_unix_epoch(1_492_441_715),
+        )
+        .issued_by(&ee_key, "test intermediate", &inter_key);
+    assert_eq!(
+        webpki::EndEntityCert::try_from(&expired[..])
+            .unwrap()
+            .verify_for_usage(
+                &[&webpki::ED25519],
+                &anchors,
+                &[&inter[..]],
+                time,
+                webpki::Usage::TlsServer
+            ),
+        Err(webpki::Error::CertExpired)
+    );
+}
@@ -0,0 +1,89 @@
+// Copyright 2016 Joseph Birr-Pixton.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use core::convert::TryFrom;
+extern crate webpki;
+
+fn verify(ee: &[u8], time: webpki::Time) -> Result<(), webpki::Error> {
+    let ca = include_bytes!("tsa/ca.der");
+    let anchors = [webpki::TrustAnchor::try_from_cert_der(ca).unwrap()];
+    let anchors = webpki::TsaTrustAnchors(&anchors);
+
+    let cert = webpki::EndEntityCert::try_from(ee)?;
+    cert.verify_is_valid_tsa_cert(&[&webpki::ECDSA_P256_SHA256], &anchors, &[], time)
+}
+
+fn time() -> webpki::Time {
+    webpki::Time::from_seconds_since_unix_epoch(0x1fed_f00d)
+}
+
+#[test]
+fn tsa() {
+    assert_eq!(verify(include_bytes!("tsa/tsa.der"), time()), Ok(()));
+}
+
+#[test]
+fn tsa_eku_not_critical() {
+    assert_eq!(
+        verify(include_bytes!("tsa/tsa_eku_not_critical.der"), time()),
+        Err(webpki::Error::InvalidTsaCert)
+    );
+}
+
+#[test]
+fn tsa_eku_extra_purpose() {
+    assert_eq!(
+        verify(include_bytes!("tsa/tsa_eku_extra_purpose.der"), time()),
+        Err(webpki::Error::InvalidTsaCert)
+    );
+}
+
+#[test]
+fn tsa_no_eku() {
+    assert_eq!(
+        verify(include_bytes!("tsa/tsa_no_eku.der"), time()),
+        Err(webpki::Error::InvalidTsaCert)
+    );
+}
+
+#[test]
+fn tsa_as_of_gen_time() {
+    // 0x1fedf00d is 1986-12-23T01:59:41Z; the certificate is only valid for
+    // 30 seconds either side of it.
+    let gen_time = webpki::Time::from_generalized_time(b"19861223015941.25Z").unwrap();
+    assert_eq!(gen_time, time());
+    assert_eq!(verify(include_bytes!("tsa/tsa.der"), gen_time), Ok(()));
+
+    let later = webpki::Time::from_generalized_time(b"19861223020041Z").unwrap();
+    assert_eq!(
+        verify(include_bytes!("tsa/tsa.der"), later),
+        Err(webpki::Error::CertExpired)
+    );
+}
+
+#[test]
+fn gen_time_syntax() {
+    for invalid in &[
+        &b"19861223083941"[..],
+        b"19861223083941.Z",
+        b"19861223083941.50Z",
+        b"861223083941Z",
+        b"19861223083941+0000",
+    ] {
+        assert_eq!(
+            webpki::Time::from_generalized_time(invalid),
+            Err(webpki::Error::BadDerTime)
+        );
+    }
+}